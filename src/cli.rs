@@ -1,9 +1,12 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 #[derive(Parser, Debug)]
 #[command(name = "ccline")]
 #[command(version, about = "High-performance Claude Code StatusLine")]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Enter TUI configuration mode
     #[arg(short = 'c', long = "config")]
     pub config: bool,
@@ -31,6 +34,72 @@ pub struct Cli {
     /// Patch Claude Code cli.js to disable context warnings
     #[arg(long = "patch")]
     pub patch: Option<String>,
+
+    /// Never make network requests; network segments use cached data only
+    #[arg(long = "no-network", global = true)]
+    pub no_network: bool,
+
+    /// Output format of the rendered statusline
+    #[arg(long = "format", value_enum, default_value_t = OutputFormat::Ansi)]
+    pub format: OutputFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// ANSI escape sequences for terminals
+    Ansi,
+    /// Pango markup for i3blocks/waybar
+    Pango,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Inspect YesCode quota and spending
+    Quota(QuotaArgs),
+    /// Print a single line for a Starship custom command module
+    Starship(StarshipArgs),
+    /// Print a shell completion script
+    Completions(CompletionsArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct QuotaArgs {
+    /// Print a one-screen report of all quota metrics
+    #[arg(long = "summary")]
+    pub summary: bool,
+
+    /// Print the raw quota numbers as JSON (full precision, for scripts)
+    #[arg(long = "json", conflicts_with = "summary")]
+    pub json: bool,
+
+    /// Refresh the quota cache and exit (started by refresh = "background")
+    #[arg(long = "refresh", hide = true, conflicts_with_all = ["summary", "json"])]
+    pub refresh: bool,
+
+    /// Test connectivity: show the API key source and endpoints, then request each once
+    #[arg(long = "check", conflicts_with_all = ["summary", "json", "refresh"])]
+    pub check: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct StarshipArgs {
+    /// Give up on live network data after this many milliseconds and render from cache
+    #[arg(long = "timeout-ms", default_value_t = 300)]
+    pub timeout_ms: u64,
+}
+
+#[derive(Args, Debug)]
+pub struct CompletionsArgs {
+    /// Shell to generate the script for
+    #[arg(value_enum)]
+    pub shell: Shell,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
 }
 
 impl Cli {
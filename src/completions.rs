@@ -0,0 +1,308 @@
+//! Shell completion scripts generated from the clap command definition,
+//! so new subcommands and flags are picked up without touching this file.
+
+use crate::cli::{Cli, Shell};
+use clap::{Arg, Command, CommandFactory};
+
+/// Completion script for `shell`, registered for the binary `bin_name`
+pub fn generate(shell: Shell, bin_name: &str) -> String {
+    let mut command = Cli::command();
+    command.build();
+    let spec = CommandSpec::from_command(&command);
+
+    match shell {
+        Shell::Bash => bash(&spec, bin_name),
+        Shell::Zsh => zsh(&spec, bin_name),
+        Shell::Fish => fish(&spec, bin_name),
+    }
+}
+
+/// The parts of a clap command a completion script needs
+struct CommandSpec {
+    name: String,
+    about: String,
+    options: Vec<OptionSpec>,
+    /// Fixed values accepted by positional arguments, e.g. `completions <shell>`
+    positional_values: Vec<String>,
+    subcommands: Vec<CommandSpec>,
+}
+
+struct OptionSpec {
+    short: Option<char>,
+    long: Option<String>,
+    help: String,
+    takes_value: bool,
+    values: Vec<String>,
+}
+
+impl CommandSpec {
+    fn from_command(command: &Command) -> Self {
+        let visible_args = || command.get_arguments().filter(|arg| !arg.is_hide_set());
+        Self {
+            name: command.get_name().to_string(),
+            about: command
+                .get_about()
+                .map(|about| about.to_string())
+                .unwrap_or_default(),
+            options: visible_args()
+                .filter(|arg| !arg.is_positional())
+                .map(OptionSpec::from_arg)
+                .collect(),
+            positional_values: visible_args()
+                .filter(|arg| arg.is_positional())
+                .flat_map(possible_values)
+                .collect(),
+            subcommands: command
+                .get_subcommands()
+                .filter(|sub| !sub.is_hide_set() && sub.get_name() != "help")
+                .map(Self::from_command)
+                .collect(),
+        }
+    }
+
+    /// Every word that can follow the command: flags, subcommands and positional values
+    fn words(&self) -> Vec<String> {
+        let mut words: Vec<String> = self.subcommands.iter().map(|s| s.name.clone()).collect();
+        words.extend(self.positional_values.iter().cloned());
+        for option in &self.options {
+            words.extend(option.short.map(|short| format!("-{}", short)));
+            words.extend(option.long.as_ref().map(|long| format!("--{}", long)));
+        }
+        words
+    }
+}
+
+impl OptionSpec {
+    fn from_arg(arg: &Arg) -> Self {
+        Self {
+            short: arg.get_short(),
+            long: arg.get_long().map(str::to_string),
+            help: arg
+                .get_help()
+                .map(|help| help.to_string())
+                .unwrap_or_default(),
+            takes_value: arg.get_action().takes_values(),
+            values: possible_values(arg),
+        }
+    }
+
+    fn flags(&self) -> Vec<String> {
+        let mut flags: Vec<String> = self.short.map(|s| format!("-{}", s)).into_iter().collect();
+        flags.extend(self.long.as_ref().map(|long| format!("--{}", long)));
+        flags
+    }
+}
+
+fn possible_values(arg: &Arg) -> Vec<String> {
+    arg.get_possible_values()
+        .into_iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_string())
+        .collect()
+}
+
+fn function_name(bin_name: &str) -> String {
+    format!("_{}", bin_name.replace(['-', '.'], "_"))
+}
+
+fn bash(spec: &CommandSpec, bin_name: &str) -> String {
+    let function = function_name(bin_name);
+    let subcommand_names: Vec<&str> = spec.subcommands.iter().map(|s| s.name.as_str()).collect();
+
+    let mut script = format!(
+        "{function}() {{\n    local cur prev subcommand word opts\n    cur=\"${{COMP_WORDS[COMP_CWORD]}}\"\n    prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"\n    subcommand=\"\"\n    for word in \"${{COMP_WORDS[@]:1:COMP_CWORD-1}}\"; do\n        case \"$word\" in\n            {}) subcommand=\"$word\"; break ;;\n        esac\n    done\n\n    case \"$prev\" in\n",
+        subcommand_names.join("|")
+    );
+
+    // Option values, e.g. `--format <TAB>`
+    let mut seen = Vec::new();
+    for command in std::iter::once(spec).chain(&spec.subcommands) {
+        for option in command.options.iter().filter(|o| o.takes_value) {
+            let flags = option.flags().join("|");
+            if seen.contains(&flags) {
+                continue;
+            }
+            script.push_str(&format!(
+                "        {})\n            COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n            return ;;\n",
+                flags,
+                option.values.join(" ")
+            ));
+            seen.push(flags);
+        }
+    }
+    script.push_str("    esac\n\n    case \"$subcommand\" in\n");
+
+    for subcommand in &spec.subcommands {
+        script.push_str(&format!(
+            "        {}) opts=\"{}\" ;;\n",
+            subcommand.name,
+            subcommand.words().join(" ")
+        ));
+    }
+    script.push_str(&format!(
+        "        *) opts=\"{}\" ;;\n    esac\n    COMPREPLY=($(compgen -W \"$opts\" -- \"$cur\"))\n}}\n\ncomplete -F {function} {bin_name}\n",
+        spec.words().join(" ")
+    ));
+    script
+}
+
+/// Escape text placed inside a single-quoted zsh `_arguments` spec
+fn zsh_escape(text: &str) -> String {
+    text.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+fn zsh_arguments(command: &CommandSpec, indent: &str) -> Vec<String> {
+    let mut specs = Vec::new();
+    for option in &command.options {
+        let value = if !option.takes_value {
+            String::new()
+        } else if option.values.is_empty() {
+            ":value:".to_string()
+        } else {
+            format!(":value:({})", option.values.join(" "))
+        };
+        for flag in option.flags() {
+            specs.push(format!(
+                "{indent}'{}[{}]{}'",
+                flag,
+                zsh_escape(&option.help),
+                value
+            ));
+        }
+    }
+    if !command.positional_values.is_empty() {
+        specs.push(format!(
+            "{indent}'1:value:({})'",
+            command.positional_values.join(" ")
+        ));
+    }
+    specs
+}
+
+fn zsh(spec: &CommandSpec, bin_name: &str) -> String {
+    let function = function_name(bin_name);
+    let mut script = format!(
+        "#compdef {bin_name}\n\n{function}() {{\n    local line state\n    local -a subcommands\n    subcommands=(\n"
+    );
+    for subcommand in &spec.subcommands {
+        script.push_str(&format!(
+            "        '{}:{}'\n",
+            subcommand.name,
+            zsh_escape(&subcommand.about)
+        ));
+    }
+    script.push_str("    )\n\n    _arguments -C \\\n");
+    for argument in zsh_arguments(spec, "        ") {
+        script.push_str(&format!("{} \\\n", argument));
+    }
+    script.push_str(
+        "        '1: :->command' \\\n        '*:: :->args'\n\n    case $state in\n        command)\n            _describe 'command' subcommands ;;\n        args)\n            case $line[1] in\n",
+    );
+    for subcommand in &spec.subcommands {
+        let arguments = zsh_arguments(subcommand, "                        ");
+        if arguments.is_empty() {
+            continue;
+        }
+        script.push_str(&format!(
+            "                {})\n                    _arguments \\\n{} ;;\n",
+            subcommand.name,
+            arguments.join(" \\\n")
+        ));
+    }
+    script.push_str(&format!(
+        "            esac ;;\n    esac\n}}\n\n{function} \"$@\"\n"
+    ));
+    script
+}
+
+fn fish_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+fn fish_option(bin_name: &str, condition: &str, option: &OptionSpec) -> String {
+    let mut line = format!("complete -c {} -n '{}'", bin_name, condition);
+    if let Some(short) = option.short {
+        line.push_str(&format!(" -s {}", short));
+    }
+    if let Some(long) = &option.long {
+        line.push_str(&format!(" -l {}", long));
+    }
+    if option.takes_value {
+        line.push_str(" -r");
+        if !option.values.is_empty() {
+            line.push_str(&format!(" -f -a '{}'", option.values.join(" ")));
+        }
+    }
+    line.push_str(&format!(" -d '{}'\n", fish_escape(&option.help)));
+    line
+}
+
+fn fish(spec: &CommandSpec, bin_name: &str) -> String {
+    let mut script = format!("complete -c {} -f\n", bin_name);
+    let no_subcommand = "__fish_use_subcommand";
+
+    for option in &spec.options {
+        script.push_str(&fish_option(bin_name, no_subcommand, option));
+    }
+    for subcommand in &spec.subcommands {
+        script.push_str(&format!(
+            "complete -c {} -n '{}' -a {} -d '{}'\n",
+            bin_name,
+            no_subcommand,
+            subcommand.name,
+            fish_escape(&subcommand.about)
+        ));
+    }
+
+    for subcommand in &spec.subcommands {
+        let condition = format!("__fish_seen_subcommand_from {}", subcommand.name);
+        for option in &subcommand.options {
+            script.push_str(&fish_option(bin_name, &condition, option));
+        }
+        if !subcommand.positional_values.is_empty() {
+            script.push_str(&format!(
+                "complete -c {} -n '{}' -a '{}'\n",
+                bin_name,
+                condition,
+                subcommand.positional_values.join(" ")
+            ));
+        }
+    }
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_shell_gets_a_script() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = generate(shell, "ccline");
+            assert!(!script.trim().is_empty(), "{:?}", shell);
+            for word in ["quota", "starship", "completions", "theme", "no-network"] {
+                assert!(script.contains(word), "{:?} is missing {}", shell, word);
+            }
+        }
+    }
+
+    #[test]
+    fn hidden_flags_and_value_lists() {
+        let script = generate(Shell::Bash, "ccline");
+        assert!(!script.contains("--refresh"));
+        assert!(script.contains("--summary"));
+        for shell in ["bash", "zsh", "fish"] {
+            assert!(script.contains(shell));
+        }
+    }
+
+    #[test]
+    fn scripts_register_the_binary_name() {
+        assert!(generate(Shell::Bash, "ccline-yescc").contains("complete -F"));
+        assert!(generate(Shell::Zsh, "ccline-yescc").starts_with("#compdef ccline-yescc"));
+        assert!(generate(Shell::Fish, "ccline-yescc").contains("complete -c ccline-yescc"));
+    }
+}
@@ -173,12 +173,15 @@ impl Config {
             return Err("No segments configured".into());
         }
 
-        // Validate segment IDs are unique
-        let mut seen_ids = std::collections::HashSet::new();
+        // Segment IDs may repeat (network segments are still fetched once), but a
+        // second entry identical to an earlier one is almost certainly a mistake
+        let mut seen_entries = Vec::new();
         for segment in &self.segments {
-            if !seen_ids.insert(segment.id) {
-                return Err(format!("Duplicate segment ID: {:?}", segment.id).into());
+            let entry = serde_json::to_value(segment)?;
+            if seen_entries.contains(&entry) {
+                return Err(format!("Duplicate segment entry: {:?}", segment.id).into());
             }
+            seen_entries.push(entry);
         }
 
         Ok(())
@@ -191,3 +194,45 @@ impl Config {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{SegmentConfig, SegmentId};
+
+    fn quota_entries(options: [serde_json::Value; 2]) -> Config {
+        let mut config = Config::default();
+        let quota = config
+            .segments
+            .iter()
+            .find(|segment| segment.id == SegmentId::Quota)
+            .cloned()
+            .unwrap();
+        config.segments = options
+            .into_iter()
+            .map(|options| SegmentConfig {
+                options: serde_json::from_value(options).unwrap(),
+                ..quota.clone()
+            })
+            .collect();
+        config
+    }
+
+    #[test]
+    fn repeated_ids_with_different_options_are_valid() {
+        let config = quota_entries([
+            serde_json::json!({}),
+            serde_json::json!({ "display_mode": "percent" }),
+        ]);
+        assert!(config.check().is_ok());
+    }
+
+    #[test]
+    fn identical_entries_are_rejected() {
+        let config = quota_entries([serde_json::json!({}), serde_json::json!({})]);
+        assert_eq!(
+            config.check().unwrap_err().to_string(),
+            "Duplicate segment entry: Quota"
+        );
+    }
+}
@@ -15,11 +15,63 @@ pub struct Config {
 pub struct StyleConfig {
     pub mode: StyleMode,
     pub separator: String,
+    /// Drop trailing segments that don't fit the terminal width
+    #[serde(default)]
+    pub truncate: bool,
+    /// Width budget for truncation; overrides COLUMNS and terminal detection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_width: Option<usize>,
+    /// End a colored statusline with `\x1b[0m`; disable if the prompt adds its own reset
+    #[serde(default = "default_true")]
+    pub emit_trailing_reset: bool,
+    /// Remove trailing whitespace left by padding or empty segments
+    #[serde(default = "default_true")]
+    pub trim_trailing_whitespace: bool,
+    /// Render segments tagged `line = "info"` on a second, low-priority line
+    #[serde(default)]
+    pub info_line: bool,
+    /// What goes between the primary and info lines when both are shown
+    #[serde(default)]
+    pub line_separator: LineSeparator,
+}
+
+impl Default for StyleConfig {
+    fn default() -> Self {
+        Self {
+            mode: StyleMode::default(),
+            separator: String::new(),
+            truncate: false,
+            max_width: None,
+            emit_trailing_reset: true,
+            trim_trailing_whitespace: true,
+            info_line: false,
+            line_separator: LineSeparator::default(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LineSeparator {
+    /// Lines follow each other directly
+    #[default]
+    None,
+    /// An empty line
+    Blank,
+    /// A `─` rule as wide as the longer line
+    Thin,
+    /// A `━` rule as wide as the longer line
+    Thick,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum StyleMode {
+    #[default]
     Plain,
     NerdFont,
     Powerline,
@@ -54,13 +106,39 @@ pub struct TextStyleConfig {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(untagged)]
+#[serde(untagged, try_from = "AnsiColorValue")]
 pub enum AnsiColor {
     Color16 { c16: u8 },
     Color256 { c256: u8 },
     Rgb { r: u8, g: u8, b: u8 },
 }
 
+/// Accepted spellings of a color in config files: a table, a basic color name
+/// (`"blue"`, `"bright_black"`) or a hex string (`"#3b4252"`)
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AnsiColorValue {
+    Color16 { c16: u8 },
+    Color256 { c256: u8 },
+    Rgb { r: u8, g: u8, b: u8 },
+    Text(String),
+}
+
+impl TryFrom<AnsiColorValue> for AnsiColor {
+    type Error = String;
+
+    fn try_from(value: AnsiColorValue) -> Result<Self, Self::Error> {
+        match value {
+            AnsiColorValue::Color16 { c16 } => Ok(AnsiColor::Color16 { c16 }),
+            AnsiColorValue::Color256 { c256 } => Ok(AnsiColor::Color256 { c256 }),
+            AnsiColorValue::Rgb { r, g, b } => Ok(AnsiColor::Rgb { r, g, b }),
+            AnsiColorValue::Text(text) => AnsiColor::from_hex(&text)
+                .or_else(|| AnsiColor::from_name(&text))
+                .ok_or_else(|| format!("unknown color `{}`", text)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum SegmentId {
@@ -71,9 +149,26 @@ pub enum SegmentId {
     Usage,
     Cost,
     Session,
+    SessionCost,
     OutputStyle,
     Update,
     Quota,
+    Static,
+}
+
+impl SegmentId {
+    /// Segments that don't read Claude Code's stdin payload (network or local state only)
+    pub fn is_input_independent(&self) -> bool {
+        self.is_network()
+    }
+
+    /// Segments that make network requests; repeated entries share one fetch
+    pub fn is_network(&self) -> bool {
+        matches!(
+            self,
+            SegmentId::Usage | SegmentId::Update | SegmentId::Quota
+        )
+    }
 }
 
 // Legacy compatibility structure
@@ -85,19 +180,24 @@ pub struct SegmentsConfig {
     // pub usage: bool,
 }
 
-// Data structures compatible with existing main.rs
-#[derive(Deserialize)]
+// Data structures compatible with existing main.rs.
+// Every field defaults so `{}` or an older payload still renders.
+#[derive(Deserialize, Default)]
+#[serde(default)]
 pub struct Model {
     pub id: String,
     pub display_name: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
+#[serde(default)]
 pub struct Workspace {
     pub current_dir: String,
+    pub project_dir: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
+#[serde(default)]
 pub struct Cost {
     pub total_cost_usd: Option<f64>,
     pub total_duration_ms: Option<u64>,
@@ -106,13 +206,16 @@ pub struct Cost {
     pub total_lines_removed: Option<u32>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
+#[serde(default)]
 pub struct OutputStyle {
     pub name: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
+#[serde(default)]
 pub struct InputData {
+    pub session_id: String,
     pub model: Model,
     pub workspace: Workspace,
     pub transcript_path: String,
@@ -295,6 +398,54 @@ impl Config {
     }
 }
 
+impl AnsiColor {
+    /// Parse `#rrggbb` (or `#rgb`) into an RGB color
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let digits = hex.trim().strip_prefix('#')?;
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize, len: usize| {
+            let value = u8::from_str_radix(&digits[i * len..(i + 1) * len], 16).ok()?;
+            Some(if len == 1 { value * 17 } else { value })
+        };
+        let len = match digits.len() {
+            3 => 1,
+            6 => 2,
+            _ => return None,
+        };
+        Some(AnsiColor::Rgb {
+            r: channel(0, len)?,
+            g: channel(1, len)?,
+            b: channel(2, len)?,
+        })
+    }
+
+    /// Resolve a basic color name (`red`, `bright_green`, `gray`, ...) to a 16-color value
+    pub fn from_name(name: &str) -> Option<Self> {
+        let c16 = match name.to_lowercase().replace('-', "_").as_str() {
+            "black" => 0,
+            "red" => 1,
+            "green" => 2,
+            "yellow" => 3,
+            "blue" => 4,
+            "magenta" => 5,
+            "cyan" => 6,
+            "white" => 7,
+            "gray" | "grey" | "bright_black" => 8,
+            "bright_red" => 9,
+            "bright_green" => 10,
+            "bright_yellow" => 11,
+            "bright_blue" => 12,
+            "bright_magenta" => 13,
+            "bright_cyan" => 14,
+            "bright_white" => 15,
+            _ => return None,
+        };
+        Some(AnsiColor::Color16 { c16 })
+    }
+}
+
 impl PartialEq for AnsiColor {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -414,4 +565,60 @@ pub struct TranscriptEntry {
     #[serde(rename = "parentUuid")]
     pub parent_uuid: Option<String>,
     pub summary: Option<String>,
+    // Per-message cost written by some Claude Code versions
+    #[serde(rename = "costUSD")]
+    pub cost_usd: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn color(value: serde_json::Value) -> Result<AnsiColor, serde_json::Error> {
+        serde_json::from_value::<ColorConfig>(serde_json::json!({ "background": value }))
+            .map(|colors| colors.background.unwrap())
+    }
+
+    #[test]
+    fn colors_accept_tables_names_and_hex() {
+        assert_eq!(
+            color(serde_json::json!({ "c16": 4 })).unwrap(),
+            AnsiColor::Color16 { c16: 4 }
+        );
+        assert_eq!(
+            color(serde_json::json!({ "c256": 236 })).unwrap(),
+            AnsiColor::Color256 { c256: 236 }
+        );
+        assert_eq!(
+            color(serde_json::json!("blue")).unwrap(),
+            AnsiColor::Color16 { c16: 4 }
+        );
+        assert_eq!(
+            color(serde_json::json!("Bright-Black")).unwrap(),
+            AnsiColor::Color16 { c16: 8 }
+        );
+        assert_eq!(
+            color(serde_json::json!("#3b4252")).unwrap(),
+            AnsiColor::Rgb {
+                r: 59,
+                g: 66,
+                b: 82
+            }
+        );
+        assert_eq!(
+            color(serde_json::json!("#fa0")).unwrap(),
+            AnsiColor::Rgb {
+                r: 255,
+                g: 170,
+                b: 0
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_colors_are_rejected() {
+        assert!(color(serde_json::json!("mauve")).is_err());
+        assert!(color(serde_json::json!("#12345")).is_err());
+        assert!(color(serde_json::json!("#gg0000")).is_err());
+    }
 }
@@ -0,0 +1,178 @@
+use crate::config::InputData;
+use std::env;
+
+/// A single `show_if` test, e.g. `model.id ^= claude-opus`
+///
+/// Supported fields are `model.id`, `model.display_name`, `output_style` and
+/// `project` (the last component of the workspace's project directory, or of
+/// the current directory when Claude Code doesn't send one). Operators:
+/// `==` (equals), `!=` (not equals), `^=` (starts with), `*=` (contains).
+/// Comparisons are case-insensitive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    field: Field,
+    op: Op,
+    value: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    ModelId,
+    ModelDisplayName,
+    OutputStyle,
+    Project,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Equals,
+    NotEquals,
+    StartsWith,
+    Contains,
+}
+
+impl Condition {
+    pub fn parse(expression: &str) -> Option<Self> {
+        // Two-character operators, checked before splitting so values may contain spaces
+        let (op, index) = [
+            ("==", Op::Equals),
+            ("!=", Op::NotEquals),
+            ("^=", Op::StartsWith),
+            ("*=", Op::Contains),
+        ]
+        .iter()
+        .filter_map(|(token, op)| expression.find(token).map(|index| (*op, index)))
+        .min_by_key(|(_, index)| *index)?;
+
+        let field = match expression[..index].trim() {
+            "model.id" => Field::ModelId,
+            "model.display_name" => Field::ModelDisplayName,
+            "output_style" => Field::OutputStyle,
+            "project" => Field::Project,
+            _ => return None,
+        };
+        let value = expression[index + 2..]
+            .trim()
+            .trim_matches(|c| c == '"' || c == '\'')
+            .to_lowercase();
+
+        Some(Self { field, op, value })
+    }
+
+    pub fn matches(&self, input: &InputData) -> bool {
+        let actual = match self.field {
+            Field::ModelId => input.model.id.clone(),
+            Field::ModelDisplayName => input.model.display_name.clone(),
+            Field::OutputStyle => input
+                .output_style
+                .as_ref()
+                .map(|style| style.name.clone())
+                .unwrap_or_default(),
+            Field::Project => {
+                let workspace = &input.workspace;
+                let dir = if workspace.project_dir.is_empty() {
+                    &workspace.current_dir
+                } else {
+                    &workspace.project_dir
+                };
+                std::path::Path::new(dir)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            }
+        }
+        .to_lowercase();
+
+        match self.op {
+            Op::Equals => actual == self.value,
+            Op::NotEquals => actual != self.value,
+            Op::StartsWith => actual.starts_with(&self.value),
+            Op::Contains => actual.contains(&self.value),
+        }
+    }
+}
+
+/// Evaluate a segment's `show_if` option: a single expression, or an array
+/// of expressions that must all match. Segments without `show_if` are shown;
+/// an invalid expression is ignored so a typo never hides a segment.
+pub fn should_show(show_if: Option<&serde_json::Value>, input: &InputData) -> bool {
+    let expressions: Vec<&str> = match show_if {
+        None => return true,
+        Some(serde_json::Value::String(expression)) => vec![expression.as_str()],
+        Some(serde_json::Value::Array(items)) => items.iter().filter_map(|v| v.as_str()).collect(),
+        Some(_) => return true,
+    };
+
+    expressions
+        .into_iter()
+        .all(|expression| match Condition::parse(expression) {
+            Some(condition) => condition.matches(input),
+            None => {
+                if env::var("YESCODE_DEBUG").is_ok() {
+                    eprintln!(
+                        "[DEBUG] Ignoring invalid show_if expression: {}",
+                        expression
+                    );
+                }
+                true
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn input(value: serde_json::Value) -> InputData {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn opus() -> InputData {
+        input(json!({ "model": { "id": "claude-opus-4-1", "display_name": "Opus" } }))
+    }
+
+    fn haiku() -> InputData {
+        input(json!({ "model": { "id": "claude-3-5-haiku", "display_name": "Haiku" } }))
+    }
+
+    #[test]
+    fn model_prefix_toggles_the_segment() {
+        let show_if = json!("model.id ^= claude-opus");
+        assert!(should_show(Some(&show_if), &opus()));
+        assert!(!should_show(Some(&show_if), &haiku()));
+    }
+
+    #[test]
+    fn comparisons_ignore_case_and_quotes() {
+        let show_if = json!("model.display_name == 'OPUS'");
+        assert!(should_show(Some(&show_if), &opus()));
+    }
+
+    #[test]
+    fn every_expression_in_an_array_must_match() {
+        let show_if = json!(["model.id ^= claude", "model.id *= opus"]);
+        assert!(should_show(Some(&show_if), &opus()));
+        assert!(!should_show(Some(&show_if), &haiku()));
+    }
+
+    #[test]
+    fn missing_or_invalid_conditions_show_the_segment() {
+        assert!(should_show(None, &haiku()));
+        assert!(should_show(Some(&json!("model.size > 200k")), &haiku()));
+        assert!(should_show(Some(&json!(42)), &haiku()));
+    }
+
+    #[test]
+    fn project_prefers_the_project_dir() {
+        let condition = Condition::parse("project == ccline").unwrap();
+        let nested = input(json!({ "workspace": {
+            "current_dir": "/work/ccline/src",
+            "project_dir": "/work/ccline",
+        } }));
+        assert!(condition.matches(&nested));
+
+        let without_project = input(json!({ "workspace": { "current_dir": "/work/ccline" } }));
+        assert!(condition.matches(&without_project));
+    }
+}
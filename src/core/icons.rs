@@ -0,0 +1,18 @@
+use crate::config::StyleMode;
+
+/// Icons for segment states, as (state, Nerd Font glyph, ASCII fallback)
+///
+/// Segments request one by setting the `icon_state` metadata key; it replaces
+/// the configured segment icon.
+const STATE_ICONS: &[(&str, &str, &str)] = &[("offline", "\u{f0164}", "x")];
+
+/// Resolve a state icon for the given style mode; Plain mode gets the ASCII fallback
+pub fn state_icon(state: &str, mode: StyleMode) -> Option<&'static str> {
+    STATE_ICONS
+        .iter()
+        .find(|(name, _, _)| *name == state)
+        .map(|(_, nerd_font, ascii)| match mode {
+            StyleMode::Plain => *ascii,
+            StyleMode::NerdFont | StyleMode::Powerline => *nerd_font,
+        })
+}
@@ -1,4 +1,8 @@
+pub mod condition;
+pub mod icons;
+pub mod pango;
 pub mod segments;
 pub mod statusline;
+pub mod transform;
 
-pub use statusline::{collect_all_segments, StatusLineGenerator};
+pub use statusline::{collect_all_segments, StatusLineGenerator, StatusLines};
@@ -0,0 +1,232 @@
+//! Convert rendered ANSI statusline output into Pango markup for
+//! i3blocks/waybar. Colors are taken from the SGR sequences the renderer
+//! already produced, so both formats resolve colors the same way.
+
+/// Default xterm palette for the 16 basic colors
+const BASIC_COLORS: [&str; 16] = [
+    "#000000", "#cd0000", "#00cd00", "#cdcd00", "#0000ee", "#cd00cd", "#00cdcd", "#e5e5e5",
+    "#7f7f7f", "#ff0000", "#00ff00", "#ffff00", "#5c5cff", "#ff00ff", "#00ffff", "#ffffff",
+];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct SpanStyle {
+    foreground: Option<String>,
+    background: Option<String>,
+    bold: bool,
+    faint: bool,
+}
+
+impl SpanStyle {
+    fn is_plain(&self) -> bool {
+        *self == SpanStyle::default()
+    }
+
+    fn open_tag(&self) -> String {
+        let mut attributes = Vec::new();
+        if let Some(foreground) = &self.foreground {
+            attributes.push(format!("foreground=\"{}\"", foreground));
+        }
+        if let Some(background) = &self.background {
+            attributes.push(format!("background=\"{}\"", background));
+        }
+        if self.bold {
+            attributes.push("weight=\"bold\"".to_string());
+        }
+        if self.faint {
+            attributes.push("alpha=\"50%\"".to_string());
+        }
+        format!("<span {}>", attributes.join(" "))
+    }
+
+    /// Apply the parameters of one SGR (`ESC [ ... m`) sequence
+    fn apply_sgr(&mut self, params: &str) {
+        let codes: Vec<u32> = params
+            .split(';')
+            .map(|code| code.parse().unwrap_or(0))
+            .collect();
+        let mut i = 0;
+        while i < codes.len() {
+            match codes[i] {
+                0 => *self = SpanStyle::default(),
+                1 => self.bold = true,
+                2 => self.faint = true,
+                22 => {
+                    self.bold = false;
+                    self.faint = false;
+                }
+                code @ 30..=37 => self.foreground = Some(BASIC_COLORS[(code - 30) as usize].into()),
+                code @ 90..=97 => {
+                    self.foreground = Some(BASIC_COLORS[(code - 90 + 8) as usize].into())
+                }
+                39 => self.foreground = None,
+                code @ 40..=47 => self.background = Some(BASIC_COLORS[(code - 40) as usize].into()),
+                code @ 100..=107 => {
+                    self.background = Some(BASIC_COLORS[(code - 100 + 8) as usize].into())
+                }
+                49 => self.background = None,
+                code @ (38 | 48) => {
+                    let (color, consumed) = extended_color(&codes[i + 1..]);
+                    if code == 38 {
+                        self.foreground = color;
+                    } else {
+                        self.background = color;
+                    }
+                    i += consumed;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+}
+
+/// Parse `5;n` or `2;r;g;b` after a 38/48 code; returns the color and how many codes were used
+fn extended_color(codes: &[u32]) -> (Option<String>, usize) {
+    match codes {
+        [5, n, ..] => (Some(color_256_to_hex(*n as u8)), 2),
+        [2, r, g, b, ..] => (Some(format!("#{:02x}{:02x}{:02x}", r, g, b)), 4),
+        _ => (None, codes.len()),
+    }
+}
+
+fn color_256_to_hex(index: u8) -> String {
+    match index {
+        0..=15 => BASIC_COLORS[index as usize].to_string(),
+        16..=231 => {
+            let index = index - 16;
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            format!(
+                "#{:02x}{:02x}{:02x}",
+                level(index / 36),
+                level(index / 6 % 6),
+                level(index % 6)
+            )
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            format!("#{:02x}{:02x}{:02x}", gray, gray, gray)
+        }
+    }
+}
+
+fn escape_markup(ch: char, out: &mut String) {
+    match ch {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        '\'' => out.push_str("&apos;"),
+        _ => out.push(ch),
+    }
+}
+
+/// Translate ANSI-colored text into Pango markup; OSC sequences such as
+/// hyperlinks are dropped and other text is escaped.
+pub fn ansi_to_pango(text: &str) -> String {
+    let mut output = String::new();
+    let mut style = SpanStyle::default();
+    let mut span_open = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\x1b' {
+            if !span_open && !style.is_plain() {
+                output.push_str(&style.open_tag());
+                span_open = true;
+            }
+            escape_markup(ch, &mut output);
+            continue;
+        }
+
+        match chars.peek() {
+            Some('[') => {
+                chars.next();
+                let mut params = String::new();
+                let mut terminator = None;
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        terminator = Some(c);
+                        break;
+                    }
+                    params.push(c);
+                }
+                if terminator == Some('m') {
+                    let previous = style.clone();
+                    style.apply_sgr(&params);
+                    if span_open && style != previous {
+                        output.push_str("</span>");
+                        span_open = false;
+                    }
+                }
+            }
+            Some(']') => {
+                // OSC: skip until ST (ESC \) or BEL
+                chars.next();
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if span_open {
+        output.push_str("</span>");
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colored_text_becomes_a_span() {
+        assert_eq!(
+            ansi_to_pango("\x1b[31mquota\x1b[0m ok"),
+            "<span foreground=\"#cd0000\">quota</span> ok"
+        );
+    }
+
+    #[test]
+    fn markup_characters_are_escaped() {
+        assert_eq!(
+            ansi_to_pango("a < b & \"c\" > 'd'"),
+            "a &lt; b &amp; &quot;c&quot; &gt; &apos;d&apos;"
+        );
+        assert_eq!(
+            ansi_to_pango("\x1b[1m<&>\x1b[22m"),
+            "<span weight=\"bold\">&lt;&amp;&gt;</span>"
+        );
+    }
+
+    #[test]
+    fn extended_colors_and_backgrounds() {
+        assert_eq!(
+            ansi_to_pango("\x1b[38;2;16;32;48;48;5;196mx\x1b[0m"),
+            "<span foreground=\"#102030\" background=\"#ff0000\">x</span>"
+        );
+        assert_eq!(
+            ansi_to_pango("\x1b[38;5;244mx"),
+            "<span foreground=\"#808080\">x</span>"
+        );
+    }
+
+    #[test]
+    fn style_changes_close_the_open_span() {
+        assert_eq!(
+            ansi_to_pango("\x1b[32ma\x1b[2mb\x1b[0mc"),
+            "<span foreground=\"#00cd00\">a</span><span foreground=\"#00cd00\" alpha=\"50%\">b</span>c"
+        );
+    }
+
+    #[test]
+    fn hyperlinks_are_dropped() {
+        assert_eq!(
+            ansi_to_pango("\x1b]8;;https://co.yes.vg\x1b\\quota\x1b]8;;\x07"),
+            "quota"
+        );
+    }
+}
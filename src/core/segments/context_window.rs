@@ -1,6 +1,6 @@
 use super::{Segment, SegmentData};
 use crate::config::{InputData, ModelConfig, SegmentId, TranscriptEntry};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
@@ -22,6 +22,10 @@ impl ContextWindowSegment {
 
 impl Segment for ContextWindowSegment {
     fn collect(&self, input: &InputData) -> Option<SegmentData> {
+        if input.transcript_path.is_empty() {
+            return None;
+        }
+
         // Dynamically determine context limit based on current model ID
         let context_limit = Self::get_context_limit_for_model(&input.model.id);
 
@@ -56,7 +60,7 @@ impl Segment for ContextWindowSegment {
             }
         };
 
-        let mut metadata = HashMap::new();
+        let mut metadata = BTreeMap::new();
         match context_used_token_opt {
             Some(context_used_token) => {
                 let context_used_rate = (context_used_token as f64 / context_limit as f64) * 100.0;
@@ -1,6 +1,6 @@
 use super::{Segment, SegmentData};
 use crate::config::{InputData, SegmentId};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 #[derive(Default)]
 pub struct CostSegment;
@@ -29,7 +29,7 @@ impl Segment for CostSegment {
         // Secondary display: empty for cost segment
         let secondary = String::new();
 
-        let mut metadata = HashMap::new();
+        let mut metadata = BTreeMap::new();
         if let Some(cost) = cost_data.total_cost_usd {
             metadata.insert("cost".to_string(), cost.to_string());
         }
@@ -1,6 +1,6 @@
 use super::{Segment, SegmentData};
 use crate::config::{InputData, SegmentId};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 #[derive(Default)]
 pub struct DirectorySegment;
@@ -39,12 +39,15 @@ impl DirectorySegment {
 impl Segment for DirectorySegment {
     fn collect(&self, input: &InputData) -> Option<SegmentData> {
         let current_dir = &input.workspace.current_dir;
+        if current_dir.is_empty() {
+            return None;
+        }
 
         // Handle cross-platform path separators manually for better compatibility
         let dir_name = Self::extract_directory_name(current_dir);
 
         // Store the full path in metadata for potential use
-        let mut metadata = HashMap::new();
+        let mut metadata = BTreeMap::new();
         metadata.insert("full_path".to_string(), current_dir.clone());
 
         Some(SegmentData {
@@ -1,6 +1,6 @@
 use super::{Segment, SegmentData};
 use crate::config::{InputData, SegmentId};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::process::Command;
 
 #[derive(Debug)]
@@ -173,9 +173,13 @@ impl GitSegment {
 
 impl Segment for GitSegment {
     fn collect(&self, input: &InputData) -> Option<SegmentData> {
+        if input.workspace.current_dir.is_empty() {
+            return None;
+        }
+
         let git_info = self.get_git_info(&input.workspace.current_dir)?;
 
-        let mut metadata = HashMap::new();
+        let mut metadata = BTreeMap::new();
         metadata.insert("branch".to_string(), git_info.branch.clone());
         metadata.insert("status".to_string(), format!("{:?}", git_info.status));
         metadata.insert("ahead".to_string(), git_info.ahead.to_string());
@@ -6,11 +6,14 @@ pub mod model;
 pub mod output_style;
 pub mod quota;
 pub mod session;
+pub mod session_cost;
+pub mod static_text;
 pub mod update;
 pub mod usage;
 
 use crate::config::{InputData, SegmentId};
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::BTreeMap;
 
 // New Segment trait for data collection only
 pub trait Segment {
@@ -18,11 +21,12 @@ pub trait Segment {
     fn id(&self) -> SegmentId;
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SegmentData {
     pub primary: String,
     pub secondary: String,
-    pub metadata: HashMap<String, String>,
+    /// Sorted so serialized output stays stable across runs
+    pub metadata: BTreeMap<String, String>,
 }
 
 // Re-export all segment types
@@ -32,7 +36,9 @@ pub use directory::DirectorySegment;
 pub use git::GitSegment;
 pub use model::ModelSegment;
 pub use output_style::OutputStyleSegment;
-pub use quota::QuotaSegment;
+pub use quota::{QuotaFetch, QuotaSegment};
 pub use session::SessionSegment;
+pub use session_cost::{CostSource, SessionCost, SessionCostSegment};
+pub use static_text::StaticSegment;
 pub use update::UpdateSegment;
 pub use usage::UsageSegment;
@@ -1,24 +1,57 @@
 use super::{Segment, SegmentData};
 use crate::config::{InputData, ModelConfig, SegmentId};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
-#[derive(Default)]
-pub struct ModelSegment;
+/// Default cap on the rendered model name, in characters
+const DEFAULT_MAX_LENGTH: usize = 32;
+
+pub struct ModelSegment {
+    display_names: Vec<(String, String)>,
+    max_length: usize,
+}
+
+impl Default for ModelSegment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl ModelSegment {
     pub fn new() -> Self {
-        Self
+        Self {
+            display_names: Vec::new(),
+            max_length: DEFAULT_MAX_LENGTH,
+        }
+    }
+
+    /// Cap the rendered name length; 0 disables truncation
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// Friendly names keyed by model id substring, used when Claude Code sends no display_name
+    pub fn with_display_names(mut self, display_names: Vec<(String, String)>) -> Self {
+        self.display_names = display_names;
+        self
     }
 }
 
 impl Segment for ModelSegment {
     fn collect(&self, input: &InputData) -> Option<SegmentData> {
-        let mut metadata = HashMap::new();
+        // An empty or legacy payload carries no model
+        if input.model.id.is_empty() && input.model.display_name.is_empty() {
+            return None;
+        }
+
+        let mut metadata = BTreeMap::new();
         metadata.insert("model_id".to_string(), input.model.id.clone());
         metadata.insert("display_name".to_string(), input.model.display_name.clone());
 
         Some(SegmentData {
-            primary: self.format_model_name(&input.model.id, &input.model.display_name),
+            primary: self.truncate_middle(
+                &self.format_model_name(&input.model.id, &input.model.display_name),
+            ),
             secondary: String::new(),
             metadata,
         })
@@ -36,9 +69,127 @@ impl ModelSegment {
         // Try to get display name from external config first
         if let Some(config_name) = model_config.get_display_name(id) {
             config_name
-        } else {
+        } else if !display_name.is_empty() {
             // Fallback to Claude Code's official display_name for unrecognized models
             display_name.to_string()
+        } else if let Some(mapped_name) = self.lookup_display_name(id) {
+            mapped_name
+        } else {
+            id.to_string()
+        }
+    }
+
+    /// Shorten long names by keeping both ends, e.g. `claude-sup…20250101`
+    fn truncate_middle(&self, name: &str) -> String {
+        let chars: Vec<char> = name.chars().collect();
+        if self.max_length == 0 || chars.len() <= self.max_length {
+            return name.to_string();
         }
+        if self.max_length == 1 {
+            return "…".to_string();
+        }
+
+        // One cell goes to the ellipsis; the head gets the extra cell on odd budgets
+        let keep = self.max_length - 1;
+        let head = keep - keep / 2;
+        let tail = keep / 2;
+
+        let mut truncated: String = chars[..head].iter().collect();
+        truncated.push('…');
+        truncated.extend(&chars[chars.len() - tail..]);
+        truncated
+    }
+
+    /// Find the most specific (longest) configured substring contained in the model id
+    fn lookup_display_name(&self, id: &str) -> Option<String> {
+        let id_lower = id.to_lowercase();
+
+        self.display_names
+            .iter()
+            .filter(|(pattern, _)| !pattern.is_empty())
+            .filter(|(pattern, _)| id_lower.contains(&pattern.to_lowercase()))
+            .max_by_key(|(pattern, _)| pattern.len())
+            .map(|(_, name)| name.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestEnv;
+
+    fn input(id: &str, display_name: &str) -> InputData {
+        serde_json::from_value(serde_json::json!({
+            "model": { "id": id, "display_name": display_name }
+        }))
+        .unwrap()
+    }
+
+    fn segment() -> ModelSegment {
+        ModelSegment::new().with_display_names(vec![
+            ("acme-turbo".to_string(), "Turbo".to_string()),
+            ("ACME-TURBO-MINI".to_string(), "Turbo Mini".to_string()),
+        ])
+    }
+
+    fn primary(segment: &ModelSegment, input: &InputData) -> String {
+        segment.collect(input).expect("model segment").primary
+    }
+
+    #[test]
+    fn display_name_map_matches_substrings() {
+        let _env = TestEnv::new();
+        assert_eq!(primary(&segment(), &input("acme-turbo-2025", "")), "Turbo");
+        // The longest matching pattern wins, case-insensitively
+        assert_eq!(
+            primary(&segment(), &input("acme-turbo-mini-2025", "")),
+            "Turbo Mini"
+        );
+    }
+
+    #[test]
+    fn display_name_map_falls_back_to_raw_id() {
+        let _env = TestEnv::new();
+        assert_eq!(
+            primary(&segment(), &input("other-model-1", "")),
+            "other-model-1"
+        );
+    }
+
+    #[test]
+    fn display_name_from_claude_code_wins_over_map() {
+        let _env = TestEnv::new();
+        assert_eq!(
+            primary(&segment(), &input("acme-turbo-2025", "Acme Turbo")),
+            "Acme Turbo"
+        );
+    }
+
+    #[test]
+    fn long_display_name_is_middle_truncated() {
+        let _env = TestEnv::new();
+        let name = "Claude Super Long Experimental Model 20250101";
+        let rendered = primary(&ModelSegment::new(), &input("x", name));
+        assert_eq!(rendered.chars().count(), DEFAULT_MAX_LENGTH);
+        assert_eq!(rendered, "Claude Super Lon… Model 20250101");
+    }
+
+    #[test]
+    fn truncation_respects_the_configured_length() {
+        let _env = TestEnv::new();
+        let segment = ModelSegment::new().with_max_length(8);
+        assert_eq!(primary(&segment, &input("x", "abcdefghijkl")), "abcd…jkl");
+        assert_eq!(primary(&segment, &input("x", "abcdefgh")), "abcdefgh");
+
+        let segment = ModelSegment::new().with_max_length(1);
+        assert_eq!(primary(&segment, &input("x", "abcdefgh")), "…");
+    }
+
+    #[test]
+    fn zero_max_length_disables_truncation() {
+        let _env = TestEnv::new();
+        let name = "x".repeat(100);
+        let segment = ModelSegment::new().with_max_length(0);
+        assert_eq!(primary(&segment, &input("x", &name)), name);
     }
 }
@@ -1,6 +1,6 @@
 use super::{Segment, SegmentData};
 use crate::config::{InputData, SegmentId};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 #[derive(Default)]
 pub struct OutputStyleSegment;
@@ -18,7 +18,7 @@ impl Segment for OutputStyleSegment {
         // Primary display: style name
         let primary = output_style.name.clone();
 
-        let mut metadata = HashMap::new();
+        let mut metadata = BTreeMap::new();
         metadata.insert("style_name".to_string(), output_style.name.clone());
 
         Some(SegmentData {
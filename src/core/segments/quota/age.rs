@@ -0,0 +1,65 @@
+use chrono::{DateTime, Duration, Utc};
+
+/// 缓存写入时间到现在的时长；时间戳无法解析时为 None
+pub fn cache_age(cached_at: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let cached_at = DateTime::parse_from_rfc3339(cached_at).ok()?;
+    Some((now - cached_at.with_timezone(&Utc)).max(Duration::zero()))
+}
+
+/// `(live)`、`(45s ago)`、`(2m ago)`、`(3h ago)`、`(2d ago)`；None 表示刚刚获取
+pub fn format_data_age(age: Option<Duration>) -> String {
+    match age {
+        Some(age) => format!("({} ago)", format_age(age)),
+        None => "(live)".to_string(),
+    }
+}
+
+/// 紧凑的时长：`45s`、`2m`、`3h`、`2d`
+pub fn format_age(age: Duration) -> String {
+    let seconds = age.num_seconds().max(0);
+    if seconds < 60 {
+        format!("{}s", seconds)
+    } else if seconds < 3600 {
+        format!("{}m", seconds / 60)
+    } else if seconds < 86400 {
+        format!("{}h", seconds / 3600)
+    } else {
+        format!("{}d", seconds / 86400)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_several_ages() {
+        assert_eq!(format_data_age(None), "(live)");
+        assert_eq!(format_data_age(Some(Duration::seconds(45))), "(45s ago)");
+        assert_eq!(format_data_age(Some(Duration::seconds(150))), "(2m ago)");
+        assert_eq!(format_data_age(Some(Duration::hours(3))), "(3h ago)");
+        assert_eq!(format_data_age(Some(Duration::hours(50))), "(2d ago)");
+        assert_eq!(format_age(Duration::seconds(-5)), "0s");
+    }
+
+    #[test]
+    fn cache_age_from_the_timestamp() {
+        let now = DateTime::parse_from_rfc3339("2026-10-14T10:02:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            cache_age("2026-10-14T10:00:00+00:00", now),
+            Some(Duration::minutes(2))
+        );
+        // 其他时区的时间戳按同一时刻计算；未来的时间戳视为刚写入
+        assert_eq!(
+            cache_age("2026-10-14T18:01:00+08:00", now),
+            Some(Duration::minutes(1))
+        );
+        assert_eq!(
+            cache_age("2026-10-14T10:05:00Z", now),
+            Some(Duration::zero())
+        );
+        assert_eq!(cache_age("yesterday", now), None);
+    }
+}
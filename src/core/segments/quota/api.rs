@@ -0,0 +1,1445 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+#[cfg(any(feature = "http-ureq", feature = "http-reqwest"))]
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+#[cfg(any(feature = "http-ureq", feature = "http-reqwest"))]
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+// API 响应结构
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "DailyUsagePayload")]
+pub struct DailyUsageApiResponse {
+    pub daily_usage: Vec<DailyUsage>,
+}
+
+/// 日用量接口可能返回 `{ "daily_usage": [...] }`，也可能直接返回数组
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DailyUsagePayload {
+    Wrapped { daily_usage: Vec<DailyUsage> },
+    Bare(Vec<DailyUsage>),
+}
+
+impl From<DailyUsagePayload> for DailyUsageApiResponse {
+    fn from(payload: DailyUsagePayload) -> Self {
+        let daily_usage = match payload {
+            DailyUsagePayload::Wrapped { daily_usage } => daily_usage,
+            DailyUsagePayload::Bare(daily_usage) => daily_usage,
+        };
+        Self { daily_usage }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyUsage {
+    pub date: String,
+    pub total_cost: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceApiResponse {
+    #[allow(dead_code)]
+    pub balance: f64,
+    pub pay_as_you_go_balance: f64,
+    pub subscription_balance: f64,
+    pub total_balance: f64,
+    pub weekly_limit: f64,
+    pub weekly_spent_balance: f64,
+    /// 日限额（部分账户才有）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_limit: Option<f64>,
+    /// 今日已计入日限额的花费
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_spent_balance: Option<f64>,
+    /// 下次周额度重置时间（RFC 3339），接口未返回时按 week_start_day 推算
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekly_reset_at: Option<String>,
+    /// ISO 4217 货币代码，接口未返回时为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    /// 账户标识（字符串或数字），用于按账户而不是 API key 缓存
+    #[serde(
+        default,
+        alias = "user_id",
+        deserialize_with = "deserialize_account_id",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub account_id: Option<String>,
+}
+
+fn deserialize_account_id<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(
+        match Option::<serde_json::Value>::deserialize(deserializer)? {
+            Some(serde_json::Value::String(id)) if !id.is_empty() => Some(id),
+            Some(serde_json::Value::Number(id)) => Some(id.to_string()),
+            _ => None,
+        },
+    )
+}
+
+/// 一次 HTTP GET 的原始结果（任何状态码都会返回，只有传输失败才是 Err）
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl HttpResponse {
+    /// 按名称（不区分大小写）读取响应头
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// 额度接口请求失败的原因
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuotaError {
+    /// 网络/传输层失败（DNS、连接被拒等）
+    Network(String),
+    /// 超过请求超时仍未完成
+    Timeout,
+    /// 401/403：API key 无效或没有权限
+    Unauthorized,
+    /// 429：请求过于频繁，附带 Retry-After（如有）
+    RateLimited { retry_after: Option<Duration> },
+    /// 其他非 200 状态码
+    Http(u16),
+    /// 200 但返回的不是 JSON（通常是代理的错误页），附带原因
+    Gateway(String),
+    /// 是 JSON，但结构不符合预期
+    Decode(String),
+    /// 503 维护中，附带接口给出的说明（如有）
+    Maintenance(Option<String>),
+}
+
+impl fmt::Display for QuotaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuotaError::Network(e) => write!(f, "network error: {}", e),
+            QuotaError::Timeout => write!(f, "timed out"),
+            QuotaError::Unauthorized => write!(f, "unauthorized"),
+            QuotaError::RateLimited {
+                retry_after: Some(retry_after),
+            } => write!(f, "rate limited, retry after {}s", retry_after.as_secs()),
+            QuotaError::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            QuotaError::Http(status) => write!(f, "HTTP {}", status),
+            QuotaError::Gateway(reason) => write!(f, "gateway error: {}", reason),
+            QuotaError::Decode(e) => write!(f, "invalid response: {}", e),
+            QuotaError::Maintenance(Some(message)) => write!(f, "maintenance: {}", message),
+            QuotaError::Maintenance(None) => write!(f, "maintenance"),
+        }
+    }
+}
+
+impl QuotaError {
+    /// 回退到缓存数据时写入 metadata 的 status
+    pub fn fallback_status(&self) -> &'static str {
+        match self {
+            QuotaError::Timeout => "timeout",
+            QuotaError::RateLimited { .. } => "rate_limited",
+            _ => "stale",
+        }
+    }
+}
+
+/// HTTP 传输层抽象，测试时可替换为返回固定响应的实现
+///
+/// 收到任何状态码的响应都返回 Ok；只有没拿到响应时才返回错误，
+/// 且只会是 `QuotaError::Timeout` 或 `QuotaError::Network`
+pub trait HttpFetcher: Send + Sync {
+    fn get(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+        timeout: Duration,
+    ) -> Result<HttpResponse, QuotaError>;
+}
+
+#[cfg(all(
+    feature = "quota",
+    not(any(feature = "http-ureq", feature = "http-reqwest"))
+))]
+compile_error!(
+    "the `quota` feature needs an HTTP backend: enable `http-ureq` (default) or `http-reqwest`"
+);
+
+/// 编译时选中的 HTTP 后端；两个特性同时启用时使用 ureq
+#[cfg(all(feature = "quota", feature = "http-ureq"))]
+pub fn default_fetcher() -> Box<dyn HttpFetcher> {
+    Box::new(UreqFetcher::default())
+}
+
+/// 编译时选中的 HTTP 后端（只启用了 `http-reqwest`）
+#[cfg(all(
+    feature = "quota",
+    feature = "http-reqwest",
+    not(feature = "http-ureq")
+))]
+pub fn default_fetcher() -> Box<dyn HttpFetcher> {
+    Box::new(ReqwestFetcher::default())
+}
+
+/// 默认的 ureq 实现（`http-ureq` 特性）
+///
+/// agent 按所用代理缓存，同一个 fetcher 的所有请求共用一份 TLS 配置，不必每次重建；
+/// `fetch_all` 的两个请求并发发出，各自建立连接
+#[cfg(feature = "http-ureq")]
+#[derive(Debug, Default)]
+pub struct UreqFetcher {
+    agents: Mutex<HashMap<Option<String>, ureq::Agent>>,
+}
+
+#[cfg(feature = "http-ureq")]
+impl UreqFetcher {
+    fn agent_for(&self, url: &str) -> ureq::Agent {
+        let proxy = select_proxy(url);
+        let mut agents = self.agents.lock().unwrap_or_else(PoisonError::into_inner);
+        agents
+            .entry(proxy)
+            .or_insert_with_key(|proxy| build_agent(proxy.as_deref()))
+            .clone()
+    }
+}
+
+#[cfg(feature = "http-ureq")]
+impl HttpFetcher for UreqFetcher {
+    fn get(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+        timeout: Duration,
+    ) -> Result<HttpResponse, QuotaError> {
+        let mut request = self.agent_for(url).get(url).timeout(timeout);
+        for (name, value) in headers {
+            request = request.set(name, value);
+        }
+
+        // ureq 把 4xx/5xx 当作错误返回，这里统一转成 HttpResponse 交给上层判断
+        let response = match request.call() {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(ureq::Error::Transport(transport)) => return Err(ureq_transport_error(transport)),
+        };
+
+        let status = response.status();
+        let headers = response
+            .headers_names()
+            .into_iter()
+            .filter_map(|name| {
+                let value = response.header(&name)?.to_string();
+                Some((name, value))
+            })
+            .collect();
+        let body = response.into_string().map_err(io_error)?;
+
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+/// 按 ureq 的错误类型区分超时，不依赖错误信息的措辞
+#[cfg(feature = "http-ureq")]
+fn ureq_transport_error(transport: ureq::Transport) -> QuotaError {
+    let source = std::error::Error::source(&transport).and_then(|e| e.downcast_ref());
+    match (transport.kind(), source) {
+        (ureq::ErrorKind::Io, Some(e)) if is_timeout(e) => QuotaError::Timeout,
+        _ => QuotaError::Network(transport.to_string()),
+    }
+}
+
+/// 读取响应体时的 io 错误：读超时在不同平台上是 TimedOut 或 WouldBlock
+#[cfg(feature = "http-ureq")]
+fn io_error(e: std::io::Error) -> QuotaError {
+    if is_timeout(&e) {
+        QuotaError::Timeout
+    } else {
+        QuotaError::Network(e.to_string())
+    }
+}
+
+#[cfg(feature = "http-ureq")]
+fn is_timeout(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+    )
+}
+
+/// reqwest blocking 实现（`http-reqwest` 特性）
+///
+/// 与 ureq 实现相同：client 按所用代理缓存，代理和 TLS 设置都来自 `build_reqwest_client`，
+/// 头部、超时和状态码的处理由上层负责，两个后端只负责收发
+#[cfg(feature = "http-reqwest")]
+#[derive(Debug, Default)]
+pub struct ReqwestFetcher {
+    clients: Mutex<HashMap<Option<String>, reqwest::blocking::Client>>,
+}
+
+#[cfg(feature = "http-reqwest")]
+impl ReqwestFetcher {
+    fn client_for(&self, url: &str) -> Result<reqwest::blocking::Client, String> {
+        let proxy = select_proxy(url);
+        let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(client) = clients.get(&proxy) {
+            return Ok(client.clone());
+        }
+        let client = build_reqwest_client(proxy.as_deref())?;
+        clients.insert(proxy, client.clone());
+        Ok(client)
+    }
+}
+
+#[cfg(feature = "http-reqwest")]
+impl HttpFetcher for ReqwestFetcher {
+    fn get(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+        timeout: Duration,
+    ) -> Result<HttpResponse, QuotaError> {
+        let mut request = self
+            .client_for(url)
+            .map_err(QuotaError::Network)?
+            .get(url)
+            .timeout(timeout);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+
+        // reqwest 不把 4xx/5xx 当作错误，状态码原样交给上层判断
+        let response = request.send().map_err(reqwest_error)?;
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+            })
+            .collect();
+        let body = response.text().map_err(reqwest_error)?;
+
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+#[cfg(feature = "http-reqwest")]
+fn reqwest_error(e: reqwest::Error) -> QuotaError {
+    if e.is_timeout() {
+        QuotaError::Timeout
+    } else {
+        QuotaError::Network(e.to_string())
+    }
+}
+
+/// reqwest 版的 `build_agent`：不读取 reqwest 自己的代理环境变量，
+/// 代理由 `select_proxy` 按与 ureq 相同的规则选出
+#[cfg(feature = "http-reqwest")]
+pub fn build_reqwest_client(proxy: Option<&str>) -> Result<reqwest::blocking::Client, String> {
+    let mut builder = reqwest::blocking::Client::builder().no_proxy();
+    if let Some(proxy) = proxy {
+        match reqwest::Proxy::all(proxy) {
+            Ok(proxy_config) => {
+                if env::var("YESCODE_DEBUG").is_ok() {
+                    eprintln!("[DEBUG] Using proxy {}", proxy);
+                }
+                builder = builder.proxy(proxy_config);
+            }
+            Err(e) => {
+                if env::var("YESCODE_DEBUG").is_ok() {
+                    eprintln!("[DEBUG] Ignoring invalid proxy {}: {}", proxy, e);
+                }
+            }
+        }
+    }
+
+    if let Some(tls_config) = super::tls::tls_config_override() {
+        builder = builder.use_preconfigured_tls((*tls_config).clone());
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// 按 HTTPS_PROXY/HTTP_PROXY/ALL_PROXY 与 NO_PROXY 选择请求 `url` 时的代理
+#[cfg(any(feature = "http-ureq", feature = "http-reqwest"))]
+fn select_proxy(url: &str) -> Option<String> {
+    let proxy_env = super::proxy::ProxyEnv::from_env();
+    let proxy = proxy_env.proxy_for(url).map(String::from);
+    if proxy.is_none() && proxy_env.bypasses(url) && env::var("YESCODE_DEBUG").is_ok() {
+        eprintln!("[DEBUG] NO_PROXY matches {}, connecting directly", url);
+    }
+    proxy
+}
+
+/// 所有额度请求的 HTTP agent 都在这里构建：代理、TLS 等连接级设置集中于此，
+/// 超时按请求设置。只有显式设置 YESCODE_INSECURE_TLS 才关闭证书校验
+#[cfg(feature = "http-ureq")]
+pub fn build_agent(proxy: Option<&str>) -> ureq::Agent {
+    let mut builder = ureq::AgentBuilder::new();
+    if let Some(proxy) = proxy {
+        match ureq::Proxy::new(proxy) {
+            Ok(proxy_config) => {
+                if env::var("YESCODE_DEBUG").is_ok() {
+                    eprintln!("[DEBUG] Using proxy {}", proxy);
+                }
+                builder = builder.proxy(proxy_config);
+            }
+            Err(e) => {
+                if env::var("YESCODE_DEBUG").is_ok() {
+                    eprintln!("[DEBUG] Ignoring invalid proxy {}: {}", proxy, e);
+                }
+            }
+        }
+    }
+
+    if let Some(tls_config) = super::tls::tls_config_override() {
+        builder = builder.tls_config(tls_config);
+    }
+    builder.build()
+}
+
+// 端点配置
+#[derive(Debug, Clone)]
+pub struct EndpointConfig {
+    pub url: String,
+    pub name: String,
+}
+
+/// 默认的 API 地址
+pub const DEFAULT_BASE_URL: &str = "https://co.yes.vg";
+
+/// 基础地址的候选来源
+#[derive(Debug, Clone, Default)]
+pub struct BaseUrlSources {
+    /// segment options 中的 `base_url`
+    pub config: Option<String>,
+    /// `YESCODE_BASE_URL`
+    pub yescode_env: Option<String>,
+    /// `ANTHROPIC_BASE_URL`（环境变量，其次 settings.json 的 env）
+    pub anthropic_env: Option<String>,
+}
+
+impl BaseUrlSources {
+    /// 显式指定的地址，优先级：配置 > YESCODE_BASE_URL > ANTHROPIC_BASE_URL。
+    /// 空值视为未设置，不是 http/https 地址的值会被跳过，末尾的 `/` 会被去掉
+    pub fn explicit(&self) -> Option<(&'static str, String)> {
+        [
+            ("config", &self.config),
+            ("YESCODE_BASE_URL", &self.yescode_env),
+            ("ANTHROPIC_BASE_URL", &self.anthropic_env),
+        ]
+        .into_iter()
+        .find_map(|(source, value)| {
+            let url = value.as_deref()?.trim().trim_end_matches('/');
+            if url.is_empty() {
+                return None;
+            }
+            if !is_valid_base_url(url) {
+                if env::var("YESCODE_DEBUG").is_ok() {
+                    eprintln!("[DEBUG] Ignoring invalid base URL from {}: {}", source, url);
+                }
+                return None;
+            }
+            Some((source, url.to_string()))
+        })
+    }
+}
+
+/// 基础地址须为 `http://` 或 `https://` 开头且带主机名
+pub fn is_valid_base_url(url: &str) -> bool {
+    let lower = url.to_ascii_lowercase();
+    let Some(rest) = lower
+        .strip_prefix("https://")
+        .or_else(|| lower.strip_prefix("http://"))
+    else {
+        return false;
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+    !host.is_empty() && !url.chars().any(char::is_whitespace)
+}
+
+/// 单次请求的默认超时（毫秒）
+pub const DEFAULT_TIMEOUT_MS: u64 = 5000;
+const MIN_TIMEOUT_MS: u64 = 200;
+const MAX_TIMEOUT_MS: u64 = 15_000;
+
+/// 默认的失败重试次数
+pub const DEFAULT_RETRIES: u32 = 2;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+
+/// 所有额度请求默认带上的 User-Agent，便于服务端识别 ccline 的流量
+pub const USER_AGENT: &str = concat!("ccline-yescc/", env!("CARGO_PKG_VERSION"));
+
+/// 用户不能通过 extra_headers 覆盖的请求头（不区分大小写）
+const PROTECTED_HEADERS: [&str; 3] = ["x-api-key", "accept", "content-type"];
+
+/// 单个端点请求的超时与重试设置；timeout 是包括所有重试在内的总预算
+#[derive(Debug, Clone, Copy)]
+pub struct RequestOptions<'a> {
+    pub timeout: Duration,
+    pub retries: u32,
+    /// 配置中的 extra_headers
+    pub extra_headers: &'a BTreeMap<String, String>,
+}
+
+/// 实际发送的请求头：默认 User-Agent，其次 extra_headers（可覆盖 User-Agent），
+/// 最后是端点必需的头；试图覆盖必需头的配置项被忽略
+fn request_headers<'a>(
+    required: &[(&'a str, &'a str)],
+    extra_headers: &'a BTreeMap<String, String>,
+) -> Vec<(&'a str, &'a str)> {
+    let mut headers = Vec::with_capacity(required.len() + extra_headers.len() + 1);
+    if !extra_headers
+        .keys()
+        .any(|name| name.eq_ignore_ascii_case("user-agent"))
+    {
+        headers.push(("User-Agent", USER_AGENT));
+    }
+    for (name, value) in extra_headers {
+        let protected = PROTECTED_HEADERS
+            .iter()
+            .any(|protected| name.eq_ignore_ascii_case(protected));
+        if protected {
+            if env::var("YESCODE_DEBUG").is_ok() {
+                eprintln!(
+                    "[DEBUG] Ignoring extra header {}: it cannot be overridden",
+                    name
+                );
+            }
+            continue;
+        }
+        headers.push((name.as_str(), value.as_str()));
+    }
+    headers.extend_from_slice(required);
+    headers
+}
+
+/// 网络错误和 5xx 会重试；4xx（例如 key 无效）重试也不会好转
+fn is_retryable(result: &Result<HttpResponse, QuotaError>) -> bool {
+    match result {
+        Ok(response) => response.status >= 500,
+        Err(_) => true,
+    }
+}
+
+/// 带指数退避的 GET：最多额外重试 retries 次，所有尝试和等待共用 timeout 预算
+fn get_with_retry(
+    fetcher: &dyn HttpFetcher,
+    endpoint: &EndpointConfig,
+    headers: &[(&str, &str)],
+    request: RequestOptions,
+) -> Result<HttpResponse, QuotaError> {
+    let headers = request_headers(headers, request.extra_headers);
+    let deadline = Instant::now() + request.timeout;
+    let mut attempt = 1;
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let result = fetcher.get(&endpoint.url, &headers, remaining);
+
+        let delay = RETRY_BASE_DELAY * 2u32.saturating_pow(attempt - 1);
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if attempt > request.retries || !is_retryable(&result) || remaining <= delay {
+            if env::var("YESCODE_DEBUG").is_ok() && attempt > 1 {
+                eprintln!("[DEBUG] {}: {} attempts", endpoint.name, attempt);
+            }
+            return result;
+        }
+
+        if env::var("YESCODE_DEBUG").is_ok() {
+            let reason = match &result {
+                Ok(response) => format!("status {}", response.status),
+                Err(e) => e.to_string(),
+            };
+            eprintln!(
+                "[DEBUG] {}: attempt {} failed ({}), retrying in {}ms",
+                endpoint.name,
+                attempt,
+                reason,
+                delay.as_millis()
+            );
+        }
+        thread::sleep(delay);
+        attempt += 1;
+    }
+}
+
+/// 请求超时：YESCODE_TIMEOUT_MS 优先于配置，限制在 200ms–15000ms
+pub fn resolve_timeout(configured_ms: u64) -> Duration {
+    let timeout_ms = env::var("YESCODE_TIMEOUT_MS")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .unwrap_or(configured_ms);
+    Duration::from_millis(timeout_ms.clamp(MIN_TIMEOUT_MS, MAX_TIMEOUT_MS))
+}
+
+/// 端点缓存默认有效期（秒）
+pub const DEFAULT_ENDPOINT_CACHE_TTL_SECS: u64 = 600;
+
+// 端点缓存
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointCache {
+    api_key_hash: u64,
+    successful_endpoint: String,
+    last_success_time: SystemTime,
+    success_count: u32,
+}
+
+impl EndpointCache {
+    /// 读取端点缓存；文件缺失、损坏或属于其他 key 时返回 None
+    fn load(path: &Path, api_key_hash: u64) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        match serde_json::from_str::<Self>(&content) {
+            Ok(cache) if cache.api_key_hash == api_key_hash => Some(cache),
+            Ok(_) => None,
+            Err(e) => {
+                if env::var("YESCODE_DEBUG").is_ok() {
+                    eprintln!("[DEBUG] Ignoring unreadable endpoint cache: {}", e);
+                }
+                None
+            }
+        }
+    }
+
+    fn is_fresh(&self, ttl: Duration) -> bool {
+        self.last_success_time.elapsed().is_ok_and(|age| age <= ttl)
+    }
+}
+
+/// 解析 200 响应体；非 JSON 的内容（代理错误页等）归类为 Gateway 错误
+pub fn parse_json_body<T: DeserializeOwned>(response: &HttpResponse) -> Result<T, QuotaError> {
+    let content_type = response.header("content-type").unwrap_or("");
+    if !content_type.is_empty() && !content_type.to_ascii_lowercase().contains("json") {
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or(content_type)
+            .trim();
+        return Err(QuotaError::Gateway(format!("proxy returned {}", mime)));
+    }
+
+    let value: serde_json::Value = serde_json::from_str(&response.body)
+        .map_err(|_| QuotaError::Gateway("proxy returned a non-JSON body".to_string()))?;
+    serde_json::from_value(value).map_err(|e| QuotaError::Decode(e.to_string()))
+}
+
+/// 非 200 响应转成错误；503 视为服务维护，并尽量取出 JSON 中的 message
+pub fn status_error(response: &HttpResponse) -> QuotaError {
+    match response.status {
+        401 | 403 => return QuotaError::Unauthorized,
+        429 => {
+            return QuotaError::RateLimited {
+                retry_after: response.header("retry-after").and_then(parse_retry_after),
+            }
+        }
+        503 => {}
+        status => return QuotaError::Http(status),
+    }
+
+    let message = serde_json::from_str::<serde_json::Value>(&response.body)
+        .ok()
+        .and_then(|body| {
+            ["message", "msg", "error"]
+                .iter()
+                .find_map(|key| body.get(key)?.as_str().map(str::trim).map(String::from))
+        })
+        .filter(|message| !message.is_empty());
+    QuotaError::Maintenance(message)
+}
+
+/// Retry-After 可以是秒数，也可以是 HTTP 日期；已过去的日期视为 0
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let secs = (date.with_timezone(&chrono::Utc) - chrono::Utc::now()).num_seconds();
+    Some(Duration::from_secs(secs.max(0) as u64))
+}
+
+fn daily_usage_headers(api_key: &str) -> [(&str, &str); 3] {
+    [
+        ("accept", "*/*"),
+        ("content-type", "application/json"),
+        ("X-API-Key", api_key),
+    ]
+}
+
+fn balance_headers(api_key: &str) -> [(&str, &str); 2] {
+    [("accept", "application/json"), ("X-API-Key", api_key)]
+}
+
+/// 请求一个端点并解析 JSON 响应：两个额度接口共用的重试、日志和错误归类，
+/// 各端点只提供地址、请求头和响应类型
+fn fetch_json<T: DeserializeOwned>(
+    fetcher: &dyn HttpFetcher,
+    endpoint: &EndpointConfig,
+    headers: &[(&str, &str)],
+    request: RequestOptions,
+) -> Result<T, QuotaError> {
+    let debug = env::var("YESCODE_DEBUG").is_ok();
+    if debug {
+        eprintln!("[DEBUG] Fetching {} from: {}", endpoint.name, endpoint.url);
+    }
+
+    let start = Instant::now();
+    let response = get_with_retry(fetcher, endpoint, headers, request).inspect_err(|e| {
+        if debug {
+            eprintln!("[DEBUG] Error: {} - {}", endpoint.name, e);
+        }
+    })?;
+
+    if response.status != 200 {
+        if debug {
+            eprintln!(
+                "[DEBUG] Failed: {} status {}",
+                endpoint.name, response.status
+            );
+        }
+        return Err(status_error(&response));
+    }
+
+    if debug {
+        eprintln!(
+            "[DEBUG] Success: {} in {}ms",
+            endpoint.name,
+            start.elapsed().as_millis()
+        );
+    }
+    let parsed = parse_json_body::<T>(&response);
+    if let (true, Err(e)) = (debug, &parsed) {
+        eprintln!("[DEBUG] Failed: {} - {}", endpoint.name, e);
+    }
+    parsed
+}
+
+/// `quota --check` 中单个端点的请求结果
+#[derive(Debug, Clone)]
+pub struct EndpointCheck {
+    pub name: String,
+    pub url: String,
+    pub elapsed: Duration,
+    /// 收到响应时的状态码；传输层失败时为 None
+    pub status: Option<u16>,
+    /// 失败原因（含原始错误信息）；成功时为 None
+    pub error: Option<String>,
+}
+
+/// 请求一次端点（不重试）并记录耗时、状态码和完整的错误信息
+fn check_endpoint<T: DeserializeOwned>(
+    fetcher: &dyn HttpFetcher,
+    endpoint: EndpointConfig,
+    headers: &[(&str, &str)],
+    request: RequestOptions,
+) -> EndpointCheck {
+    let headers = request_headers(headers, request.extra_headers);
+    let start = Instant::now();
+    let result = fetcher.get(&endpoint.url, &headers, request.timeout);
+    let elapsed = start.elapsed();
+
+    let (status, error) = match result {
+        Ok(response) if response.status == 200 => (
+            Some(200),
+            parse_json_body::<T>(&response).err().map(|e| e.to_string()),
+        ),
+        Ok(response) => {
+            let body = response.body.trim();
+            let error = if body.is_empty() {
+                status_error(&response).to_string()
+            } else {
+                let snippet: String = body.chars().take(200).collect();
+                format!("{} ({})", status_error(&response), snippet)
+            };
+            (Some(response.status), Some(error))
+        }
+        Err(e) => (None, Some(e.to_string())),
+    };
+    EndpointCheck {
+        name: endpoint.name,
+        url: endpoint.url,
+        elapsed,
+        status,
+        error,
+    }
+}
+
+// 智能端点检测器
+pub struct SmartEndpointDetector;
+
+impl SmartEndpointDetector {
+    fn get_daily_usage_endpoint(base_url: &str) -> EndpointConfig {
+        EndpointConfig {
+            url: format!("{}/api/v1/user/usage/daily", base_url),
+            name: "daily_usage".to_string(),
+        }
+    }
+
+    fn get_balance_endpoint(base_url: &str) -> EndpointConfig {
+        EndpointConfig {
+            url: format!("{}/api/v1/user/balance", base_url),
+            name: "balance".to_string(),
+        }
+    }
+
+    /// 两个端点共用的基础地址：显式来源优先；都没有时在有效期内复用上次成功的端点，
+    /// 否则使用默认地址
+    pub fn resolve_base_url(sources: &BaseUrlSources, api_key_hash: u64, ttl: Duration) -> String {
+        if let Some((source, url)) = sources.explicit() {
+            if env::var("YESCODE_DEBUG").is_ok() {
+                eprintln!("[DEBUG] Using base URL from {}: {}", source, url);
+            }
+            return url;
+        }
+
+        let cached = EndpointCache::load(&Self::get_cache_file_path(), api_key_hash)
+            .filter(|cache| cache.is_fresh(ttl));
+        match cached {
+            Some(cache) => {
+                if env::var("YESCODE_DEBUG").is_ok() {
+                    eprintln!(
+                        "[DEBUG] Reusing cached endpoint: {}",
+                        cache.successful_endpoint
+                    );
+                }
+                cache.successful_endpoint
+            }
+            None => {
+                if env::var("YESCODE_DEBUG").is_ok() {
+                    eprintln!("[DEBUG] Using default base URL: {}", DEFAULT_BASE_URL);
+                }
+                DEFAULT_BASE_URL.to_string()
+            }
+        }
+    }
+
+    /// 余额请求成功后更新端点缓存；写入失败不影响渲染
+    pub fn record_success(api_key_hash: u64, base_url: &str) {
+        let path = Self::get_cache_file_path();
+        let success_count = EndpointCache::load(&path, api_key_hash)
+            .filter(|cache| cache.successful_endpoint == base_url)
+            .map_or(0, |cache| cache.success_count);
+        let cache = EndpointCache {
+            api_key_hash,
+            successful_endpoint: base_url.to_string(),
+            last_success_time: SystemTime::now(),
+            success_count: success_count.saturating_add(1),
+        };
+
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Ok(json) = serde_json::to_string_pretty(&cache) {
+            let _ = super::cache::write_atomic(&path, json.as_bytes());
+        }
+    }
+
+    fn get_cache_file_path() -> PathBuf {
+        if let Some(home) = dirs::home_dir() {
+            home.join(".claude")
+                .join("ccline")
+                .join("endpoint_cache.json")
+        } else {
+            PathBuf::from("endpoint_cache.json")
+        }
+    }
+
+    /// 缓存文件中用来识别 API key 的哈希（64 位 FNV-1a）。
+    /// 与 DefaultHasher 不同，结果不随 Rust 版本或平台变化，升级工具链后缓存仍然有效
+    pub fn hash_api_key(api_key: &str) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        api_key.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+        })
+    }
+
+    pub fn fetch_daily_usage(
+        fetcher: &dyn HttpFetcher,
+        base_url: &str,
+        api_key: &str,
+        request: RequestOptions,
+    ) -> Result<DailyUsageApiResponse, QuotaError> {
+        fetch_json(
+            fetcher,
+            &Self::get_daily_usage_endpoint(base_url),
+            &daily_usage_headers(api_key),
+            request,
+        )
+    }
+
+    pub fn fetch_balance(
+        fetcher: &dyn HttpFetcher,
+        base_url: &str,
+        api_key: &str,
+        request: RequestOptions,
+    ) -> Result<BalanceApiResponse, QuotaError> {
+        fetch_json(
+            fetcher,
+            &Self::get_balance_endpoint(base_url),
+            &balance_headers(api_key),
+            request,
+        )
+    }
+
+    /// `quota --check`：依次请求两个端点各一次，不重试、不写任何缓存
+    pub fn check_endpoints(
+        fetcher: &dyn HttpFetcher,
+        base_url: &str,
+        api_key: &str,
+        request: RequestOptions,
+    ) -> Vec<EndpointCheck> {
+        vec![
+            check_endpoint::<DailyUsageApiResponse>(
+                fetcher,
+                Self::get_daily_usage_endpoint(base_url),
+                &daily_usage_headers(api_key),
+                request,
+            ),
+            check_endpoint::<BalanceApiResponse>(
+                fetcher,
+                Self::get_balance_endpoint(base_url),
+                &balance_headers(api_key),
+                request,
+            ),
+        ]
+    }
+
+    /// 两个接口互不依赖，在两个线程上同时请求，最坏耗时取两者较大值而不是之和
+    ///
+    /// 某个线程 panic 时只把那一路视为失败，不影响另一路。
+    pub fn fetch_all(
+        fetcher: &dyn HttpFetcher,
+        base_url: &str,
+        api_key: &str,
+        request: RequestOptions,
+    ) -> (
+        Result<DailyUsageApiResponse, QuotaError>,
+        Result<BalanceApiResponse, QuotaError>,
+    ) {
+        thread::scope(|scope| {
+            let daily_usage =
+                scope.spawn(|| Self::fetch_daily_usage(fetcher, base_url, api_key, request));
+            let balance = scope.spawn(|| Self::fetch_balance(fetcher, base_url, api_key, request));
+
+            let panicked = |name: &str| {
+                if env::var("YESCODE_DEBUG").is_ok() {
+                    eprintln!("[DEBUG] {} fetch thread panicked", name);
+                }
+            };
+            let daily_usage = daily_usage.join().unwrap_or_else(|_| {
+                panicked("Daily usage");
+                Err(QuotaError::Network("fetch thread panicked".to_string()))
+            });
+            let balance = balance.join().unwrap_or_else(|_| {
+                panicked("Balance");
+                Err(QuotaError::Network("fetch thread panicked".to_string()))
+            });
+            (daily_usage, balance)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, content_type: &str, body: &str) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: vec![("Content-Type".to_string(), content_type.to_string())],
+            body: body.to_string(),
+        }
+    }
+
+    fn daily(date: &str, total_cost: f64) -> DailyUsage {
+        DailyUsage {
+            date: date.to_string(),
+            total_cost,
+        }
+    }
+
+    #[test]
+    fn daily_usage_accepts_the_wrapped_shape() {
+        let response: DailyUsageApiResponse = serde_json::from_str(
+            r#"{ "daily_usage": [{ "date": "2026-10-14", "total_cost": 1.5 }] }"#,
+        )
+        .unwrap();
+        assert_eq!(response.daily_usage, vec![daily("2026-10-14", 1.5)]);
+    }
+
+    #[test]
+    fn daily_usage_accepts_a_bare_array() {
+        let response: DailyUsageApiResponse = serde_json::from_str(
+            r#"[{ "date": "2026-10-13", "total_cost": 2.0 }, { "date": "2026-10-14", "total_cost": 0.25 }]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            response.daily_usage,
+            vec![daily("2026-10-13", 2.0), daily("2026-10-14", 0.25)]
+        );
+    }
+
+    #[test]
+    fn daily_usage_rejects_other_shapes() {
+        assert!(serde_json::from_str::<DailyUsageApiResponse>(r#"{ "usage": [] }"#).is_err());
+    }
+
+    #[test]
+    fn html_error_page_is_a_gateway_error() {
+        let page = response(
+            200,
+            "text/html; charset=utf-8",
+            "<html>502 Bad Gateway</html>",
+        );
+        assert_eq!(
+            parse_json_body::<BalanceApiResponse>(&page),
+            Err(QuotaError::Gateway("proxy returned text/html".to_string()))
+        );
+    }
+
+    #[test]
+    fn non_json_body_is_a_gateway_error_even_when_labelled_json() {
+        let page = response(200, "application/json", "<html>oops</html>");
+        assert_eq!(
+            parse_json_body::<BalanceApiResponse>(&page),
+            Err(QuotaError::Gateway(
+                "proxy returned a non-JSON body".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn json_with_the_wrong_shape_is_a_parse_error() {
+        let body = response(200, "application/json", r#"{ "total_balance": "lots" }"#);
+        assert!(matches!(
+            parse_json_body::<BalanceApiResponse>(&body),
+            Err(QuotaError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn service_unavailable_is_maintenance() {
+        assert_eq!(
+            status_error(&response(
+                503,
+                "application/json",
+                r#"{ "message": " Back at 02:00 UTC " }"#
+            )),
+            QuotaError::Maintenance(Some("Back at 02:00 UTC".to_string()))
+        );
+        assert_eq!(
+            status_error(&response(503, "application/json", r#"{ "msg": "" }"#)),
+            QuotaError::Maintenance(None)
+        );
+        assert_eq!(
+            status_error(&response(503, "text/html", "<h1>Down</h1>")),
+            QuotaError::Maintenance(None)
+        );
+    }
+
+    #[test]
+    fn other_statuses_stay_generic() {
+        let body = r#"{ "message": "nope" }"#;
+        assert_eq!(
+            status_error(&response(500, "application/json", body)),
+            QuotaError::Http(500)
+        );
+        assert_eq!(
+            status_error(&response(502, "application/json", body)),
+            QuotaError::Http(502)
+        );
+    }
+
+    #[test]
+    fn base_url_precedence_across_all_sources() {
+        let value = |set: bool, url: &str| set.then(|| url.to_string());
+        for mask in 0..8 {
+            let (config, yescode, anthropic) = (mask & 1 != 0, mask & 2 != 0, mask & 4 != 0);
+            let sources = BaseUrlSources {
+                config: value(config, "https://config.example"),
+                yescode_env: value(yescode, "https://yescode.example"),
+                anthropic_env: value(anthropic, "https://anthropic.example"),
+            };
+            let expected = if config {
+                Some(("config", "https://config.example"))
+            } else if yescode {
+                Some(("YESCODE_BASE_URL", "https://yescode.example"))
+            } else if anthropic {
+                Some(("ANTHROPIC_BASE_URL", "https://anthropic.example"))
+            } else {
+                None
+            };
+            assert_eq!(
+                sources
+                    .explicit()
+                    .as_ref()
+                    .map(|(source, url)| (*source, url.as_str())),
+                expected,
+                "config={} yescode={} anthropic={}",
+                config,
+                yescode,
+                anthropic
+            );
+        }
+    }
+
+    #[test]
+    fn empty_sources_fall_through() {
+        let sources = BaseUrlSources {
+            config: Some("  ".to_string()),
+            yescode_env: Some(String::new()),
+            anthropic_env: Some("https://anthropic.example/".to_string()),
+        };
+        assert_eq!(
+            sources.explicit(),
+            Some((
+                "ANTHROPIC_BASE_URL",
+                "https://anthropic.example".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn default_base_url_without_any_source() {
+        let _env = crate::test_support::TestEnv::new();
+        assert_eq!(
+            SmartEndpointDetector::resolve_base_url(
+                &BaseUrlSources::default(),
+                1,
+                Duration::from_secs(600)
+            ),
+            DEFAULT_BASE_URL
+        );
+    }
+
+    #[test]
+    fn timeout_is_configurable_and_clamped() {
+        let env = crate::test_support::TestEnv::new();
+        assert_eq!(resolve_timeout(DEFAULT_TIMEOUT_MS), Duration::from_secs(5));
+        assert_eq!(resolve_timeout(800), Duration::from_millis(800));
+        assert_eq!(resolve_timeout(1), Duration::from_millis(MIN_TIMEOUT_MS));
+        assert_eq!(
+            resolve_timeout(60_000),
+            Duration::from_millis(MAX_TIMEOUT_MS)
+        );
+
+        env.set("YESCODE_TIMEOUT_MS", " 300 ");
+        assert_eq!(resolve_timeout(800), Duration::from_millis(300));
+        env.set("YESCODE_TIMEOUT_MS", "fast");
+        assert_eq!(resolve_timeout(800), Duration::from_millis(800));
+    }
+
+    /// 用计数的 mock 发起一次带重试的请求，返回结果和尝试次数
+    fn attempts(
+        results: Vec<Result<HttpResponse, QuotaError>>,
+        retries: u32,
+        timeout: Duration,
+    ) -> (Result<HttpResponse, QuotaError>, usize) {
+        let fetcher = super::super::fixture::MockFetcher::new().route("/balance", results);
+        let endpoint = EndpointConfig {
+            url: "https://co.yes.vg/api/v1/user/balance".to_string(),
+            name: "balance".to_string(),
+        };
+        let result = get_with_retry(
+            &fetcher,
+            &endpoint,
+            &[],
+            RequestOptions {
+                timeout,
+                retries,
+                extra_headers: &BTreeMap::new(),
+            },
+        );
+        (result, fetcher.requests().len())
+    }
+
+    fn status(status: u16) -> Result<HttpResponse, QuotaError> {
+        Ok(response(status, "application/json", "{}"))
+    }
+
+    #[test]
+    fn client_errors_are_not_retried() {
+        for code in [401, 403, 404] {
+            let (result, count) = attempts(vec![status(code)], 2, Duration::from_secs(5));
+            assert_eq!(result.unwrap().status, code);
+            assert_eq!(count, 1, "HTTP {}", code);
+        }
+    }
+
+    #[test]
+    fn server_errors_are_retried_with_backoff() {
+        let started = Instant::now();
+        let (result, count) = attempts(vec![status(500)], 2, Duration::from_secs(5));
+        assert_eq!(result.unwrap().status, 500);
+        assert_eq!(count, 3);
+        // 两次等待：100ms + 200ms
+        assert!(started.elapsed() >= RETRY_BASE_DELAY * 3);
+
+        let (result, count) = attempts(
+            vec![
+                Err(QuotaError::Network("connection reset".to_string())),
+                status(502),
+                status(200),
+            ],
+            2,
+            Duration::from_secs(5),
+        );
+        assert_eq!(result.unwrap().status, 200);
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn retries_stop_at_the_configured_count_and_time_budget() {
+        let (_, count) = attempts(vec![status(503)], 0, Duration::from_secs(5));
+        assert_eq!(count, 1);
+
+        // 250ms 的预算：第一次等待 100ms 后，剩余时间不够第二次等待 200ms
+        let (_, count) = attempts(vec![status(503)], 5, Duration::from_millis(250));
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn api_key_hash_is_stable() {
+        // FNV-1a 64 位的标准测试向量
+        assert_eq!(
+            SmartEndpointDetector::hash_api_key(""),
+            0xcbf2_9ce4_8422_2325
+        );
+        assert_eq!(
+            SmartEndpointDetector::hash_api_key("a"),
+            0xaf63_dc4c_8601_ec8c
+        );
+        assert_eq!(
+            SmartEndpointDetector::hash_api_key("foobar"),
+            0x8594_4171_f739_67e8
+        );
+        assert_ne!(
+            SmartEndpointDetector::hash_api_key("test-key"),
+            SmartEndpointDetector::hash_api_key("test-kez")
+        );
+    }
+
+    /// 每个请求都等到另一个请求也已发出（最多 5 秒）才返回的 fetcher，记录同时进行的最大请求数；
+    /// 可让日用量请求 panic
+    #[derive(Default)]
+    struct OverlapFetcher {
+        panic_on_daily: bool,
+        in_flight: std::sync::atomic::AtomicUsize,
+        max_in_flight: std::sync::atomic::AtomicUsize,
+    }
+
+    impl HttpFetcher for OverlapFetcher {
+        fn get(
+            &self,
+            url: &str,
+            _: &[(&str, &str)],
+            _: Duration,
+        ) -> Result<HttpResponse, QuotaError> {
+            use std::sync::atomic::Ordering::SeqCst;
+
+            let in_flight = self.in_flight.fetch_add(1, SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, SeqCst);
+            if url.contains("/usage/daily") {
+                assert!(!self.panic_on_daily, "daily usage fetch failed");
+            }
+            // 依次请求时另一路永远不会出现，等到期限后返回，由测试断言失败
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while self.max_in_flight.load(SeqCst) < 2 && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(1));
+            }
+            self.in_flight.fetch_sub(1, SeqCst);
+
+            if url.contains("/usage/daily") {
+                Ok(response(
+                    200,
+                    "application/json",
+                    r#"{ "daily_usage": [{ "date": "2026-10-14", "total_cost": 1.5 }] }"#,
+                ))
+            } else {
+                Ok(response(
+                    200,
+                    "application/json",
+                    r#"{ "balance": 7, "pay_as_you_go_balance": 0, "subscription_balance": 7,
+                         "total_balance": 7, "weekly_limit": 60, "weekly_spent_balance": 12 }"#,
+                ))
+            }
+        }
+    }
+
+    fn fetch_all_with(
+        fetcher: &dyn HttpFetcher,
+    ) -> (
+        Result<DailyUsageApiResponse, QuotaError>,
+        Result<BalanceApiResponse, QuotaError>,
+    ) {
+        SmartEndpointDetector::fetch_all(
+            fetcher,
+            DEFAULT_BASE_URL,
+            "test-key",
+            RequestOptions {
+                timeout: Duration::from_secs(5),
+                retries: 0,
+                extra_headers: &BTreeMap::new(),
+            },
+        )
+    }
+
+    #[test]
+    fn concurrent_fetch_matches_each_response_to_its_endpoint() {
+        let fetcher = OverlapFetcher::default();
+        let (daily_usage, balance) = fetch_all_with(&fetcher);
+        // 两个请求同时在进行中
+        assert_eq!(
+            fetcher
+                .max_in_flight
+                .load(std::sync::atomic::Ordering::SeqCst),
+            2
+        );
+
+        assert_eq!(
+            daily_usage.unwrap().daily_usage,
+            vec![daily("2026-10-14", 1.5)]
+        );
+        let balance = balance.unwrap();
+        assert_eq!(balance.total_balance, 7.0);
+        assert_eq!(balance.weekly_spent_balance, 12.0);
+    }
+
+    #[test]
+    fn a_panicking_fetch_only_fails_its_own_endpoint() {
+        let (daily_usage, balance) = fetch_all_with(&OverlapFetcher {
+            panic_on_daily: true,
+            ..OverlapFetcher::default()
+        });
+        assert_eq!(
+            daily_usage.unwrap_err(),
+            QuotaError::Network("fetch thread panicked".to_string())
+        );
+        assert_eq!(balance.unwrap().weekly_limit, 60.0);
+    }
+
+    /// 只应答一次的本地 HTTP 服务器，返回地址和它收到的请求头（小写）
+    #[cfg(any(feature = "http-ureq", feature = "http-reqwest"))]
+    fn serve_once(response: &'static str) -> (String, thread::JoinHandle<String>) {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!(
+            "http://{}/api/v1/user/balance",
+            listener.local_addr().unwrap()
+        );
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                    break;
+                }
+                request.push_str(&line.to_ascii_lowercase());
+            }
+            (&stream).write_all(response.as_bytes()).unwrap();
+            request
+        });
+        (url, server)
+    }
+
+    /// 两个后端对同一个响应给出相同的 HttpResponse，4xx/5xx 不当作传输错误
+    #[cfg(any(feature = "http-ureq", feature = "http-reqwest"))]
+    fn backend_passes_status_headers_and_body_through(fetcher: &dyn HttpFetcher) {
+        let _env = crate::test_support::TestEnv::new();
+        let (url, server) = serve_once(
+            "HTTP/1.1 503 Service Unavailable\r\nContent-Type: application/json\r\n\
+             Retry-After: 30\r\nContent-Length: 13\r\nConnection: close\r\n\r\n{\"error\":\"x\"}",
+        );
+
+        let response = fetcher
+            .get(&url, &[("X-API-Key", "test-key")], Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(response.status, 503);
+        assert_eq!(response.header("retry-after"), Some("30"));
+        assert_eq!(response.header("content-type"), Some("application/json"));
+        assert_eq!(response.body, "{\"error\":\"x\"}");
+        assert!(server.join().unwrap().contains("x-api-key: test-key\r\n"));
+    }
+
+    /// 接受连接但从不响应的服务器：按错误类型而不是错误信息判断为超时
+    #[cfg(any(feature = "http-ureq", feature = "http-reqwest"))]
+    fn backend_reports_a_silent_server_as_timeout(fetcher: &dyn HttpFetcher) {
+        let _env = crate::test_support::TestEnv::new();
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!(
+            "http://{}/api/v1/user/balance",
+            listener.local_addr().unwrap()
+        );
+
+        let result = fetcher.get(&url, &[], Duration::from_millis(200));
+        assert_eq!(result.unwrap_err(), QuotaError::Timeout);
+    }
+
+    #[cfg(any(feature = "http-ureq", feature = "http-reqwest"))]
+    fn backend_reports_a_refused_connection_as_network_error(fetcher: &dyn HttpFetcher) {
+        let _env = crate::test_support::TestEnv::new();
+        // 绑定后立即关闭，得到一个没有人监听的端口
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let url = format!("http://127.0.0.1:{}/api/v1/user/balance", port);
+
+        let result = fetcher.get(&url, &[], Duration::from_secs(5));
+        assert!(
+            matches!(result, Err(QuotaError::Network(_))),
+            "{:?}",
+            result
+        );
+    }
+
+    #[cfg(feature = "http-ureq")]
+    #[test]
+    fn ureq_backend_passes_responses_through() {
+        backend_passes_status_headers_and_body_through(&UreqFetcher::default());
+    }
+
+    #[cfg(feature = "http-ureq")]
+    #[test]
+    fn ureq_backend_classifies_transport_errors() {
+        backend_reports_a_silent_server_as_timeout(&UreqFetcher::default());
+        backend_reports_a_refused_connection_as_network_error(&UreqFetcher::default());
+    }
+
+    #[cfg(feature = "http-reqwest")]
+    #[test]
+    fn reqwest_backend_passes_responses_through() {
+        backend_passes_status_headers_and_body_through(&ReqwestFetcher::default());
+    }
+
+    #[cfg(feature = "http-reqwest")]
+    #[test]
+    fn reqwest_backend_classifies_transport_errors() {
+        backend_reports_a_silent_server_as_timeout(&ReqwestFetcher::default());
+        backend_reports_a_refused_connection_as_network_error(&ReqwestFetcher::default());
+    }
+}
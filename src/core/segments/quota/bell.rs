@@ -0,0 +1,82 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+const BEL: &[u8] = b"\x07";
+
+fn get_sentinel_path() -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    Some(home.join(".claude").join("ccline").join("over_limit_bell"))
+}
+
+/// 本会话是否还没响过铃；没有会话标识时无法去重，干脆不响
+pub fn should_ring(rung_session: Option<&str>, session: &str) -> bool {
+    !session.is_empty() && rung_session != Some(session)
+}
+
+/// 超出周限制时响铃，每个会话只响一次；哨兵文件记录最后响铃的会话
+pub fn ring_once(session: &str) {
+    if !claim(session) {
+        return;
+    }
+
+    if env::var("YESCODE_DEBUG").is_ok() {
+        eprintln!("[DEBUG] Weekly limit exceeded, ringing bell for this session");
+    }
+    emit_bell();
+}
+
+/// 把本会话记入哨兵文件；返回本次是否应该响铃
+fn claim(session: &str) -> bool {
+    let Some(path) = get_sentinel_path() else {
+        return false;
+    };
+    let rung_session = fs::read_to_string(&path).ok();
+    if !should_ring(rung_session.as_deref().map(str::trim), session) {
+        return false;
+    }
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    // 先写哨兵再响铃：写入失败时宁可不响，也不要每次渲染都响
+    super::cache::write_atomic(&path, session.as_bytes()).is_ok()
+}
+
+/// stdout 交给 Claude Code 渲染，BEL 直接写到终端；没有终端时写 stderr
+fn emit_bell() {
+    #[cfg(unix)]
+    if let Ok(mut tty) = fs::OpenOptions::new().write(true).open("/dev/tty") {
+        let _ = tty.write_all(BEL);
+        return;
+    }
+
+    let _ = std::io::stderr().write_all(BEL);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestEnv;
+
+    #[test]
+    fn rings_once_per_session() {
+        let _env = TestEnv::new();
+        assert!(claim("session-a"));
+        assert!(!claim("session-a"));
+        assert!(!claim("session-a"));
+
+        assert!(claim("session-b"));
+        assert!(!claim("session-b"));
+    }
+
+    #[test]
+    fn never_rings_without_a_session() {
+        let _env = TestEnv::new();
+        assert!(!claim(""));
+        assert!(!should_ring(None, ""));
+        assert!(should_ring(None, "session-a"));
+        assert!(!should_ring(Some("session-a"), "session-a"));
+    }
+}
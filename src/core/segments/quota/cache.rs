@@ -0,0 +1,361 @@
+use super::api::{BalanceApiResponse, DailyUsageApiResponse};
+use super::options::{CacheKey, CacheWriteStrategy};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// 响应快照：一次成功获取的全部数据
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuotaSnapshot {
+    pub daily_usage: Option<DailyUsageApiResponse>,
+    pub balance: BalanceApiResponse,
+}
+
+/// 每个账户最多记住的 API key 数量
+const MAX_ACCOUNT_KEYS: usize = 16;
+
+// 响应缓存（与端点缓存分开存放）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuotaCache {
+    pub api_key_hash: u64,
+    /// 按账户缓存时记录的账户标识
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    /// 成功获取时确认属于 account_id 的 API key 哈希，最近使用的在后
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub account_key_hashes: Vec<u64>,
+    pub cached_at: String,
+    pub snapshot: QuotaSnapshot,
+}
+
+impl QuotaCache {
+    fn get_cache_path() -> Option<PathBuf> {
+        let home = dirs::home_dir()?;
+        Some(home.join(".claude").join("ccline").join("quota_cache.json"))
+    }
+
+    /// 损坏（例如写到一半被中断）的缓存视为未命中并删除
+    fn load() -> Option<Self> {
+        let cache_path = Self::get_cache_path()?;
+        let content = fs::read_to_string(&cache_path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(cache) => Some(cache),
+            Err(e) => {
+                if env::var("YESCODE_DEBUG").is_ok() {
+                    eprintln!("[DEBUG] Corrupt quota cache, removing it: {}", e);
+                }
+                let _ = fs::remove_file(&cache_path);
+                None
+            }
+        }
+    }
+
+    /// 读取属于当前 API key（按账户缓存时为当前账户）的缓存
+    pub fn load_for(api_key_hash: u64, cache_key: CacheKey) -> Option<Self> {
+        Self::load().filter(|cache| cache.belongs_to(api_key_hash, cache_key))
+    }
+
+    /// 按账户缓存时，只有确认属于同一账户的 key 才能共用缓存；
+    /// 新 key 第一次使用时未命中，成功获取后记入该账户
+    fn belongs_to(&self, api_key_hash: u64, cache_key: CacheKey) -> bool {
+        self.api_key_hash == api_key_hash
+            || (cache_key == CacheKey::Account
+                && self.account_id.is_some()
+                && self.account_key_hashes.contains(&api_key_hash))
+    }
+
+    /// 本次写入后属于该账户的 key：沿用同一账户已知的 key，再加上当前 key
+    fn account_key_hashes(
+        previous: Option<&Self>,
+        account_id: Option<&String>,
+        api_key_hash: u64,
+    ) -> Vec<u64> {
+        let Some(account_id) = account_id else {
+            return Vec::new();
+        };
+
+        let mut hashes = Vec::new();
+        if let Some(previous) =
+            previous.filter(|cache| cache.account_id.as_ref() == Some(account_id))
+        {
+            hashes.extend(&previous.account_key_hashes);
+            hashes.push(previous.api_key_hash);
+        }
+        hashes.retain(|hash| *hash != api_key_hash);
+        hashes.dedup();
+        hashes.push(api_key_hash);
+
+        let excess = hashes.len().saturating_sub(MAX_ACCOUNT_KEYS);
+        hashes.drain(..excess);
+        hashes
+    }
+
+    fn save(&self) {
+        if let Some(cache_path) = Self::get_cache_path() {
+            if let Some(parent) = cache_path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            if let Ok(json) = serde_json::to_string_pretty(self) {
+                let _ = write_atomic(&cache_path, json.as_bytes());
+            }
+        }
+    }
+
+    /// 按写入策略保存最新快照
+    pub fn store(
+        api_key_hash: u64,
+        snapshot: &QuotaSnapshot,
+        strategy: CacheWriteStrategy,
+        cache_key: CacheKey,
+    ) {
+        let account_id = match cache_key {
+            CacheKey::Account => snapshot.balance.account_id.clone(),
+            CacheKey::ApiKey => None,
+        };
+
+        let previous = Self::load();
+        if strategy == CacheWriteStrategy::OnChange {
+            let unchanged = previous.as_ref().is_some_and(|cache| {
+                cache.belongs_to(api_key_hash, cache_key)
+                    && cache.account_id == account_id
+                    && cache.snapshot == *snapshot
+            });
+            if unchanged {
+                if env::var("YESCODE_DEBUG").is_ok() {
+                    eprintln!("[DEBUG] Quota cache unchanged, skipping write");
+                }
+                return;
+            }
+        }
+
+        let cache = Self {
+            api_key_hash,
+            account_key_hashes: Self::account_key_hashes(
+                previous.as_ref(),
+                account_id.as_ref(),
+                api_key_hash,
+            ),
+            account_id,
+            cached_at: chrono::Utc::now().to_rfc3339(),
+            snapshot: snapshot.clone(),
+        };
+        cache.save();
+    }
+}
+
+/// 先写同目录的临时文件再 rename，读者不会看到写了一半的内容
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(format!(".{}.tmp", std::process::id()));
+    let tmp_path = path.with_file_name(tmp_name);
+
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp_path);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestEnv;
+
+    fn snapshot(weekly_spent: f64) -> QuotaSnapshot {
+        QuotaSnapshot {
+            daily_usage: None,
+            balance: serde_json::from_value(serde_json::json!({
+                "balance": 50.0,
+                "pay_as_you_go_balance": 20.0,
+                "subscription_balance": 30.0,
+                "total_balance": 50.0,
+                "weekly_limit": 100.0,
+                "weekly_spent_balance": weekly_spent,
+            }))
+            .unwrap(),
+        }
+    }
+
+    fn cached_at() -> String {
+        QuotaCache::load().expect("cache written").cached_at
+    }
+
+    #[test]
+    fn on_change_skips_identical_snapshots() {
+        let _env = TestEnv::new();
+        QuotaCache::store(
+            1,
+            &snapshot(10.0),
+            CacheWriteStrategy::OnChange,
+            CacheKey::ApiKey,
+        );
+        let first = cached_at();
+
+        QuotaCache::store(
+            1,
+            &snapshot(10.0),
+            CacheWriteStrategy::OnChange,
+            CacheKey::ApiKey,
+        );
+        assert_eq!(cached_at(), first);
+
+        QuotaCache::store(
+            1,
+            &snapshot(12.5),
+            CacheWriteStrategy::OnChange,
+            CacheKey::ApiKey,
+        );
+        assert_ne!(cached_at(), first);
+        assert_eq!(QuotaCache::load().unwrap().snapshot, snapshot(12.5));
+    }
+
+    #[test]
+    fn always_rewrites_identical_snapshots() {
+        let _env = TestEnv::new();
+        QuotaCache::store(
+            1,
+            &snapshot(10.0),
+            CacheWriteStrategy::Always,
+            CacheKey::ApiKey,
+        );
+        let first = cached_at();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+
+        QuotaCache::store(
+            1,
+            &snapshot(10.0),
+            CacheWriteStrategy::Always,
+            CacheKey::ApiKey,
+        );
+        assert_ne!(cached_at(), first);
+    }
+
+    #[test]
+    fn on_change_rewrites_for_another_key() {
+        let _env = TestEnv::new();
+        QuotaCache::store(
+            1,
+            &snapshot(10.0),
+            CacheWriteStrategy::OnChange,
+            CacheKey::ApiKey,
+        );
+        QuotaCache::store(
+            2,
+            &snapshot(10.0),
+            CacheWriteStrategy::OnChange,
+            CacheKey::ApiKey,
+        );
+        assert!(QuotaCache::load_for(2, CacheKey::ApiKey).is_some());
+        assert!(QuotaCache::load_for(1, CacheKey::ApiKey).is_none());
+    }
+
+    fn account_snapshot(account_id: &str) -> QuotaSnapshot {
+        let mut snapshot = snapshot(10.0);
+        snapshot.balance.account_id = Some(account_id.to_string());
+        snapshot
+    }
+
+    fn store_for_account(api_key_hash: u64, account_id: &str) {
+        QuotaCache::store(
+            api_key_hash,
+            &account_snapshot(account_id),
+            CacheWriteStrategy::Always,
+            CacheKey::Account,
+        );
+    }
+
+    #[test]
+    fn rotated_key_shares_the_cache_once_known_to_the_account() {
+        let _env = TestEnv::new();
+        store_for_account(1, "acct-1");
+
+        // 新 key 尚未确认属于哪个账户，不能读取别的 key 留下的缓存
+        assert!(QuotaCache::load_for(2, CacheKey::Account).is_none());
+
+        store_for_account(2, "acct-1");
+        assert!(QuotaCache::load_for(2, CacheKey::Account).is_some());
+        assert!(QuotaCache::load_for(1, CacheKey::Account).is_some());
+        assert_eq!(QuotaCache::load().unwrap().account_key_hashes, vec![1, 2]);
+
+        // 按 API key 缓存时不共用
+        assert!(QuotaCache::load_for(1, CacheKey::ApiKey).is_none());
+    }
+
+    #[test]
+    fn another_accounts_cache_is_not_shared() {
+        let _env = TestEnv::new();
+        store_for_account(1, "acct-1");
+        store_for_account(2, "acct-2");
+
+        assert!(QuotaCache::load_for(1, CacheKey::Account).is_none());
+        assert!(QuotaCache::load_for(2, CacheKey::Account).is_some());
+        assert_eq!(QuotaCache::load().unwrap().account_key_hashes, vec![2]);
+    }
+
+    #[test]
+    fn without_an_account_id_the_key_hash_decides() {
+        let _env = TestEnv::new();
+        QuotaCache::store(
+            1,
+            &snapshot(10.0),
+            CacheWriteStrategy::Always,
+            CacheKey::Account,
+        );
+        QuotaCache::store(
+            2,
+            &snapshot(10.0),
+            CacheWriteStrategy::Always,
+            CacheKey::Account,
+        );
+
+        assert!(QuotaCache::load_for(1, CacheKey::Account).is_none());
+        assert!(QuotaCache::load_for(2, CacheKey::Account).is_some());
+        assert!(QuotaCache::load().unwrap().account_key_hashes.is_empty());
+    }
+
+    #[test]
+    fn account_keys_are_capped_keeping_the_most_recent() {
+        let _env = TestEnv::new();
+        let last = MAX_ACCOUNT_KEYS as u64 + 4;
+        for api_key_hash in 1..=last {
+            store_for_account(api_key_hash, "acct-1");
+        }
+        store_for_account(last - 1, "acct-1");
+
+        let hashes = QuotaCache::load().unwrap().account_key_hashes;
+        assert_eq!(hashes.len(), MAX_ACCOUNT_KEYS);
+        assert_eq!(hashes[MAX_ACCOUNT_KEYS - 2..], [last, last - 1]);
+        assert!(QuotaCache::load_for(1, CacheKey::Account).is_none());
+        assert!(QuotaCache::load_for(last, CacheKey::Account).is_some());
+    }
+
+    #[test]
+    fn truncated_cache_is_a_miss_and_removed() {
+        let env = TestEnv::new();
+        QuotaCache::store(
+            1,
+            &snapshot(10.0),
+            CacheWriteStrategy::Always,
+            CacheKey::ApiKey,
+        );
+        let path = env.home().join(".claude/ccline/quota_cache.json");
+        let content = fs::read_to_string(&path).unwrap();
+        fs::write(&path, &content[..content.len() / 2]).unwrap();
+
+        assert!(QuotaCache::load_for(1, CacheKey::ApiKey).is_none());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn atomic_writes_leave_no_temporary_file() {
+        let env = TestEnv::new();
+        let path = env.home().join("cache.json");
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        let entries: Vec<_> = fs::read_dir(env.home()).unwrap().collect();
+        assert_eq!(entries.len(), 1);
+    }
+}
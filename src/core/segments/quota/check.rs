@@ -0,0 +1,42 @@
+use super::api::EndpointCheck;
+
+/// `ccline quota --check` 的诊断报告，一行一项，标签左对齐
+#[derive(Debug, Default)]
+pub struct Report {
+    lines: Vec<String>,
+}
+
+impl Report {
+    pub fn line(&mut self, label: &str, value: &str) {
+        self.lines.push(format!("{:<12} {}", label, value));
+    }
+
+    /// `balance      GET https://co.yes.vg/api/v1/user/balance`，下一行为状态码、耗时和结果
+    pub fn endpoint(&mut self, check: &EndpointCheck) {
+        self.line(&check.name, &format!("GET {}", check.url));
+        let status = match check.status {
+            Some(status) => format!("HTTP {}", status),
+            None => "no response".to_string(),
+        };
+        let outcome = check.error.as_deref().unwrap_or("ok");
+        self.line(
+            "",
+            &format!("{} in {}ms: {}", status, check.elapsed.as_millis(), outcome),
+        );
+    }
+
+    pub fn finish(self) -> String {
+        self.lines.join("\n")
+    }
+}
+
+/// 只保留首尾各 3 个字符，足以确认用的是哪个 key 而不泄露它
+pub fn mask_api_key(api_key: &str) -> String {
+    let chars: Vec<char> = api_key.chars().collect();
+    if chars.len() <= 6 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..3].iter().collect();
+    let tail: String = chars[chars.len() - 3..].iter().collect();
+    format!("{}...{}", head, tail)
+}
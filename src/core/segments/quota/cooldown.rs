@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 429 未带 Retry-After 时的冷却时长
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Retry-After 过大（或日期写错）时最多冷却这么久
+const MAX_COOLDOWN: Duration = Duration::from_secs(3600);
+
+/// 收到 429 后的冷却期：结束前所有终端都不再请求，只使用缓存
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cooldown {
+    pub api_key_hash: u64,
+    pub until_ms: u64,
+}
+
+fn get_cooldown_path() -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    Some(
+        home.join(".claude")
+            .join("ccline")
+            .join("quota_cooldown.json"),
+    )
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 记录冷却期：优先使用 Retry-After，否则 60 秒；返回实际的冷却时长
+pub fn start(api_key_hash: u64, retry_after: Option<Duration>) -> Duration {
+    let duration = retry_after.unwrap_or(DEFAULT_COOLDOWN).min(MAX_COOLDOWN);
+    if let Some(path) = get_cooldown_path() {
+        start_at(&path, api_key_hash, duration, now_ms());
+    }
+    duration
+}
+
+/// 当前 key 剩余的冷却时长；没有冷却或已结束时为 None
+pub fn remaining(api_key_hash: u64) -> Option<Duration> {
+    remaining_at(&get_cooldown_path()?, api_key_hash, now_ms())
+}
+
+/// 请求成功后结束冷却
+pub fn clear() {
+    if let Some(path) = get_cooldown_path() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn start_at(path: &Path, api_key_hash: u64, duration: Duration, now_ms: u64) {
+    let cooldown = Cooldown {
+        api_key_hash,
+        until_ms: now_ms.saturating_add(duration.as_millis() as u64),
+    };
+    if env::var("YESCODE_DEBUG").is_ok() {
+        eprintln!(
+            "[DEBUG] Rate limited, pausing quota requests for {}s",
+            duration.as_secs()
+        );
+    }
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Ok(json) = serde_json::to_string(&cooldown) {
+        let _ = super::cache::write_atomic(path, json.as_bytes());
+    }
+}
+
+/// 冷却期结束后删除文件，之后的渲染恢复正常请求
+fn remaining_at(path: &Path, api_key_hash: u64, now_ms: u64) -> Option<Duration> {
+    let content = fs::read_to_string(path).ok()?;
+    let cooldown = serde_json::from_str::<Cooldown>(&content).ok()?;
+    if cooldown.api_key_hash != api_key_hash {
+        return None;
+    }
+    if cooldown.until_ms <= now_ms {
+        let _ = fs::remove_file(path);
+        return None;
+    }
+    Some(Duration::from_millis(cooldown.until_ms - now_ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestEnv;
+
+    #[test]
+    fn cooldown_counts_down_and_clears_itself_once_elapsed() {
+        let env = TestEnv::new();
+        let path = env.home().join("quota_cooldown.json");
+        start_at(&path, 7, Duration::from_secs(60), 1_000);
+
+        assert_eq!(remaining_at(&path, 7, 1_000), Some(Duration::from_secs(60)));
+        assert_eq!(
+            remaining_at(&path, 7, 31_000),
+            Some(Duration::from_secs(30))
+        );
+        assert!(path.exists());
+
+        assert_eq!(remaining_at(&path, 7, 61_000), None);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn cooldown_only_applies_to_the_key_that_was_rate_limited() {
+        let env = TestEnv::new();
+        let path = env.home().join("quota_cooldown.json");
+        start_at(&path, 7, Duration::from_secs(60), 1_000);
+
+        assert_eq!(remaining_at(&path, 8, 1_000), None);
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn start_defaults_to_sixty_seconds_and_caps_retry_after() {
+        let _env = TestEnv::new();
+        assert_eq!(start(7, None), DEFAULT_COOLDOWN);
+        assert!(remaining(7).unwrap() <= DEFAULT_COOLDOWN);
+
+        assert_eq!(start(7, Some(Duration::from_secs(86_400))), MAX_COOLDOWN);
+        assert!(remaining(7).unwrap() > DEFAULT_COOLDOWN);
+
+        clear();
+        assert_eq!(remaining(7), None);
+    }
+
+    #[test]
+    fn unreadable_cooldown_file_is_ignored() {
+        let env = TestEnv::new();
+        let path = env.home().join("quota_cooldown.json");
+        fs::write(&path, "not json").unwrap();
+        assert_eq!(remaining_at(&path, 7, 0), None);
+    }
+}
@@ -0,0 +1,323 @@
+use super::api::{
+    BalanceApiResponse, DailyUsageApiResponse, HttpFetcher, HttpResponse, QuotaError,
+};
+use serde::de::DeserializeOwned;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// 从固定数据文件返回响应的 HttpFetcher，用于测试、演示和截图
+///
+/// 文件格式：`{ "daily_usage": <日用量响应>, "balance": <余额响应> }`。
+/// 值为 JSON 时按 `application/json` 原样返回；值为字符串时作为
+/// `text/html` 响应体返回（模拟代理错误页）；缺少的键返回 404。
+/// 可选的 `"status": { "balance": 503 }` 为对应端点指定状态码（默认 200）。
+#[derive(Debug, Clone)]
+pub struct FixtureFetcher {
+    daily_usage: Option<serde_json::Value>,
+    balance: Option<serde_json::Value>,
+    status: serde_json::Map<String, serde_json::Value>,
+}
+
+impl FixtureFetcher {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read fixture {}: {}", path.display(), e))?;
+        Self::from_json(&content).map_err(|e| format!("invalid fixture {}: {}", path.display(), e))
+    }
+
+    /// `YESCODE_QUOTA_FIXTURE` 指向的固定数据，已按真实响应结构校验；未设置时为 None
+    pub fn from_env() -> Option<Result<Self, String>> {
+        let path = env::var_os("YESCODE_QUOTA_FIXTURE").filter(|path| !path.is_empty())?;
+        let path = Path::new(&path);
+        Some(Self::from_file(path).and_then(|fixture| {
+            fixture
+                .validate()
+                .map(|()| fixture)
+                .map_err(|e| format!("invalid fixture {}: {}", path.display(), e))
+        }))
+    }
+
+    /// 以 200 返回的 JSON 负载必须能解析为真实接口使用的结构；
+    /// 字符串（模拟错误页）和指定了其他状态码的端点不做校验
+    pub fn validate(&self) -> Result<(), String> {
+        self.check::<DailyUsageApiResponse>("daily_usage", self.daily_usage.as_ref())?;
+        self.check::<BalanceApiResponse>("balance", self.balance.as_ref())
+    }
+
+    fn check<T: DeserializeOwned>(
+        &self,
+        key: &str,
+        payload: Option<&serde_json::Value>,
+    ) -> Result<(), String> {
+        let served_as_ok = self
+            .status
+            .get(key)
+            .and_then(|s| s.as_u64())
+            .is_none_or(|status| status == 200);
+        match payload {
+            Some(serde_json::Value::String(_)) | None => Ok(()),
+            Some(_) if !served_as_ok => Ok(()),
+            Some(value) => serde_json::from_value::<T>(value.clone())
+                .map(|_| ())
+                .map_err(|e| format!("{}: {}", key, e)),
+        }
+    }
+
+    pub fn from_json(content: &str) -> Result<Self, String> {
+        let mut fixture: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(content).map_err(|e| e.to_string())?;
+        let status = match fixture.remove("status") {
+            Some(serde_json::Value::Object(status)) => status,
+            _ => serde_json::Map::new(),
+        };
+        Ok(Self {
+            daily_usage: fixture.remove("daily_usage"),
+            balance: fixture.remove("balance"),
+            status,
+        })
+    }
+
+    fn response_for(&self, key: &str, payload: Option<&serde_json::Value>) -> HttpResponse {
+        let mut response = Self::payload_response(payload);
+        if let Some(status) = self.status.get(key).and_then(|s| s.as_u64()) {
+            response.status = status as u16;
+        }
+        response
+    }
+
+    fn payload_response(payload: Option<&serde_json::Value>) -> HttpResponse {
+        match payload {
+            Some(serde_json::Value::String(body)) => HttpResponse {
+                status: 200,
+                headers: vec![("content-type".to_string(), "text/html".to_string())],
+                body: body.clone(),
+            },
+            Some(value) => HttpResponse {
+                status: 200,
+                headers: vec![("content-type".to_string(), "application/json".to_string())],
+                body: value.to_string(),
+            },
+            None => HttpResponse {
+                status: 404,
+                headers: Vec::new(),
+                body: String::new(),
+            },
+        }
+    }
+}
+
+impl HttpFetcher for FixtureFetcher {
+    fn get(
+        &self,
+        url: &str,
+        _headers: &[(&str, &str)],
+        _timeout: Duration,
+    ) -> Result<HttpResponse, QuotaError> {
+        if url.contains("/usage/daily") {
+            Ok(self.response_for("daily_usage", self.daily_usage.as_ref()))
+        } else if url.contains("/balance") {
+            Ok(self.response_for("balance", self.balance.as_ref()))
+        } else {
+            Err(QuotaError::Network(format!("no fixture for {}", url)))
+        }
+    }
+}
+
+/// 测试用：读取 `fixtures/quota/<name>`，并把日用量的日期整体平移到最新一条为今天，
+/// 这样按日期选取今日花费的逻辑在任何一天都得到相同的结果
+#[cfg(test)]
+pub fn load(name: &str) -> FixtureFetcher {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join("quota")
+        .join(name);
+    let mut fixture = FixtureFetcher::from_file(&path).unwrap();
+    if let Some(entries) = fixture.daily_usage.as_mut().and_then(daily_entries_mut) {
+        shift_dates_to_today(entries);
+    }
+    fixture
+}
+
+#[cfg(test)]
+fn daily_entries_mut(daily_usage: &mut serde_json::Value) -> Option<&mut Vec<serde_json::Value>> {
+    match daily_usage {
+        serde_json::Value::Array(entries) => Some(entries),
+        serde_json::Value::Object(response) => response.get_mut("daily_usage")?.as_array_mut(),
+        _ => None,
+    }
+}
+
+/// 只改写日期部分，`T12:00:00Z` 之类的时间后缀保持不变
+#[cfg(test)]
+fn shift_dates_to_today(entries: &mut [serde_json::Value]) {
+    let date_of = |entry: &serde_json::Value| {
+        let date = entry.get("date")?.as_str()?.get(..10)?;
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+    };
+    let Some(newest) = entries.iter().filter_map(date_of).max() else {
+        return;
+    };
+    let offset = chrono::Local::now().date_naive() - newest;
+    for entry in entries.iter_mut() {
+        let Some(date) = date_of(entry) else {
+            continue;
+        };
+        let suffix = entry["date"].as_str().unwrap_or_default()[10..].to_string();
+        entry["date"] = format!("{}{}", (date + offset).format("%Y-%m-%d"), suffix).into();
+    }
+}
+
+/// 测试用的 HttpFetcher：按 URL 片段返回预设结果（用完后重复最后一个），并记录每次请求
+#[cfg(test)]
+#[derive(Debug, Clone, Default)]
+pub struct MockFetcher {
+    routes: std::sync::Arc<std::sync::Mutex<Vec<MockRoute>>>,
+    requests: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+#[cfg(test)]
+#[derive(Debug)]
+struct MockRoute {
+    fragment: String,
+    results: std::collections::VecDeque<Result<HttpResponse, QuotaError>>,
+}
+
+#[cfg(test)]
+impl MockFetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn route(self, fragment: &str, results: Vec<Result<HttpResponse, QuotaError>>) -> Self {
+        self.routes.lock().unwrap().push(MockRoute {
+            fragment: fragment.to_string(),
+            results: results.into(),
+        });
+        self
+    }
+
+    /// 所有请求过的 URL
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl HttpFetcher for MockFetcher {
+    fn get(
+        &self,
+        url: &str,
+        _headers: &[(&str, &str)],
+        _timeout: Duration,
+    ) -> Result<HttpResponse, QuotaError> {
+        self.requests.lock().unwrap().push(url.to_string());
+        let mut routes = self.routes.lock().unwrap();
+        let route = routes
+            .iter_mut()
+            .find(|route| url.contains(&route.fragment))
+            .ok_or_else(|| QuotaError::Network(format!("no mock route for {}", url)))?;
+        if route.results.len() > 1 {
+            route.results.pop_front().unwrap()
+        } else {
+            route
+                .results
+                .front()
+                .cloned()
+                .unwrap_or_else(|| Err(QuotaError::Network(format!("no mock result for {}", url))))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Local;
+
+    const FIXTURES: &[&str] = &[
+        "balance_down.json",
+        "bare_daily_array.json",
+        "daily_limit.json",
+        "daily_usage_down.json",
+        "empty_daily.json",
+        "extra_fields.json",
+        "gateway_error.json",
+        "huge_numbers.json",
+        "maintenance.json",
+        "missing_fields.json",
+        "negative_balance.json",
+        "negative_limit.json",
+        "typical.json",
+        "zero_limit.json",
+    ];
+
+    fn get(fixture: &FixtureFetcher, path: &str) -> HttpResponse {
+        fixture
+            .get(&format!("https://co.yes.vg{}", path), &[], Duration::ZERO)
+            .unwrap()
+    }
+
+    fn daily_usage(fixture: &FixtureFetcher) -> DailyUsageApiResponse {
+        serde_json::from_str(&get(fixture, "/api/v1/user/usage/daily").body).unwrap()
+    }
+
+    #[test]
+    fn every_fixture_validates_except_missing_fields() {
+        for name in FIXTURES {
+            let result = load(name).validate();
+            assert_eq!(result.is_ok(), *name != "missing_fields.json", "{}", name);
+        }
+    }
+
+    #[test]
+    fn newest_entry_is_moved_to_today() {
+        let today = Local::now().date_naive();
+        let dates: Vec<String> = daily_usage(&load("typical.json"))
+            .daily_usage
+            .into_iter()
+            .map(|usage| usage.date)
+            .collect();
+        let expected: Vec<String> = [0, 1, 2]
+            .iter()
+            .map(|days| {
+                (today - chrono::Duration::days(*days))
+                    .format("%Y-%m-%d")
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(dates, expected);
+    }
+
+    #[test]
+    fn time_suffixes_survive_the_shift() {
+        let today = Local::now().format("%Y-%m-%d").to_string();
+        let dates: Vec<String> = daily_usage(&load("bare_daily_array.json"))
+            .daily_usage
+            .into_iter()
+            .map(|usage| usage.date)
+            .collect();
+        assert_eq!(
+            dates,
+            [
+                format!("{}T00:00:00Z", today),
+                format!("{}T12:00:00Z", today)
+            ]
+        );
+    }
+
+    #[test]
+    fn routes_statuses_and_error_pages() {
+        let balance_down = load("balance_down.json");
+        assert_eq!(get(&balance_down, "/api/v1/user/balance").status, 500);
+        assert_eq!(get(&balance_down, "/api/v1/user/usage/daily").status, 200);
+
+        let gateway = load("gateway_error.json");
+        let page = get(&gateway, "/api/v1/user/balance");
+        assert_eq!(page.header("content-type"), Some("text/html"));
+
+        let unknown = load("typical.json").get("https://co.yes.vg/other", &[], Duration::ZERO);
+        assert!(unknown.is_err());
+    }
+}
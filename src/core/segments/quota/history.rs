@@ -0,0 +1,469 @@
+use super::api::DailyUsageApiResponse;
+use super::options::{ResetTimezone, WeekDefinition};
+use chrono::{DateTime, Datelike, Local, LocalResult, NaiveDate, TimeZone, Utc, Weekday};
+use std::collections::BTreeMap;
+
+/// 月末预测所需的最少已过天数
+const MIN_DAYS_FOR_PROJECTION: u32 = 3;
+
+/// 日均花费低于此值时视为没有花费，不估算剩余天数
+const MIN_SPEND_FOR_RUNWAY: f64 = 0.01;
+
+/// 估算剩余天数时的日均统计窗口
+pub const RUNWAY_WINDOW_DAYS: u32 = 7;
+
+/// 判断花费突增时的基准窗口（今天之前的天数）
+const SPIKE_BASELINE_DAYS: i64 = 7;
+
+/// 基准窗口内至少要有这么多天的记录才判断突增
+const MIN_DAYS_FOR_SPIKE: usize = 3;
+
+/// 按日期合并重复条目，跳过无法解析日期的条目
+pub fn coalesce_daily_usage(response: &DailyUsageApiResponse) -> BTreeMap<NaiveDate, f64> {
+    let mut days = BTreeMap::new();
+    for usage in &response.daily_usage {
+        if let Some(date) = parse_usage_date(&usage.date) {
+            *days.entry(date).or_insert(0.0) += usage.total_cost;
+        }
+    }
+    days
+}
+
+/// 花费合计；没有记录时为 0.0（`Iterator::sum` 对空的 f64 序列得到 -0.0，会显示成 `$-0.00`）
+fn total<'a>(costs: impl Iterator<Item = &'a f64>) -> f64 {
+    costs.fold(0.0, |sum, cost| sum + cost)
+}
+
+/// 最近 N 条记录（按日期倒序，而不是接口返回的顺序）的花费合计
+pub fn recent_entries_spend(days: &BTreeMap<NaiveDate, f64>, count: u32) -> f64 {
+    total(days.values().rev().take(count as usize))
+}
+
+/// 按周定义算出的本周已过天数（含今天）
+fn week_elapsed_days(today: NaiveDate, week: WeekDefinition) -> u32 {
+    match week {
+        WeekDefinition::Iso => today.weekday().num_days_from_monday() + 1,
+        WeekDefinition::Us => today.weekday().num_days_from_sunday() + 1,
+        WeekDefinition::Rolling => 7,
+    }
+}
+
+/// 本周截至今天（含）的花费
+pub fn week_to_date_spend(
+    days: &BTreeMap<NaiveDate, f64>,
+    today: NaiveDate,
+    week: WeekDefinition,
+) -> f64 {
+    trailing_days_spend(days, today, week_elapsed_days(today, week))
+}
+
+/// 本周日均花费，按已过天数而不是 7 天平均
+pub fn average_daily_spend_this_week(
+    days: &BTreeMap<NaiveDate, f64>,
+    today: NaiveDate,
+    week: WeekDefinition,
+) -> f64 {
+    week_to_date_spend(days, today, week) / week_elapsed_days(today, week) as f64
+}
+
+/// 截至今天（含）最近 N 天的花费
+pub fn trailing_days_spend(days: &BTreeMap<NaiveDate, f64>, today: NaiveDate, count: u32) -> f64 {
+    let start = today - chrono::Duration::days(count.saturating_sub(1) as i64);
+    total(days.range(start..=today).map(|(_, cost)| cost))
+}
+
+/// 今日花费与前 7 天日均的倍数；历史记录不足或前 7 天几乎没有花费时为 None
+pub fn spend_spike_ratio(days: &BTreeMap<NaiveDate, f64>, today: NaiveDate) -> Option<f64> {
+    let start = today - chrono::Duration::days(SPIKE_BASELINE_DAYS);
+    let yesterday = today.pred_opt()?;
+    let baseline_days: Vec<f64> = days
+        .range(start..=yesterday)
+        .map(|(_, cost)| *cost)
+        .collect();
+    if baseline_days.len() < MIN_DAYS_FOR_SPIKE {
+        return None;
+    }
+
+    let baseline = baseline_days.iter().sum::<f64>() / SPIKE_BASELINE_DAYS as f64;
+    if baseline < MIN_SPEND_FOR_RUNWAY {
+        return None;
+    }
+    Some(days.get(&today).copied().unwrap_or(0.0) / baseline)
+}
+
+/// 按日均花费估算余额还能用几天；余额耗尽为 0，几乎没有花费时为 None（无限）
+pub fn runway_days(balance: f64, average_daily_spend: f64) -> Option<f64> {
+    if balance <= 0.0 {
+        return Some(0.0);
+    }
+    if average_daily_spend < MIN_SPEND_FOR_RUNWAY {
+        return None;
+    }
+    Some(balance / average_daily_spend)
+}
+
+/// 本月 1 日至今的花费
+pub fn month_to_date_spend(days: &BTreeMap<NaiveDate, f64>, today: NaiveDate) -> f64 {
+    let month_start = today.with_day(1).unwrap_or(today);
+    total(days.range(month_start..=today).map(|(_, cost)| cost))
+}
+
+/// 按当月日均线性外推月末总花费；本月不足 3 天时数据太少，不做预测
+pub fn project_month_end(month_to_date: f64, today: NaiveDate) -> Option<f64> {
+    let elapsed_days = today.day();
+    if elapsed_days < MIN_DAYS_FOR_PROJECTION {
+        return None;
+    }
+    Some(month_to_date / elapsed_days as f64 * days_in_month(today) as f64)
+}
+
+/// 周末花费预测
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeeklyProjection {
+    pub amount: f64,
+    /// 最近 7 天记录不足 3 天，日均只能用今天的花费代替
+    pub low_confidence: bool,
+}
+
+/// 按最近 7 天日均线性外推到周额度重置：本周已用 + 日均 × 剩余天数（不含今天）
+///
+/// 剩余天数由 [`days_before_reset`] 从实际的重置时间得出，与 `resets in` 同源；
+/// 这里的周是计费周，不受 week_definition 影响
+pub fn project_week_end(
+    days: &BTreeMap<NaiveDate, f64>,
+    today: NaiveDate,
+    weekly_spent: f64,
+    days_remaining: u32,
+) -> WeeklyProjection {
+    let window = RUNWAY_WINDOW_DAYS;
+    let start = today - chrono::Duration::days(window as i64 - 1);
+    let recorded_days = days.range(start..=today).count();
+    let low_confidence = recorded_days < MIN_DAYS_FOR_PROJECTION as usize;
+    let average = if low_confidence {
+        days.get(&today).copied().unwrap_or(0.0)
+    } else {
+        trailing_days_spend(days, today, window) / window as f64
+    };
+
+    WeeklyProjection {
+        amount: weekly_spent + average * days_remaining as f64,
+        low_confidence,
+    }
+}
+
+/// 今天之后、周额度重置当天之前还有几天（0 到 6）
+pub fn days_before_reset(today: NaiveDate, reset: DateTime<Utc>) -> u32 {
+    let reset_day = reset.with_timezone(&Local).date_naive();
+    ((reset_day - today).num_days() - 1).clamp(0, 6) as u32
+}
+
+/// `date` 是本周（从 `week_start` 起算）的第几天，0 表示 `week_start` 当天
+pub fn days_since_week_start(date: NaiveDate, week_start: Weekday) -> u32 {
+    (date.weekday().num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7
+}
+
+/// 推算的下次周额度重置：`week_start` 那天的 00:00，按 `zone` 的日期和时刻计算。
+/// 今天就是 `week_start` 时，今天 00:00 已经过去，重置在 7 天后
+pub fn next_weekly_reset(
+    now: DateTime<Utc>,
+    week_start: Weekday,
+    zone: ResetTimezone,
+) -> Option<DateTime<Utc>> {
+    match zone {
+        ResetTimezone::Local => next_midnight_on(&Local, now, week_start),
+        ResetTimezone::Utc => next_midnight_on(&Utc, now, week_start),
+    }
+}
+
+fn next_midnight_on<Tz: TimeZone>(
+    zone: &Tz,
+    now: DateTime<Utc>,
+    week_start: Weekday,
+) -> Option<DateTime<Utc>> {
+    let today = now.with_timezone(zone).date_naive();
+    let days_until = 7 - days_since_week_start(today, week_start) as i64;
+    let midnight = (today + chrono::Duration::days(days_until)).and_hms_opt(0, 0, 0)?;
+    // 夏令时切换：00:00 出现两次时取较早的一次；被跳过时取跳变后的 01:00
+    let reset = match zone.from_local_datetime(&midnight) {
+        LocalResult::Single(reset) | LocalResult::Ambiguous(reset, _) => reset,
+        LocalResult::None => zone
+            .from_local_datetime(&(midnight + chrono::Duration::hours(1)))
+            .earliest()?,
+    };
+    Some(reset.with_timezone(&Utc))
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|next_month| next_month.pred_opt())
+        .map(|last_day| last_day.day())
+        .unwrap_or(30)
+}
+
+/// 解析 `YYYY-MM-DD` 或以其开头的时间戳
+fn parse_usage_date(date: &str) -> Option<NaiveDate> {
+    let day = date.get(..10)?;
+    NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn days(entries: &[(&str, f64)]) -> BTreeMap<NaiveDate, f64> {
+        entries.iter().map(|(d, cost)| (date(d), *cost)).collect()
+    }
+
+    #[test]
+    fn month_to_date_counts_only_this_month() {
+        let days = days(&[
+            ("2026-09-30", 100.0),
+            ("2026-10-01", 10.0),
+            ("2026-10-05", 20.0),
+            ("2026-10-15", 5.0),
+        ]);
+        assert_eq!(month_to_date_spend(&days, date("2026-10-10")), 30.0);
+    }
+
+    #[test]
+    fn month_projection_needs_three_days() {
+        assert_eq!(project_month_end(10.0, date("2026-10-01")), None);
+        assert_eq!(project_month_end(10.0, date("2026-10-02")), None);
+        assert_eq!(project_month_end(30.0, date("2026-10-03")), Some(310.0));
+    }
+
+    #[test]
+    fn month_projection_scales_by_month_length() {
+        // $140 over 14 days = $10/day
+        assert_eq!(project_month_end(140.0, date("2026-10-14")), Some(310.0));
+        assert_eq!(project_month_end(140.0, date("2026-11-14")), Some(300.0));
+        assert_eq!(project_month_end(140.0, date("2026-02-14")), Some(280.0));
+        assert_eq!(project_month_end(140.0, date("2028-02-14")), Some(290.0));
+        assert_eq!(project_month_end(140.0, date("2026-12-14")), Some(310.0));
+    }
+
+    #[test]
+    fn runway_divides_the_balance_by_daily_spend() {
+        assert_eq!(runway_days(90.0, 10.0), Some(9.0));
+        assert_eq!(runway_days(5.0, 20.0), Some(0.25));
+    }
+
+    #[test]
+    fn runway_is_zero_once_the_balance_is_gone() {
+        assert_eq!(runway_days(0.0, 10.0), Some(0.0));
+        assert_eq!(runway_days(-5.0, 0.0), Some(0.0));
+    }
+
+    #[test]
+    fn runway_is_unbounded_without_spend() {
+        assert_eq!(runway_days(50.0, 0.0), None);
+        assert_eq!(runway_days(50.0, 0.005), None);
+    }
+
+    #[test]
+    fn trailing_window_includes_today() {
+        let days = days(&[
+            ("2026-10-07", 100.0),
+            ("2026-10-08", 7.0),
+            ("2026-10-14", 7.0),
+            ("2026-10-15", 50.0),
+        ]);
+        assert_eq!(trailing_days_spend(&days, date("2026-10-14"), 7), 14.0);
+        assert_eq!(trailing_days_spend(&days, date("2026-10-14"), 1), 7.0);
+    }
+
+    #[test]
+    fn spike_ratio_compares_today_with_the_previous_week() {
+        let days = days(&[
+            ("2026-10-01", 50.0),
+            ("2026-10-07", 2.0),
+            ("2026-10-09", 3.0),
+            ("2026-10-13", 2.0),
+            ("2026-10-14", 7.0),
+        ]);
+        // 前 7 天（10-07 至 10-13）共 $7，日均 $1；10-01 在窗口之外
+        assert_eq!(spend_spike_ratio(&days, date("2026-10-14")), Some(7.0));
+    }
+
+    #[test]
+    fn spike_ratio_needs_enough_history() {
+        let days = days(&[
+            ("2026-10-12", 2.0),
+            ("2026-10-13", 2.0),
+            ("2026-10-14", 40.0),
+        ]);
+        assert_eq!(spend_spike_ratio(&days, date("2026-10-14")), None);
+    }
+
+    #[test]
+    fn spike_ratio_ignores_a_near_zero_baseline() {
+        let days = days(&[
+            ("2026-10-11", 0.0),
+            ("2026-10-12", 0.0),
+            ("2026-10-13", 0.0),
+            ("2026-10-14", 5.0),
+        ]);
+        assert_eq!(spend_spike_ratio(&days, date("2026-10-14")), None);
+    }
+
+    /// 2026-10-07（周三）至 2026-10-14（周三），每天依次花费 $1 到 $8
+    fn two_weeks() -> BTreeMap<NaiveDate, f64> {
+        (0..8)
+            .map(|offset| {
+                (
+                    date("2026-10-07") + chrono::Duration::days(offset),
+                    (offset + 1) as f64,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn week_to_date_per_definition() {
+        let days = two_weeks();
+        let today = date("2026-10-14");
+        // 最近 7 天：10-08 至 10-14
+        assert_eq!(
+            week_to_date_spend(&days, today, WeekDefinition::Rolling),
+            35.0
+        );
+        // 周一 10-12 起
+        assert_eq!(week_to_date_spend(&days, today, WeekDefinition::Iso), 21.0);
+        // 周日 10-11 起
+        assert_eq!(week_to_date_spend(&days, today, WeekDefinition::Us), 26.0);
+    }
+
+    #[test]
+    fn weekly_average_divides_by_elapsed_days() {
+        let days = two_weeks();
+        let today = date("2026-10-14");
+        assert_eq!(
+            average_daily_spend_this_week(&days, today, WeekDefinition::Rolling),
+            5.0
+        );
+        assert_eq!(
+            average_daily_spend_this_week(&days, today, WeekDefinition::Iso),
+            7.0
+        );
+        assert_eq!(
+            average_daily_spend_this_week(&days, today, WeekDefinition::Us),
+            6.5
+        );
+    }
+
+    #[test]
+    fn a_new_week_starts_with_only_today() {
+        let days = two_weeks();
+        // 10-12 是周一，10-11 是周日
+        assert_eq!(
+            week_to_date_spend(&days, date("2026-10-12"), WeekDefinition::Iso),
+            6.0
+        );
+        assert_eq!(
+            week_to_date_spend(&days, date("2026-10-11"), WeekDefinition::Us),
+            5.0
+        );
+        assert_eq!(
+            week_to_date_spend(&days, date("2026-10-11"), WeekDefinition::Iso),
+            15.0
+        );
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn week_end_projection_counts_only_the_days_before_the_reset() {
+        let days = two_weeks();
+        let today = date("2026-10-14");
+        // 最近 7 天日均 5.0
+        let projection = project_week_end(&days, today, 20.0, 2);
+        assert_eq!(projection.amount, 30.0);
+        assert!(!projection.low_confidence);
+        assert_eq!(project_week_end(&days, today, 20.0, 0).amount, 20.0);
+    }
+
+    #[test]
+    fn days_before_reset_follows_the_local_reset_day() {
+        let today = date("2026-10-14");
+        let local_midnight = |d: &str| {
+            Local
+                .from_local_datetime(&date(d).and_hms_opt(0, 0, 0).unwrap())
+                .earliest()
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        assert_eq!(days_before_reset(today, local_midnight("2026-10-15")), 0);
+        assert_eq!(days_before_reset(today, local_midnight("2026-10-19")), 4);
+        assert_eq!(days_before_reset(today, local_midnight("2026-10-21")), 6);
+        // 异常的远期重置时间也不超过一周
+        assert_eq!(days_before_reset(today, local_midnight("2026-11-30")), 6);
+    }
+
+    #[test]
+    fn days_since_week_start_wraps_around_the_week() {
+        // 2026-10-14 是星期三
+        let wednesday = date("2026-10-14");
+        assert_eq!(days_since_week_start(wednesday, Weekday::Mon), 2);
+        assert_eq!(days_since_week_start(wednesday, Weekday::Wed), 0);
+        assert_eq!(days_since_week_start(wednesday, Weekday::Thu), 6);
+        assert_eq!(days_since_week_start(wednesday, Weekday::Sun), 3);
+    }
+
+    #[test]
+    fn next_reset_is_the_coming_week_start_at_midnight() {
+        let reset = next_weekly_reset(
+            utc("2026-10-14T15:30:00Z"),
+            Weekday::Mon,
+            ResetTimezone::Utc,
+        );
+        assert_eq!(reset, Some(utc("2026-10-19T00:00:00Z")));
+
+        let reset = next_weekly_reset(
+            utc("2026-10-14T15:30:00Z"),
+            Weekday::Thu,
+            ResetTimezone::Utc,
+        );
+        assert_eq!(reset, Some(utc("2026-10-15T00:00:00Z")));
+    }
+
+    #[test]
+    fn next_reset_on_the_week_start_itself_is_a_week_away() {
+        let reset = next_weekly_reset(
+            utc("2026-10-19T00:00:00Z"),
+            Weekday::Mon,
+            ResetTimezone::Utc,
+        );
+        assert_eq!(reset, Some(utc("2026-10-26T00:00:00Z")));
+
+        let reset = next_weekly_reset(
+            utc("2026-10-18T23:59:59Z"),
+            Weekday::Mon,
+            ResetTimezone::Utc,
+        );
+        assert_eq!(reset, Some(utc("2026-10-19T00:00:00Z")));
+    }
+
+    #[test]
+    fn empty_history_sums_to_positive_zero() {
+        let days = BTreeMap::new();
+        let today = date("2026-10-14");
+        for spend in [
+            recent_entries_spend(&days, 1),
+            trailing_days_spend(&days, today, 7),
+            month_to_date_spend(&days, today),
+        ] {
+            assert!(spend == 0.0 && spend.is_sign_positive(), "{}", spend);
+        }
+    }
+}
@@ -0,0 +1,2969 @@
+mod age;
+mod api;
+mod bell;
+mod cache;
+mod check;
+mod cooldown;
+mod fixture;
+mod history;
+mod money;
+mod options;
+mod placeholder;
+#[cfg(any(feature = "http-ureq", feature = "http-reqwest"))]
+mod proxy;
+mod rate_limit;
+mod refresh;
+mod severity;
+mod summary;
+#[cfg(any(feature = "http-ureq", feature = "http-reqwest"))]
+mod tls;
+
+use super::{Segment, SegmentData};
+use crate::config::{InputData, SegmentId};
+use api::{
+    BalanceApiResponse, BaseUrlSources, DailyUsageApiResponse, RequestOptions,
+    SmartEndpointDetector,
+};
+use cache::{QuotaCache, QuotaSnapshot};
+use chrono::{DateTime, Local, Utc};
+use money::MoneyFormat;
+use options::{
+    Account, BarGlyphs, DailyDenominator, DisplayMode, OfflineDisplay, PlaceholderStyle,
+    QuotaField, QuotaOptions, QuotaStyle, RefreshMode, WeeklyStyle,
+};
+use serde::Serialize;
+use severity::{Severity, Thresholds};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+
+#[cfg(feature = "quota")]
+pub use api::default_fetcher;
+#[cfg(feature = "http-reqwest")]
+pub use api::ReqwestFetcher;
+#[cfg(feature = "http-ureq")]
+pub use api::UreqFetcher;
+pub use api::{HttpFetcher, HttpResponse, QuotaError};
+pub use fixture::FixtureFetcher;
+
+/// 一次获取的结果（成功的快照、要使用的缓存或失败原因），与段的显示选项无关
+#[derive(Debug, Clone)]
+pub struct QuotaFetch(FetchOutcome);
+
+#[derive(Debug, Clone)]
+enum FetchOutcome {
+    /// 找不到 API key
+    NoKey,
+    /// YESCODE_QUOTA_FIXTURE 无法读取
+    FixtureError(String),
+    /// 不发请求，使用缓存并以 status 标记原因
+    Cached {
+        api_key_hash: u64,
+        status: &'static str,
+    },
+    /// 429 冷却期内
+    CoolingDown {
+        api_key_hash: u64,
+        remaining: std::time::Duration,
+    },
+    /// 获取成功；fixture 数据不是实时的（live 为 false），不触发提示音。
+    /// 只有日用量接口失败时 daily_error 给出原因
+    Fetched {
+        snapshot: QuotaSnapshot,
+        live: bool,
+        daily_error: Option<QuotaError>,
+    },
+    /// 余额接口失败；fixture 模式没有缓存可回退（api_key_hash 为 None）
+    Failed {
+        api_key_hash: Option<u64>,
+        error: QuotaError,
+        daily_usage: Option<DailyUsageApiResponse>,
+        cooldown: Option<std::time::Duration>,
+    },
+}
+
+pub struct QuotaSegment {
+    options: QuotaOptions,
+    #[cfg(feature = "quota")]
+    fetcher: Box<dyn HttpFetcher>,
+    #[cfg(feature = "quota")]
+    spawner: refresh::Spawner,
+    session_cost: Option<f64>,
+    enabled: bool,
+}
+
+impl Default for QuotaSegment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QuotaSegment {
+    pub fn new() -> Self {
+        Self {
+            options: QuotaOptions::default(),
+            #[cfg(feature = "quota")]
+            fetcher: default_fetcher(),
+            #[cfg(feature = "quota")]
+            spawner: Box::new(refresh::spawn_refresh_process),
+            session_cost: None,
+            enabled: true,
+        }
+    }
+
+    pub fn with_options(mut self, options: &HashMap<String, serde_json::Value>) -> Self {
+        self.options = QuotaOptions::from_options(options);
+        self
+    }
+
+    /// 把会话花费作为后缀并入第一块，例如 `$12.34/$50.00 (sess $0.43)`
+    pub fn with_session_cost(mut self, session_cost: Option<f64>) -> Self {
+        self.session_cost = session_cost;
+        self
+    }
+
+    /// 段配置中的 `enabled`；为 false 时 collect 直接返回 None，不发任何请求
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// 是否需要收集数据：编译时启用了 quota 特性、配置未关闭，且未设置 YESCODE_QUOTA_DISABLED。
+    /// 任何一处关闭都生效，环境变量设为 0 也不会重新打开配置中关闭的段
+    pub fn is_enabled(&self) -> bool {
+        if !cfg!(feature = "quota") || !self.enabled {
+            return false;
+        }
+        let disabled_by_env = matches!(
+            env::var("YESCODE_QUOTA_DISABLED").as_deref(),
+            Ok("1") | Ok("true") | Ok("yes")
+        );
+        if disabled_by_env && env::var("YESCODE_DEBUG").is_ok() {
+            eprintln!("[DEBUG] Quota segment disabled by YESCODE_QUOTA_DISABLED");
+        }
+        !disabled_by_env
+    }
+
+    /// 替换 HTTP 传输层（例如使用固定响应的测试实现）
+    #[cfg(feature = "quota")]
+    pub fn with_fetcher(mut self, fetcher: Box<dyn HttpFetcher>) -> Self {
+        self.fetcher = fetcher;
+        self
+    }
+
+    /// 替换 `refresh = "background"` 启动刷新进程的方式
+    #[cfg(feature = "quota")]
+    pub fn with_spawner(mut self, spawner: refresh::Spawner) -> Self {
+        self.spawner = spawner;
+        self
+    }
+
+    fn load_api_key(&self) -> Option<String> {
+        let (source, key) = self.find_api_key()?;
+        if env::var("YESCODE_DEBUG").is_ok() {
+            eprintln!("[DEBUG] Using API key from {}", source);
+        }
+        Some(key)
+    }
+
+    /// 按优先级返回第一个有效的 key 及其来源；无效的 key 跳过并继续查找下一个来源
+    fn find_api_key(&self) -> Option<(String, String)> {
+        let accept = |source: String, raw: Option<String>| {
+            let raw = raw?;
+            match valid_api_key(&raw) {
+                Some(key) => Some((source, key)),
+                None => {
+                    if env::var("YESCODE_DEBUG").is_ok() {
+                        eprintln!("[DEBUG] Ignoring invalid API key from {}", source);
+                    }
+                    None
+                }
+            }
+        };
+
+        // 配置了账户列表时优先用当前账户的 key
+        if let Some(account) = self.active_account() {
+            let source = format!("account \"{}\"", account.name);
+            if let Some(found) = accept(source, Some(account.key.clone())) {
+                return Some(found);
+            }
+        }
+
+        // 优先级：环境变量 > Claude Code settings.json > YES.code CLI 配置 > api_key 文件
+        let sources: [(&str, &dyn Fn() -> Option<String>); 7] = [
+            // 1. 环境变量
+            ("YESCODE_API_KEY", &|| env::var("YESCODE_API_KEY").ok()),
+            ("ANTHROPIC_API_KEY", &|| env::var("ANTHROPIC_API_KEY").ok()),
+            ("ANTHROPIC_AUTH_TOKEN", &|| {
+                env::var("ANTHROPIC_AUTH_TOKEN").ok()
+            }),
+            // 2. Claude Code settings.json
+            ("settings.json ANTHROPIC_AUTH_TOKEN", &|| {
+                Self::settings_env("ANTHROPIC_AUTH_TOKEN")
+            }),
+            ("settings.json ANTHROPIC_API_KEY", &|| {
+                Self::settings_env("ANTHROPIC_API_KEY")
+            }),
+            // 3. YES.code CLI 自己的凭据文件
+            ("YES.code CLI config", &Self::load_from_yescode_config),
+            // 4. api_key 文件
+            ("~/.claude/api_key", &|| {
+                let api_key_path = dirs::home_dir()?.join(".claude").join("api_key");
+                fs::read_to_string(api_key_path).ok()
+            }),
+        ];
+        sources
+            .iter()
+            .find_map(|(source, load)| accept(source.to_string(), load()))
+    }
+
+    /// 当前账户：YESCODE_ACCOUNT 指定的名称，未设置或找不到时为第一个
+    fn active_account(&self) -> Option<&Account> {
+        let accounts = &self.options.accounts;
+        let requested = env::var("YESCODE_ACCOUNT")
+            .ok()
+            .filter(|name| !name.is_empty());
+        if let Some(name) = requested {
+            if let Some(account) = accounts.iter().find(|account| account.name == name) {
+                return Some(account);
+            }
+            if !accounts.is_empty() && env::var("YESCODE_DEBUG").is_ok() {
+                eprintln!(
+                    "[DEBUG] YESCODE_ACCOUNT={} not found in accounts, using the first",
+                    name
+                );
+            }
+        }
+        accounts.first()
+    }
+
+    /// 写入 metadata 的 account，按需给第一块加上 `[name] ` 前缀
+    fn with_account(&self, mut data: SegmentData) -> SegmentData {
+        let Some(account) = self.active_account() else {
+            return data;
+        };
+        if self.options.show_account
+            && self.options.quota_style != QuotaStyle::Dot
+            && !data.primary.is_empty()
+        {
+            data.primary = format!("[{}] {}", account.name, data.primary);
+        }
+        data.metadata
+            .insert("account".to_string(), account.name.clone());
+        data
+    }
+
+    /// YES.code CLI 的凭据：`~/.yescode/config.json` 或 `~/.config/yescode/auth.json` 中的
+    /// `api_key` / `token`；文件存在但没有非空的 key 时继续查找下一个来源
+    fn load_from_yescode_config() -> Option<String> {
+        let home = dirs::home_dir()?;
+        [
+            home.join(".yescode").join("config.json"),
+            home.join(".config").join("yescode").join("auth.json"),
+        ]
+        .iter()
+        .find_map(|path| {
+            let content = fs::read_to_string(path).ok()?;
+            let config = serde_json::from_str::<serde_json::Value>(&content).ok()?;
+            ["api_key", "token"].iter().find_map(|field| {
+                let key = config.get(field)?.as_str()?.trim();
+                (!key.is_empty()).then(|| key.to_string())
+            })
+        })
+    }
+
+    /// 读取 Claude Code settings.json 中 env 块的字符串值
+    fn settings_env(name: &str) -> Option<String> {
+        let settings_path = dirs::home_dir()?.join(".claude").join("settings.json");
+        let content = fs::read_to_string(settings_path).ok()?;
+        let settings = serde_json::from_str::<serde_json::Value>(&content).ok()?;
+        settings.get("env")?.get(name)?.as_str().map(String::from)
+    }
+
+    /// 只缓存自动选择的端点；显式配置的地址每次都重新读取，去掉配置后立即恢复默认地址
+    fn record_endpoint(&self, api_key_hash: u64, base_url: &str) {
+        if self.base_url_sources().explicit().is_none() {
+            SmartEndpointDetector::record_success(api_key_hash, base_url);
+        }
+    }
+
+    fn base_url_sources(&self) -> BaseUrlSources {
+        BaseUrlSources {
+            config: self.options.base_url.clone(),
+            yescode_env: env::var("YESCODE_BASE_URL").ok(),
+            anthropic_env: env::var("ANTHROPIC_BASE_URL")
+                .ok()
+                .or_else(|| Self::settings_env("ANTHROPIC_BASE_URL")),
+        }
+    }
+
+    fn format_daily_used_total(
+        &self,
+        daily_used: Option<f64>,
+        total: f64,
+        currency: &str,
+    ) -> String {
+        let daily_used = self.money_or_unknown(daily_used, 2, currency);
+        if total < 0.0 {
+            // 欠费：用真正的减号并标注 due，避免出现 `$-5.00`
+            format!(
+                "{}/\u{2212}{} (due)",
+                daily_used,
+                self.money(-total, 2, currency)
+            )
+        } else {
+            format!("{}/{}", daily_used, self.money(total, 2, currency))
+        }
+    }
+
+    /// 按 display_mode 显示周额度；没有周限制时无法算百分比，回退到金额
+    fn format_week_limit(&self, weekly_used: f64, limit: f64, currency: &str) -> String {
+        // 周限制为 0 或负数表示不限额：只显示已用金额，不显示分母
+        if limit <= 0.0 {
+            return format!("Week: {}", self.money(weekly_used, 2, currency));
+        }
+        let percent = weekly_used / limit * 100.0;
+        match self.options.display_mode {
+            DisplayMode::Percent => format!("Week: {:.0}%", percent),
+            DisplayMode::Both => format!(
+                "Week: {} ({:.0}%)",
+                self.money(weekly_used, 2, currency),
+                percent
+            ),
+            DisplayMode::Absolute => format!(
+                "Week: {}/{}",
+                self.money(weekly_used, 2, currency),
+                self.money(limit, 0, currency)
+            ),
+        }
+    }
+
+    /// `Week ▓▓▓▓░░░░ 52%`；超额时显示满格，百分比仍为实际值。调用方保证 limit > 0
+    fn format_week_bar(&self, weekly_used: f64, limit: f64) -> String {
+        let width = self.options.bar_width.max(1);
+        let limit = limit.max(f64::EPSILON);
+        let ratio = (weekly_used / limit).clamp(0.0, 1.0);
+        let filled = ((ratio * width as f64).round() as usize).min(width);
+        let (filled_glyph, empty_glyph) = match self.options.bar_glyphs {
+            BarGlyphs::Unicode => ("\u{2593}", "\u{2591}"),
+            BarGlyphs::Ascii => ("#", "-"),
+        };
+        format!(
+            "Week {}{} {:.0}%",
+            filled_glyph.repeat(filled),
+            empty_glyph.repeat(width - filled),
+            weekly_used / limit * 100.0
+        )
+    }
+
+    /// `72% wk`；没有周限制时为 `no wk limit`
+    fn format_week_percent(&self, weekly_used: f64, limit: f64) -> String {
+        if limit <= 0.0 {
+            return "no wk limit".to_string();
+        }
+        format!("{:.0}% wk", weekly_used / limit * 100.0)
+    }
+
+    fn format_day_limit(&self, daily_used: Option<f64>, limit: f64, currency: &str) -> String {
+        format!(
+            "Day: {}/{}",
+            self.money_or_unknown(daily_used, 2, currency),
+            self.money(limit, 0, currency)
+        )
+    }
+
+    fn format_daily_average(&self, average: f64, currency: &str) -> String {
+        format!("avg {}/d", self.money(average, 2, currency))
+    }
+
+    fn format_month_projection(&self, projection: f64, currency: &str) -> String {
+        format!("proj {}", self.money(projection, 0, currency))
+    }
+
+    /// `~9d left`；几乎没有花费时显示 `∞ left`
+    fn format_runway(&self, runway_days: Option<f64>) -> String {
+        match runway_days {
+            Some(days) => format!("~{:.0}d left", days.floor()),
+            None => "\u{221e} left".to_string(),
+        }
+    }
+
+    fn format_lookback_total(&self, count: u32, total: f64, currency: &str) -> String {
+        format!("{}d {}", count, self.money(total, 2, currency))
+    }
+
+    /// 第一块的分母；所选来源在响应中缺失（为 0）时回退到总余额
+    fn daily_denominator(&self, balance: &BalanceApiResponse) -> (DailyDenominator, f64) {
+        let source = self.options.daily_denominator;
+        let value = match source {
+            DailyDenominator::TotalBalance => return (source, balance.total_balance),
+            DailyDenominator::WeeklyLimit => balance.weekly_limit,
+            DailyDenominator::SubscriptionBalance => balance.subscription_balance,
+        };
+
+        if value > 0.0 {
+            (source, value)
+        } else {
+            if env::var("YESCODE_DEBUG").is_ok() {
+                eprintln!(
+                    "[DEBUG] Response has no {}, using total_balance as daily denominator",
+                    source.as_str()
+                );
+            }
+            (DailyDenominator::TotalBalance, balance.total_balance)
+        }
+    }
+
+    /// 有效的个人周目标（未设置或非正数时为 None）
+    fn weekly_goal(&self) -> Option<f64> {
+        self.options.weekly_goal.filter(|goal| *goal > 0.0)
+    }
+
+    /// 下次周额度重置及其来源：接口给出且仍在未来的 weekly_reset_at（`api`）优先，
+    /// 否则按 week_start_day 与 reset_timezone 推算（`computed`）
+    fn weekly_reset(
+        &self,
+        balance: &BalanceApiResponse,
+        now: DateTime<Utc>,
+    ) -> Option<(DateTime<Utc>, &'static str)> {
+        let from_api = balance
+            .weekly_reset_at
+            .as_deref()
+            .and_then(|at| DateTime::parse_from_rfc3339(at.trim()).ok())
+            .map(|at| at.with_timezone(&Utc))
+            .filter(|at| *at > now);
+        match from_api {
+            Some(reset) => Some((reset, "api")),
+            None => history::next_weekly_reset(
+                now,
+                self.options.week_start_day,
+                self.options.reset_timezone,
+            )
+            .map(|reset| (reset, "computed")),
+        }
+    }
+
+    /// 周比例、进度条和着色对照的金额：个人目标优先，否则为账户周限制
+    fn weekly_target(&self, weekly_limit: f64) -> f64 {
+        self.weekly_goal().unwrap_or(weekly_limit)
+    }
+
+    /// 段内所有金额都经过这里：补齐宽度、符号位置和千位分隔
+    fn money(&self, amount: f64, decimals: usize, currency: &str) -> String {
+        money::format_money_with(amount, decimals, currency, self.money_format())
+    }
+
+    /// 数据缺失时显示 `$?.??`，而不是当作 0
+    fn money_or_unknown(&self, amount: Option<f64>, decimals: usize, currency: &str) -> String {
+        match amount {
+            Some(amount) => self.money(amount, decimals, currency),
+            None => money::format_unknown_money(decimals, currency, self.money_format()),
+        }
+    }
+
+    fn money_format(&self) -> MoneyFormat {
+        MoneyFormat {
+            width: self.options.money_field_width,
+            position: self.options.symbol_position,
+            thousands_separator: self.options.thousands_separator,
+        }
+    }
+
+    /// 本次显示使用的货币符号；配置了 currency_symbol 时直接使用
+    fn currency_for(&self, api_currency: Option<&str>) -> String {
+        if let Some(symbol) = self
+            .options
+            .currency_symbol
+            .as_ref()
+            .filter(|s| !s.is_empty())
+        {
+            return symbol.clone();
+        }
+        money::resolve_currency(api_currency, self.options.currency.as_deref())
+    }
+
+    /// `[██████░░] $37 left`，按剩余比例着色；没有周限制时只显示剩余余额
+    fn format_remaining_bar(
+        &self,
+        spent: f64,
+        limit: f64,
+        balance: f64,
+        currency: &str,
+    ) -> (String, &'static str) {
+        if limit <= 0.0 {
+            return (
+                format!("{} left", self.money(balance.max(0.0), 0, currency)),
+                "green",
+            );
+        }
+
+        let remaining = (limit - spent).max(0.0);
+        let ratio = (remaining / limit).clamp(0.0, 1.0);
+        let filled =
+            ((ratio * REMAINING_BAR_WIDTH as f64).round() as usize).min(REMAINING_BAR_WIDTH);
+        let color = if ratio > 0.5 {
+            "green"
+        } else if ratio > 0.2 {
+            "yellow"
+        } else {
+            "red"
+        };
+
+        (
+            format!(
+                "[{}{}] {} left",
+                "\u{2588}".repeat(filled),
+                "\u{2591}".repeat(REMAINING_BAR_WIDTH - filled),
+                self.money(remaining, 0, currency)
+            ),
+            color,
+        )
+    }
+
+    /// `$37 left / $50`：总余额减去今日花费，透支时剩余按 0 显示
+    fn format_remaining_and_total(
+        &self,
+        today_cost: Option<f64>,
+        total: f64,
+        currency: &str,
+    ) -> String {
+        let remaining = today_cost.map(|cost| (total - cost).max(0.0));
+        format!(
+            "{} left / {}",
+            self.money_or_unknown(remaining, 0, currency),
+            self.money(total, 0, currency)
+        )
+    }
+
+    /// 今日花费：日期最新的一天（同一天的多条记录合并），与 `lookback_days = 1` 一致。
+    /// 不和本地日期比较，接口按 UTC 或服务器时区记日期时也不会在半天里显示 $0.00
+    fn get_today_cost(&self, response: &DailyUsageApiResponse) -> f64 {
+        history::recent_entries_spend(&history::coalesce_daily_usage(response), 1)
+    }
+}
+
+/// 单字符模式使用的圆点
+const STATUS_DOT: &str = "\u{25cf}";
+
+/// 今日花费突增时追加在第一块后的标记
+const SPIKE_MARKER: &str = "\u{26a1}";
+
+/// 余额低于 balance_alert_threshold 时第一块的前缀
+const LOW_BALANCE_MARKER: &str = "\u{26a0}";
+
+/// 维护说明在 secondary 中最多显示的字符数
+const MAINTENANCE_MESSAGE_WIDTH: usize = 40;
+
+/// 剩余额度进度条宽度（字符数）
+const REMAINING_BAR_WIDTH: usize = 8;
+
+impl QuotaSegment {
+    fn offline_segment_data(&self) -> SegmentData {
+        let mut metadata = BTreeMap::new();
+        metadata.insert("status".to_string(), "offline".to_string());
+
+        // 离线图标由渲染器按样式模式解析（Plain 模式为 ASCII）
+        let (primary, secondary) = match self.options.offline_display {
+            OfflineDisplay::Text => ("Offline", "Offline"),
+            OfflineDisplay::Icon => ("", ""),
+            OfflineDisplay::Both => ("Offline", ""),
+        };
+        if self.options.offline_display != OfflineDisplay::Text {
+            metadata.insert("icon_state".to_string(), "offline".to_string());
+        }
+
+        let data = SegmentData {
+            primary: primary.to_string(),
+            secondary: secondary.to_string(),
+            metadata,
+        };
+        self.apply_quota_style(data, "gray")
+    }
+
+    /// 不发起请求或请求失败时的显示：有缓存用缓存（status 标明原因），否则显示占位符
+    fn cached_segment_data(&self, api_key_hash: u64, status: &str) -> SegmentData {
+        match QuotaCache::load_for(api_key_hash, self.options.cache_key) {
+            Some(cache) => {
+                let age = age::cache_age(&cache.cached_at, chrono::Utc::now());
+                let mut data = self.with_data_age(self.build_segment_data(&cache.snapshot), age);
+                data.metadata
+                    .insert("status".to_string(), status.to_string());
+                if let Some(age) = age {
+                    data.metadata
+                        .insert("cached_age".to_string(), age::format_age(age));
+                }
+                data
+            }
+            None => self.placeholder_segment_data(),
+        }
+    }
+
+    /// 429 冷却期内：显示缓存数据，`retry_in` 给出恢复请求前的倒计时
+    fn cooling_down_segment_data(
+        &self,
+        api_key_hash: u64,
+        remaining: std::time::Duration,
+    ) -> SegmentData {
+        let mut data = self.cached_segment_data(api_key_hash, "rate_limited");
+        data.metadata
+            .insert("status".to_string(), "rate_limited".to_string());
+        data.metadata
+            .insert("retry_in".to_string(), Self::format_retry_in(remaining));
+        data
+    }
+
+    /// 倒计时向上取整到秒：`42s`、`2m`
+    fn format_retry_in(remaining: std::time::Duration) -> String {
+        let seconds = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+        age::format_age(chrono::Duration::seconds(seconds as i64))
+    }
+
+    /// 按 fields 的顺序排列各块：第一块作为 primary，其余以空格连接为 secondary；
+    /// 设置了 separator 时全部以它连接为 primary
+    fn arrange_fields(
+        fields: &[QuotaField],
+        separator: Option<&str>,
+        daily: String,
+        weekly: String,
+        balance: String,
+    ) -> (String, String) {
+        let mut blocks = fields.iter().map(|field| match field {
+            QuotaField::Daily => daily.clone(),
+            QuotaField::Weekly => weekly.clone(),
+            QuotaField::Balance => balance.clone(),
+        });
+        if let Some(separator) = separator {
+            return (blocks.collect::<Vec<_>>().join(separator), String::new());
+        }
+        let primary = blocks.next().unwrap_or_default();
+        let secondary = blocks.collect::<Vec<_>>().join(" ");
+        (primary, secondary)
+    }
+
+    /// compact：各块取整后以 `/`（或 separator）合并，周额度带 `w` 后缀，如 `$3/$21w`
+    fn format_compact(
+        &self,
+        fields: &[QuotaField],
+        today_cost: Option<f64>,
+        balance: &BalanceApiResponse,
+        currency: &str,
+    ) -> String {
+        let format = self.money_format();
+        let compact = |amount: f64| money::format_compact_money(amount, currency, format);
+        fields
+            .iter()
+            .map(|field| match field {
+                QuotaField::Daily => match today_cost {
+                    Some(cost) => compact(cost),
+                    None => {
+                        money::format_unknown_money(0, currency, MoneyFormat { width: 0, ..format })
+                    }
+                },
+                QuotaField::Weekly => format!("{}w", compact(balance.weekly_spent_balance)),
+                QuotaField::Balance => compact(balance.total_balance),
+            })
+            .collect::<Vec<_>>()
+            .join(
+                self.options
+                    .separator
+                    .as_deref()
+                    .filter(|s| !s.is_empty())
+                    .unwrap_or("/"),
+            )
+    }
+
+    /// 缓存距今不到 min_refresh_interval_secs 时返回其年龄；间隔为 0 时不节流
+    fn fresh_cache_age(&self, api_key_hash: u64) -> Option<chrono::Duration> {
+        let interval = self.options.min_refresh_interval_secs;
+        if interval == 0 {
+            return None;
+        }
+        let cache = QuotaCache::load_for(api_key_hash, self.options.cache_key)?;
+        let age = age::cache_age(&cache.cached_at, chrono::Utc::now())?;
+        (age < chrono::Duration::seconds(interval as i64)).then_some(age)
+    }
+
+    /// show_data_age：在末尾追加数据年龄，None 表示本次实时获取
+    fn with_data_age(&self, mut data: SegmentData, age: Option<chrono::Duration>) -> SegmentData {
+        if let Some(age) = age {
+            data.metadata
+                .insert("data_age_secs".to_string(), age.num_seconds().to_string());
+        }
+        if !self.options.show_data_age || self.options.quota_style == QuotaStyle::Dot {
+            return data;
+        }
+
+        let suffix = age::format_data_age(age);
+        let target = if data.secondary.is_empty() {
+            &mut data.primary
+        } else {
+            &mut data.secondary
+        };
+        target.push(' ');
+        target.push_str(&suffix);
+        data
+    }
+
+    /// 还没有任何成功获取的数据时显示的内容
+    fn placeholder_segment_data(&self) -> SegmentData {
+        if self.options.placeholder != PlaceholderStyle::Spinner {
+            return self.offline_segment_data();
+        }
+
+        let mut metadata = BTreeMap::new();
+        metadata.insert("status".to_string(), "warming".to_string());
+        metadata.insert("dynamic_icon".to_string(), String::new());
+        metadata.insert("text_color".to_string(), "gray".to_string());
+
+        SegmentData {
+            primary: placeholder::next_spinner_frame().to_string(),
+            secondary: String::new(),
+            metadata,
+        }
+    }
+
+    /// 代理返回了非 JSON 的 200 响应（错误页），secondary 中给出原因
+    fn gateway_segment_data(&self, reason: &str) -> SegmentData {
+        let mut metadata = BTreeMap::new();
+        metadata.insert("status".to_string(), "gateway_error".to_string());
+
+        let data = SegmentData {
+            primary: "Gateway error".to_string(),
+            secondary: reason.to_string(),
+            metadata,
+        };
+        self.apply_quota_style(data, "red")
+    }
+
+    /// YESCODE_QUOTA_FIXTURE 无法读取或不符合响应结构
+    fn fixture_error_segment_data(&self, reason: &str) -> SegmentData {
+        let mut metadata = BTreeMap::new();
+        metadata.insert("status".to_string(), "fixture_error".to_string());
+
+        let data = SegmentData {
+            primary: "Fixture error".to_string(),
+            secondary: reason.to_string(),
+            metadata,
+        };
+        self.apply_quota_style(data, "red")
+    }
+
+    /// 服务维护中（503），secondary 中给出接口的说明
+    fn maintenance_segment_data(&self, message: Option<&str>) -> SegmentData {
+        let mut metadata = BTreeMap::new();
+        metadata.insert("status".to_string(), "maintenance".to_string());
+
+        let secondary = match message {
+            Some(message) if message.chars().count() > MAINTENANCE_MESSAGE_WIDTH => {
+                let truncated: String = message
+                    .chars()
+                    .take(MAINTENANCE_MESSAGE_WIDTH - 1)
+                    .collect();
+                format!("{}\u{2026}", truncated.trim_end())
+            }
+            Some(message) => message.to_string(),
+            None => "Service under maintenance".to_string(),
+        };
+
+        let data = SegmentData {
+            primary: "Maintenance".to_string(),
+            secondary,
+            metadata,
+        };
+        self.apply_quota_style(data, "yellow")
+    }
+
+    /// 接口返回 401/403：key 无效或已被吊销
+    fn auth_failed_segment_data(&self) -> SegmentData {
+        let mut metadata = BTreeMap::new();
+        metadata.insert("status".to_string(), "auth_failed".to_string());
+
+        let data = SegmentData {
+            primary: "Auth Failed".to_string(),
+            secondary: "check YESCODE_API_KEY".to_string(),
+            metadata,
+        };
+        self.apply_quota_style(data, "red")
+    }
+
+    /// 余额接口失败但日用量可用：`$3.21` + `Week: ?`
+    fn daily_only_segment_data(&self, daily_usage: &DailyUsageApiResponse) -> SegmentData {
+        let today_cost = self.get_today_cost(daily_usage);
+        let currency = self.currency_for(None);
+
+        let mut metadata = BTreeMap::new();
+        metadata.insert("daily_spent".to_string(), today_cost.to_string());
+        metadata.insert("status".to_string(), "partial".to_string());
+        metadata.insert("week_status".to_string(), "unavailable".to_string());
+
+        let data = SegmentData {
+            primary: self.money(today_cost, 2, &currency),
+            secondary: "Week: ?".to_string(),
+            metadata,
+        };
+        self.apply_quota_style(data, "gray")
+    }
+
+    /// 找不到 API key：保持段可见并提示如何配置，与网络故障的 Offline 区分开
+    fn no_key_segment_data(&self) -> SegmentData {
+        let mut metadata = BTreeMap::new();
+        metadata.insert("status".to_string(), "no_key".to_string());
+
+        let data = SegmentData {
+            primary: "No API Key".to_string(),
+            secondary: "set YESCODE_API_KEY".to_string(),
+            metadata,
+        };
+        self.apply_quota_style(data, "gray")
+    }
+
+    /// 单字符模式：只保留一个着色圆点，完整数据仍保留在 metadata 中
+    fn apply_quota_style(&self, mut data: SegmentData, dot_color: &str) -> SegmentData {
+        if self.options.quota_style == QuotaStyle::Dot {
+            data.primary = STATUS_DOT.to_string();
+            data.secondary = String::new();
+            data.metadata
+                .insert("dynamic_icon".to_string(), String::new());
+            data.metadata
+                .insert("text_color".to_string(), dot_color.to_string());
+        }
+        data
+    }
+
+    fn build_segment_data(&self, snapshot: &QuotaSnapshot) -> SegmentData {
+        let balance_response = &snapshot.balance;
+        // 日用量接口失败时今日花费未知，显示为 `$?.??` 而不是 0
+        let today_cost = snapshot
+            .daily_usage
+            .as_ref()
+            .map(|r| self.get_today_cost(r));
+
+        let currency = self.currency_for(balance_response.currency.as_deref());
+
+        // 第一块：今日已用 / 总余额（或配置的其他分母）
+        let (denominator_source, denominator) = self.daily_denominator(balance_response);
+        // 余额告警：总余额低于阈值（未欠费）时第一块只显示剩余金额
+        let low_balance = self.options.balance_alert_threshold > 0.0
+            && balance_response.total_balance < self.options.balance_alert_threshold;
+        let mut primary = if low_balance && balance_response.total_balance >= 0.0 {
+            format!(
+                "{} {} left",
+                LOW_BALANCE_MARKER,
+                self.money(balance_response.total_balance, 2, &currency)
+            )
+        } else if self.options.quota_style == QuotaStyle::RemainingAndTotal {
+            self.format_remaining_and_total(today_cost, balance_response.total_balance, &currency)
+        } else {
+            self.format_daily_used_total(today_cost, denominator, &currency)
+        };
+        if let Some(session_cost) = self.session_cost {
+            primary.push_str(&format!(
+                " (sess {})",
+                self.money(session_cost, 2, &currency)
+            ));
+        }
+
+        // 第二块：本周已用 / 周限制（设置了个人目标时对照目标）
+        let weekly_target = self.weekly_target(balance_response.weekly_limit);
+        let severity = Severity::from_weekly(
+            balance_response.weekly_spent_balance,
+            weekly_target,
+            self.options.over_limit_tolerance,
+            Thresholds::from_percent(self.options.warning_percent, self.options.critical_percent),
+        );
+        let mut secondary = match self.options.weekly_style {
+            WeeklyStyle::Amounts if self.options.show_bar && weekly_target > 0.0 => {
+                self.format_week_bar(balance_response.weekly_spent_balance, weekly_target)
+            }
+            WeeklyStyle::Amounts => self.format_week_limit(
+                balance_response.weekly_spent_balance,
+                weekly_target,
+                &currency,
+            ),
+            WeeklyStyle::Percent => {
+                self.format_week_percent(balance_response.weekly_spent_balance, weekly_target)
+            }
+        };
+
+        // 有日限额时在周额度后追加：Day: $3.21/$20
+        let daily_limit = balance_response.daily_limit.filter(|limit| *limit > 0.0);
+        let daily_limit_spent = balance_response.daily_spent_balance.or(today_cost);
+        if let Some(limit) = daily_limit {
+            secondary.push(' ');
+            secondary.push_str(&self.format_day_limit(daily_limit_spent, limit, &currency));
+        }
+
+        let today = Local::now().date_naive();
+        let days = snapshot
+            .daily_usage
+            .as_ref()
+            .map(history::coalesce_daily_usage);
+        let daily_average = days.as_ref().map(|days| {
+            history::average_daily_spend_this_week(days, today, self.options.week_definition)
+        });
+        let month_to_date = days
+            .as_ref()
+            .map(|days| history::month_to_date_spend(days, today));
+        let month_projection =
+            month_to_date.and_then(|spent| history::project_month_end(spent, today));
+        let spike_ratio = days
+            .as_ref()
+            .and_then(|days| history::spend_spike_ratio(days, today));
+        let spend_spike = self.options.spike_multiplier > 0.0
+            && spike_ratio.is_some_and(|ratio| ratio >= self.options.spike_multiplier);
+        if spend_spike {
+            primary.push(' ');
+            primary.push_str(SPIKE_MARKER);
+        }
+
+        let runway_days = days.as_ref().map(|days| {
+            let window = history::RUNWAY_WINDOW_DAYS;
+            let average = history::trailing_days_spend(days, today, window) / window as f64;
+            history::runway_days(balance_response.total_balance, average)
+        });
+
+        let now = Utc::now();
+        let weekly_reset = self.weekly_reset(balance_response, now);
+
+        // 预测的是计费周，剩余天数取自与 `resets in` 相同的重置时间
+        let weekly_projection = days.as_ref().zip(weekly_reset).map(|(days, (reset, _))| {
+            history::project_week_end(
+                days,
+                today,
+                balance_response.weekly_spent_balance,
+                history::days_before_reset(today, reset),
+            )
+        });
+
+        let lookback = self
+            .options
+            .lookback_days
+            .filter(|count| *count > 0)
+            .zip(days.as_ref())
+            .map(|(count, days)| (count, history::recent_entries_spend(days, count)));
+
+        let mut extras = Vec::new();
+        if let (true, Some(average)) = (self.options.show_daily_average, daily_average) {
+            extras.push(self.format_daily_average(average, &currency));
+        }
+        if let (true, Some(projection)) = (self.options.show_month_projection, month_projection) {
+            extras.push(self.format_month_projection(projection, &currency));
+        }
+        if let (true, Some(runway)) = (self.options.show_runway, runway_days) {
+            extras.push(self.format_runway(runway));
+        }
+        if let (true, Some((count, total))) = (self.options.show_lookback_total, lookback) {
+            extras.push(self.format_lookback_total(count, total, &currency));
+        }
+        if let (true, Some((reset, _))) = (self.options.show_reset, weekly_reset) {
+            extras.push(format!("resets in {}", age::format_age(reset - now)));
+        }
+        let fields = self.options.fields.as_deref().filter(|f| !f.is_empty());
+        let separator = self.options.separator.as_deref().filter(|s| !s.is_empty());
+        if self.options.compact {
+            primary = self.format_compact(
+                fields.unwrap_or(&[QuotaField::Daily, QuotaField::Weekly]),
+                today_cost,
+                balance_response,
+                &currency,
+            );
+            if spend_spike {
+                primary.push(' ');
+                primary.push_str(SPIKE_MARKER);
+            }
+            if low_balance {
+                primary = format!("{} {}", LOW_BALANCE_MARKER, primary);
+            }
+            secondary = String::new();
+        } else if fields.is_some() || separator.is_some() {
+            let balance = self.money(balance_response.total_balance, 2, &currency);
+            (primary, secondary) = Self::arrange_fields(
+                fields.unwrap_or(&[QuotaField::Daily, QuotaField::Weekly]),
+                separator,
+                primary,
+                secondary,
+                balance,
+            );
+        }
+        // 附加信息跟在最后一块后面
+        let last_block = if secondary.is_empty() {
+            &mut primary
+        } else {
+            &mut secondary
+        };
+        for extra in extras {
+            last_block.push(' ');
+            last_block.push_str(&extra);
+        }
+
+        let mut metadata = BTreeMap::new();
+        match today_cost {
+            Some(cost) => metadata.insert("daily_spent".to_string(), cost.to_string()),
+            None => metadata.insert("daily_status".to_string(), "unavailable".to_string()),
+        };
+        metadata.insert(
+            "daily_denominator".to_string(),
+            denominator_source.as_str().to_string(),
+        );
+        metadata.insert(
+            "total_balance".to_string(),
+            balance_response.total_balance.to_string(),
+        );
+        // 混合套餐：按量付费余额与订阅余额分开给出
+        metadata.insert(
+            "pay_as_you_go_balance".to_string(),
+            balance_response.pay_as_you_go_balance.to_string(),
+        );
+        metadata.insert(
+            "subscription_balance".to_string(),
+            balance_response.subscription_balance.to_string(),
+        );
+        if low_balance {
+            metadata.insert("balance_alert".to_string(), "low".to_string());
+        }
+        metadata.insert(
+            "weekly_spent".to_string(),
+            balance_response.weekly_spent_balance.to_string(),
+        );
+        metadata.insert(
+            "weekly_limit".to_string(),
+            balance_response.weekly_limit.to_string(),
+        );
+        let limit_kind = if balance_response.weekly_limit > 0.0 {
+            "limited"
+        } else {
+            "unlimited"
+        };
+        metadata.insert("weekly_limit_kind".to_string(), limit_kind.to_string());
+        if let Some(goal) = self.weekly_goal() {
+            metadata.insert("weekly_goal".to_string(), goal.to_string());
+        }
+        if let Some((reset, source)) = weekly_reset {
+            metadata.insert("weekly_reset".to_string(), reset.to_rfc3339());
+            metadata.insert("weekly_reset_in".to_string(), age::format_age(reset - now));
+            metadata.insert("weekly_reset_source".to_string(), source.to_string());
+        }
+        if let Some(limit) = daily_limit {
+            metadata.insert("daily_limit".to_string(), limit.to_string());
+            if let Some(spent) = daily_limit_spent {
+                metadata.insert("daily_limit_spent".to_string(), spent.to_string());
+            }
+        }
+        if let Some(average) = daily_average {
+            metadata.insert("weekly_daily_average".to_string(), average.to_string());
+        }
+        if let Some(spent) = month_to_date {
+            metadata.insert("month_to_date".to_string(), spent.to_string());
+        }
+        if let Some(projection) = month_projection {
+            metadata.insert("month_projection".to_string(), projection.to_string());
+        }
+        if let Some(Some(runway)) = runway_days {
+            metadata.insert("runway_days".to_string(), runway.to_string());
+        }
+        if let Some((_, total)) = lookback {
+            metadata.insert("lookback_total".to_string(), total.to_string());
+        }
+        if let Some(projection) = weekly_projection {
+            metadata.insert(
+                "weekly_projection".to_string(),
+                projection.amount.to_string(),
+            );
+            let confidence = if projection.low_confidence {
+                "low"
+            } else {
+                "normal"
+            };
+            metadata.insert(
+                "weekly_projection_confidence".to_string(),
+                confidence.to_string(),
+            );
+            // 预计超出周限制（或个人目标）时供渲染提示"on track to overspend"
+            if weekly_target > 0.0 {
+                let over = projection.amount > weekly_target + self.options.over_limit_tolerance;
+                metadata.insert("weekly_projection_over".to_string(), over.to_string());
+            }
+        }
+        if let Some(session_cost) = self.session_cost {
+            metadata.insert("session_cost".to_string(), session_cost.to_string());
+        }
+        if let Some(ratio) = spike_ratio {
+            metadata.insert("spend_spike_ratio".to_string(), ratio.to_string());
+        }
+        // 周额度着色：百分比样式始终按状态着色，金额样式只在接近上限时变黄、变红
+        let week_color = match self.options.weekly_style {
+            WeeklyStyle::Percent => Some(severity.dot_color()),
+            WeeklyStyle::Amounts => severity.week_color(),
+        };
+        if let (Some(color), true) = (week_color, weekly_target > 0.0) {
+            metadata.insert("secondary_color".to_string(), color.to_string());
+        }
+        if balance_response.total_balance < 0.0 {
+            metadata.insert("status".to_string(), "negative_balance".to_string());
+            metadata.insert("text_color".to_string(), "red".to_string());
+        } else {
+            if low_balance {
+                metadata.insert("text_color".to_string(), "yellow".to_string());
+            }
+            if spend_spike {
+                metadata.insert("status".to_string(), "spend_spike".to_string());
+            }
+        }
+
+        metadata.insert("severity".to_string(), severity.as_str().to_string());
+
+        if self.options.quota_style == QuotaStyle::RemainingBar {
+            let (bar, color) = self.format_remaining_bar(
+                balance_response.weekly_spent_balance,
+                weekly_target,
+                balance_response.total_balance,
+                &currency,
+            );
+            // 欠费时保留红色
+            metadata
+                .entry("text_color".to_string())
+                .or_insert_with(|| color.to_string());
+            return SegmentData {
+                primary: bar,
+                secondary: String::new(),
+                metadata,
+            };
+        }
+
+        let data = SegmentData {
+            primary,
+            secondary,
+            metadata,
+        };
+        self.apply_quota_style(data, severity.dot_color())
+    }
+}
+
+impl QuotaSegment {
+    /// 会话标识：优先 session_id，旧版 Claude Code 没有时用 transcript 路径
+    fn session_key(input: &InputData) -> &str {
+        if input.session_id.is_empty() {
+            &input.transcript_path
+        } else {
+            &input.session_id
+        }
+    }
+
+    fn request_options(&self) -> RequestOptions<'_> {
+        RequestOptions {
+            timeout: api::resolve_timeout(self.options.timeout_ms),
+            retries: self.options.retries,
+            extra_headers: &self.options.extra_headers,
+        }
+    }
+
+    /// 本次请求使用的 API 地址
+    fn base_url(&self, api_key_hash: u64) -> String {
+        SmartEndpointDetector::resolve_base_url(
+            &self.base_url_sources(),
+            api_key_hash,
+            std::time::Duration::from_secs(self.options.endpoint_cache_ttl_secs),
+        )
+    }
+
+    /// 实时获取（或决定只用缓存），不渲染；结果由 render 按段的选项渲染
+    #[cfg(feature = "quota")]
+    fn fetch_with_mode(&self, mode: RefreshMode) -> QuotaFetch {
+        // 没有 API key 时到此为止：不读缓存、端点缓存或限流文件，也不发请求，只显示提示
+        let Some(api_key) = self.load_api_key() else {
+            if env::var("YESCODE_DEBUG").is_ok() {
+                eprintln!("[DEBUG] No API key found");
+            }
+            return QuotaFetch(FetchOutcome::NoKey);
+        };
+        let api_key_hash = SmartEndpointDetector::hash_api_key(&api_key);
+        let cached = |status| {
+            QuotaFetch(FetchOutcome::Cached {
+                api_key_hash,
+                status,
+            })
+        };
+
+        // 离线模式：不发起任何请求，只使用缓存
+        if crate::utils::network::is_offline() {
+            return cached("no_network");
+        }
+
+        // 缓存还足够新：不发请求
+        if let Some(age) = self.fresh_cache_age(api_key_hash) {
+            if env::var("YESCODE_DEBUG").is_ok() {
+                eprintln!(
+                    "[DEBUG] Serving quota from cache ({} old, min_refresh_interval_secs = {})",
+                    age::format_age(age),
+                    self.options.min_refresh_interval_secs
+                );
+            }
+            return cached("cached");
+        }
+
+        // 429 冷却期内不发请求，结束后自动恢复
+        if let Some(remaining) = cooldown::remaining(api_key_hash) {
+            if env::var("YESCODE_DEBUG").is_ok() {
+                eprintln!(
+                    "[DEBUG] Rate limit cooldown, retrying in {}s",
+                    remaining.as_secs()
+                );
+            }
+            return QuotaFetch(FetchOutcome::CoolingDown {
+                api_key_hash,
+                remaining,
+            });
+        }
+
+        // 后台刷新：有缓存就立即返回，让后台进程去请求
+        if mode == RefreshMode::Background
+            && QuotaCache::load_for(api_key_hash, self.options.cache_key).is_some()
+        {
+            refresh::spawn_background(&self.spawner);
+            return cached("cached");
+        }
+
+        // 全局限流：令牌用完时本次只使用缓存
+        if !rate_limit::acquire(self.options.max_fetches_per_minute) {
+            return cached("rate_limited");
+        }
+
+        if env::var("YESCODE_DEBUG").is_ok() {
+            eprintln!("[DEBUG] Fetching fresh quota data");
+        }
+        let base_url = self.base_url(api_key_hash);
+        let request = self.request_options();
+
+        // 同时获取今日使用量和余额信息
+        let (daily_usage, balance) =
+            SmartEndpointDetector::fetch_all(self.fetcher.as_ref(), &base_url, &api_key, request);
+
+        match balance {
+            Ok(balance) => {
+                self.record_endpoint(api_key_hash, &base_url);
+                cooldown::clear();
+                let (daily_usage, daily_error) = split_daily(daily_usage);
+                let snapshot = QuotaSnapshot {
+                    daily_usage,
+                    balance,
+                };
+                QuotaCache::store(
+                    api_key_hash,
+                    &snapshot,
+                    self.options.cache_write,
+                    self.options.cache_key,
+                );
+                QuotaFetch(FetchOutcome::Fetched {
+                    snapshot,
+                    live: true,
+                    daily_error,
+                })
+            }
+            Err(error) => {
+                // 429：进入冷却期，期间所有终端只使用缓存
+                let cooldown = match error {
+                    QuotaError::RateLimited { retry_after } => {
+                        Some(cooldown::start(api_key_hash, retry_after))
+                    }
+                    _ => None,
+                };
+                QuotaFetch(FetchOutcome::Failed {
+                    api_key_hash: Some(api_key_hash),
+                    error,
+                    daily_usage: daily_usage.ok(),
+                    cooldown,
+                })
+            }
+        }
+    }
+
+    /// YESCODE_QUOTA_FIXTURE 模式：只读固定数据，不需要 API key，不读写任何缓存也不联网
+    #[cfg(feature = "quota")]
+    fn fetch_fixture(&self, fixture: Result<FixtureFetcher, String>) -> QuotaFetch {
+        let fixture = match fixture {
+            Ok(fixture) => fixture,
+            Err(reason) => {
+                if env::var("YESCODE_DEBUG").is_ok() {
+                    eprintln!("[DEBUG] {}", reason);
+                }
+                return QuotaFetch(FetchOutcome::FixtureError(reason));
+            }
+        };
+        if env::var("YESCODE_DEBUG").is_ok() {
+            eprintln!("[DEBUG] Rendering quota from YESCODE_QUOTA_FIXTURE");
+        }
+
+        let (daily_usage, balance) = SmartEndpointDetector::fetch_all(
+            &fixture,
+            api::DEFAULT_BASE_URL,
+            "fixture",
+            self.request_options(),
+        );
+        let (daily_usage, daily_error) = split_daily(daily_usage);
+        QuotaFetch(match balance {
+            Ok(balance) => FetchOutcome::Fetched {
+                snapshot: QuotaSnapshot {
+                    daily_usage,
+                    balance,
+                },
+                live: false,
+                daily_error,
+            },
+            // 没有缓存可回退，直接显示 Offline
+            Err(error) => FetchOutcome::Failed {
+                api_key_hash: None,
+                error,
+                daily_usage,
+                cooldown: None,
+            },
+        })
+    }
+
+    /// 获取一次额度数据。同一行里重复的 quota 段共用这次结果，各自用 render 按自己的选项渲染；
+    /// 段被关闭时为 None，不发任何请求
+    pub fn fetch(&self) -> Option<QuotaFetch> {
+        // 编译时未启用特性与运行时关闭都在 is_enabled 中返回 false
+        if !self.is_enabled() {
+            return None;
+        }
+
+        #[cfg(feature = "quota")]
+        {
+            Some(match FixtureFetcher::from_env() {
+                Some(fixture) => self.fetch_fixture(fixture),
+                None => self.fetch_with_mode(self.options.refresh),
+            })
+        }
+
+        #[cfg(not(feature = "quota"))]
+        {
+            None
+        }
+    }
+
+    /// 按本段的选项渲染获取结果；失败时回退到缓存或占位符
+    pub fn render(&self, fetch: &QuotaFetch, input: &InputData) -> SegmentData {
+        let data = match &fetch.0 {
+            FetchOutcome::NoKey => self.no_key_segment_data(),
+            FetchOutcome::FixtureError(reason) => self.fixture_error_segment_data(reason),
+            FetchOutcome::Cached {
+                api_key_hash,
+                status,
+            } => self.cached_segment_data(*api_key_hash, status),
+            FetchOutcome::CoolingDown {
+                api_key_hash,
+                remaining,
+            } => self.cooling_down_segment_data(*api_key_hash, *remaining),
+            FetchOutcome::Fetched {
+                snapshot,
+                live,
+                daily_error,
+            } => {
+                let mut data = self.build_segment_data(snapshot);
+                if let Some(error) = daily_error {
+                    data.metadata
+                        .insert("daily_error".to_string(), error.to_string());
+                }
+                if *live
+                    && self.options.over_limit_bell
+                    && data.metadata.get("severity").map(String::as_str) == Some("over_limit")
+                {
+                    bell::ring_once(Self::session_key(input));
+                }
+                self.with_data_age(data, None)
+            }
+            FetchOutcome::Failed {
+                api_key_hash,
+                error,
+                daily_usage,
+                cooldown,
+            } => self.failed_segment_data(*api_key_hash, error, daily_usage.as_ref(), *cooldown),
+        };
+        self.with_account(data)
+    }
+
+    fn failed_segment_data(
+        &self,
+        api_key_hash: Option<u64>,
+        error: &QuotaError,
+        daily_usage: Option<&DailyUsageApiResponse>,
+        cooldown: Option<std::time::Duration>,
+    ) -> SegmentData {
+        match error {
+            QuotaError::Gateway(reason) => return self.gateway_segment_data(reason),
+            QuotaError::Maintenance(message) => {
+                return self.maintenance_segment_data(message.as_deref())
+            }
+            // key 被拒绝时缓存的数据也不可信，直接提示
+            QuotaError::Unauthorized => return self.auth_failed_segment_data(),
+            _ => {}
+        }
+
+        // 只有余额接口失败：照常显示今日花费，周额度标记为不可用
+        if let Some(daily_usage) = daily_usage {
+            return self.with_data_age(self.daily_only_segment_data(daily_usage), None);
+        }
+        // API调用失败：显示上次成功的数据并标记原因（stale/timeout/rate_limited）；
+        // 从未成功获取过（或是 fixture 模式）时显示占位符
+        let Some(api_key_hash) = api_key_hash else {
+            return self.offline_segment_data();
+        };
+        let mut data = self.cached_segment_data(api_key_hash, error.fallback_status());
+        if let QuotaError::RateLimited {
+            retry_after: Some(retry_after),
+        } = error
+        {
+            data.metadata.insert(
+                "retry_after_secs".to_string(),
+                retry_after.as_secs().to_string(),
+            );
+        }
+        if let Some(cooldown) = cooldown {
+            data.metadata
+                .insert("retry_in".to_string(), Self::format_retry_in(cooldown));
+        }
+        data
+    }
+
+    /// `quota --refresh`：后台刷新进程的入口，同步获取一次并写缓存
+    #[cfg(feature = "quota")]
+    pub fn refresh_cache(&self) {
+        self.fetch_with_mode(RefreshMode::Blocking);
+        refresh::finish();
+    }
+
+    #[cfg(feature = "quota")]
+    /// 报告用的数据：联网时请求一次并写缓存，失败或离线时回退到缓存（同时返回缓存时间）
+    fn report_snapshot(&self) -> Result<(QuotaSnapshot, Option<String>), String> {
+        if let Some(fixture) = FixtureFetcher::from_env() {
+            let (daily_usage, balance) = SmartEndpointDetector::fetch_all(
+                &fixture?,
+                api::DEFAULT_BASE_URL,
+                "fixture",
+                self.request_options(),
+            );
+            let balance = balance.map_err(|e| format!("fixture balance: {}", e))?;
+            return Ok((
+                QuotaSnapshot {
+                    daily_usage: daily_usage.ok(),
+                    balance,
+                },
+                None,
+            ));
+        }
+
+        let api_key = self
+            .load_api_key()
+            .ok_or_else(|| "No API key found (set YESCODE_API_KEY)".to_string())?;
+        let api_key_hash = SmartEndpointDetector::hash_api_key(&api_key);
+
+        let fetched = if crate::utils::network::is_offline() {
+            None
+        } else {
+            let base_url = self.base_url(api_key_hash);
+            let request = self.request_options();
+            let (daily_usage, balance) = SmartEndpointDetector::fetch_all(
+                self.fetcher.as_ref(),
+                &base_url,
+                &api_key,
+                request,
+            );
+            if matches!(balance, Err(QuotaError::Unauthorized)) {
+                return Err("API key was rejected (HTTP 401/403)".to_string());
+            }
+            balance
+                .map(|balance| {
+                    self.record_endpoint(api_key_hash, &base_url);
+                    let snapshot = QuotaSnapshot {
+                        daily_usage: daily_usage.ok(),
+                        balance,
+                    };
+                    QuotaCache::store(
+                        api_key_hash,
+                        &snapshot,
+                        self.options.cache_write,
+                        self.options.cache_key,
+                    );
+                    snapshot
+                })
+                .ok()
+        };
+
+        match fetched {
+            Some(snapshot) => Ok((snapshot, None)),
+            None => {
+                let cache = QuotaCache::load_for(api_key_hash, self.options.cache_key)
+                    .ok_or_else(|| "Quota unavailable and no cached data".to_string())?;
+                Ok((cache.snapshot, Some(cache.cached_at)))
+            }
+        }
+    }
+
+    #[cfg(feature = "quota")]
+    /// 渲染 `quota --summary` 报告
+    pub fn summary(&self) -> Result<String, String> {
+        let (snapshot, cached_at) = self.report_snapshot()?;
+        let report = summary::render_summary(
+            &snapshot,
+            &self.currency_for(snapshot.balance.currency.as_deref()),
+            self.options.week_definition,
+            Local::now(),
+            self.weekly_reset(&snapshot.balance, Utc::now())
+                .map(|(reset, _)| reset),
+        );
+        Ok(match cached_at {
+            Some(cached_at) => format!("(cached {})\n{}", cached_at, report),
+            None => report,
+        })
+    }
+
+    #[cfg(feature = "quota")]
+    /// `quota --check`：打印 key 来源、请求地址以及每个端点的状态码和耗时，用于排查 Offline
+    pub fn diagnose(&self) -> String {
+        let mut report = check::Report::default();
+        for invalid in &self.options.invalid {
+            report.line("Option", &format!("{}, using its default", invalid));
+        }
+        let Some((source, api_key)) = self.find_api_key() else {
+            report.line("API key", "not found (set YESCODE_API_KEY)");
+            return report.finish();
+        };
+        report.line(
+            "API key",
+            &format!("{} from {}", check::mask_api_key(&api_key), source),
+        );
+
+        let api_key_hash = SmartEndpointDetector::hash_api_key(&api_key);
+        let base_url = self.base_url(api_key_hash);
+        let base_url_source = match self.base_url_sources().explicit() {
+            Some((source, _)) => source,
+            None if base_url == api::DEFAULT_BASE_URL => "default",
+            None => "endpoint_cache.json",
+        };
+        report.line("Base URL", &format!("{} ({})", base_url, base_url_source));
+
+        let request = self.request_options();
+        report.line("Timeout", &format!("{}ms", request.timeout.as_millis()));
+        #[cfg(any(feature = "http-ureq", feature = "http-reqwest"))]
+        report.line(
+            "Proxy",
+            proxy::ProxyEnv::from_env()
+                .proxy_for(&base_url)
+                .unwrap_or("none"),
+        );
+        // 离线时连诊断请求也不发
+        if crate::utils::network::is_offline() {
+            report.line(
+                "Network",
+                "offline detected; the status line skips requests",
+            );
+            return report.finish();
+        }
+
+        for endpoint in SmartEndpointDetector::check_endpoints(
+            self.fetcher.as_ref(),
+            &base_url,
+            &api_key,
+            request,
+        ) {
+            report.endpoint(&endpoint);
+        }
+        report.finish()
+    }
+
+    #[cfg(feature = "quota")]
+    /// `quota --json`：原始数值的 JSON，金额不做显示用的舍入，便于脚本自行计算
+    pub fn to_json(&self) -> Result<String, String> {
+        let (snapshot, cached_at) = self.report_snapshot()?;
+        let balance = &snapshot.balance;
+        let report = QuotaJson {
+            daily_spent: snapshot
+                .daily_usage
+                .as_ref()
+                .map(|r| self.get_today_cost(r)),
+            total_balance: balance.total_balance,
+            weekly_spent: balance.weekly_spent_balance,
+            weekly_limit: balance.weekly_limit,
+            currency: balance
+                .currency
+                .clone()
+                .or_else(|| self.options.currency.clone())
+                .unwrap_or_else(|| "USD".to_string()),
+            cached_at,
+        };
+        serde_json::to_string_pretty(&report).map_err(|e| e.to_string())
+    }
+}
+
+/// 日用量接口的结果拆成快照里的数据和失败原因
+fn split_daily(
+    daily_usage: Result<DailyUsageApiResponse, QuotaError>,
+) -> (Option<DailyUsageApiResponse>, Option<QuotaError>) {
+    match daily_usage {
+        Ok(daily_usage) => (Some(daily_usage), None),
+        Err(error) => (None, Some(error)),
+    }
+}
+
+/// 去掉首尾空白；为空或含控制字符（如复制进来的换行）时视为无效
+fn valid_api_key(raw: &str) -> Option<String> {
+    let key = raw.trim();
+    (!key.is_empty() && !key.chars().any(char::is_control)).then(|| key.to_string())
+}
+
+/// `quota --json` 的输出
+#[derive(Serialize)]
+struct QuotaJson {
+    /// 日用量接口失败时为 null
+    daily_spent: Option<f64>,
+    total_balance: f64,
+    weekly_spent: f64,
+    weekly_limit: f64,
+    /// 货币代码：接口返回的，其次为配置的 currency，默认 USD
+    currency: String,
+    /// 数据来自缓存时的写入时间，实时获取时为 null
+    cached_at: Option<String>,
+}
+
+impl Segment for QuotaSegment {
+    fn collect(&self, input: &InputData) -> Option<SegmentData> {
+        let fetch = self.fetch()?;
+        Some(self.render(&fetch, input))
+    }
+
+    fn id(&self) -> SegmentId {
+        SegmentId::Quota
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestEnv;
+    use crate::utils::network;
+    use fixture::MockFetcher;
+
+    fn segment(options: serde_json::Value, fetcher: &MockFetcher) -> QuotaSegment {
+        let options: HashMap<String, serde_json::Value> = serde_json::from_value(options).unwrap();
+        QuotaSegment::new()
+            .with_options(&options)
+            .with_fetcher(Box::new(fetcher.clone()))
+    }
+
+    fn json(status: u16, body: serde_json::Value) -> Result<HttpResponse, QuotaError> {
+        Ok(HttpResponse {
+            status,
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+            body: body.to_string(),
+        })
+    }
+
+    fn daily_usage_ok() -> Result<HttpResponse, QuotaError> {
+        json(
+            200,
+            serde_json::json!({ "daily_usage": [
+                { "date": Local::now().format("%Y-%m-%d").to_string(), "total_cost": 3.21 }
+            ] }),
+        )
+    }
+
+    fn balance_ok() -> Result<HttpResponse, QuotaError> {
+        json(
+            200,
+            serde_json::json!({
+                "balance": 50.0,
+                "pay_as_you_go_balance": 0.0,
+                "subscription_balance": 50.0,
+                "total_balance": 50.0,
+                "weekly_limit": 100.0,
+                "weekly_spent_balance": 21.5,
+            }),
+        )
+    }
+
+    /// 两个端点都正常返回的 mock
+    fn healthy_fetcher() -> MockFetcher {
+        fetcher(daily_usage_ok(), balance_ok())
+    }
+
+    fn fetcher(
+        daily_usage: Result<HttpResponse, QuotaError>,
+        balance: Result<HttpResponse, QuotaError>,
+    ) -> MockFetcher {
+        MockFetcher::new()
+            .route("/usage/daily", vec![daily_usage])
+            .route("/balance", vec![balance])
+    }
+
+    /// 不节流、不重试，每次 collect 都实时请求一次
+    fn live_options() -> serde_json::Value {
+        serde_json::json!({ "min_refresh_interval_secs": 0, "retries": 0 })
+    }
+
+    /// 以 test-key 请求的环境
+    fn keyed_env() -> TestEnv {
+        let env = TestEnv::new();
+        env.set("YESCODE_API_KEY", "test-key");
+        env
+    }
+
+    /// 今日已花 3.21 的快照
+    fn snapshot(total_balance: f64, weekly_spent: f64, weekly_limit: f64) -> QuotaSnapshot {
+        QuotaSnapshot {
+            daily_usage: Some(
+                serde_json::from_value(serde_json::json!({ "daily_usage": [
+                    { "date": Local::now().format("%Y-%m-%d").to_string(), "total_cost": 3.21 }
+                ] }))
+                .unwrap(),
+            ),
+            balance: serde_json::from_value(serde_json::json!({
+                "balance": total_balance,
+                "pay_as_you_go_balance": 0.0,
+                "subscription_balance": total_balance,
+                "total_balance": total_balance,
+                "weekly_limit": weekly_limit,
+                "weekly_spent_balance": weekly_spent,
+            }))
+            .unwrap(),
+        }
+    }
+
+    fn render(options: serde_json::Value, snapshot: &QuotaSnapshot) -> SegmentData {
+        let _env = TestEnv::new();
+        segment(options, &MockFetcher::new()).build_segment_data(snapshot)
+    }
+
+    /// 用 fixtures/quota 下的固定数据实时获取一次
+    fn collect_fixture(name: &str, options: serde_json::Value) -> SegmentData {
+        let _env = keyed_env();
+        let options: HashMap<String, serde_json::Value> = serde_json::from_value(options).unwrap();
+        let segment = QuotaSegment::new()
+            .with_options(&options)
+            .with_fetcher(Box::new(fixture::load(name)));
+        collect(&segment)
+    }
+
+    fn collect(segment: &QuotaSegment) -> SegmentData {
+        segment
+            .collect(&InputData::default())
+            .expect("quota segment")
+    }
+
+    fn store_cache(api_key: &str) {
+        let snapshot = QuotaSnapshot {
+            daily_usage: None,
+            ..snapshot(50.0, 21.5, 100.0)
+        };
+        QuotaCache::store(
+            SmartEndpointDetector::hash_api_key(api_key),
+            &snapshot,
+            options::CacheWriteStrategy::Always,
+            options::CacheKey::ApiKey,
+        );
+    }
+
+    #[test]
+    fn no_network_flag_never_calls_the_fetcher() {
+        let env = TestEnv::new();
+        env.set("YESCODE_API_KEY", "test-key");
+        network::set_no_network(true);
+        let fetcher = healthy_fetcher();
+
+        let data = collect(&segment(serde_json::json!({}), &fetcher));
+        assert!(fetcher.requests().is_empty());
+        assert_eq!(data.primary, "Offline");
+        assert_eq!(data.metadata["status"], "offline");
+    }
+
+    #[test]
+    fn offline_env_serves_the_cache_without_fetching() {
+        let env = TestEnv::new();
+        env.set("YESCODE_API_KEY", "test-key");
+        env.set("YESCODE_OFFLINE", "1");
+        store_cache("test-key");
+        let fetcher = healthy_fetcher();
+
+        let data = collect(&segment(serde_json::json!({}), &fetcher));
+        assert!(fetcher.requests().is_empty());
+        assert_eq!(data.primary, "$?.??/$50.00");
+        assert_eq!(data.metadata["status"], "no_network");
+    }
+
+    #[test]
+    fn offline_report_uses_the_cache_without_fetching() {
+        let env = TestEnv::new();
+        env.set("YESCODE_API_KEY", "test-key");
+        network::set_no_network(true);
+        store_cache("test-key");
+        let fetcher = healthy_fetcher();
+
+        let json = segment(serde_json::json!({}), &fetcher).to_json().unwrap();
+        assert!(fetcher.requests().is_empty());
+        assert!(json.contains("\"total_balance\": 50.0"));
+    }
+
+    #[test]
+    fn online_render_does_fetch() {
+        let env = TestEnv::new();
+        env.set("YESCODE_API_KEY", "test-key");
+        let fetcher = healthy_fetcher();
+
+        let data = collect(&segment(serde_json::json!({}), &fetcher));
+        assert_eq!(fetcher.requests().len(), 2);
+        assert_eq!(data.primary, "$3.21/$50.00");
+    }
+
+    #[test]
+    fn canned_success_renders_both_blocks() {
+        let _env = keyed_env();
+        let data = collect(&segment(live_options(), &healthy_fetcher()));
+        assert_eq!(data.primary, "$3.21/$50.00");
+        assert_eq!(data.secondary, "Week: $21.50/$100");
+        assert_eq!(data.metadata["daily_spent"], "3.21");
+        assert_eq!(data.metadata["total_balance"], "50");
+        assert_eq!(data.metadata["weekly_spent"], "21.5");
+        assert_eq!(data.metadata["weekly_limit"], "100");
+        assert_eq!(data.metadata["severity"], "ok");
+        assert!(!data.metadata.contains_key("status"));
+    }
+
+    #[test]
+    fn canned_http_error_falls_back_to_offline() {
+        let _env = keyed_env();
+        let fetcher = fetcher(
+            json(500, serde_json::json!({})),
+            json(500, serde_json::json!({})),
+        );
+        let data = collect(&segment(live_options(), &fetcher));
+        assert_eq!(data.primary, "Offline");
+        assert_eq!(data.secondary, "Offline");
+        assert_eq!(data.metadata["status"], "offline");
+    }
+
+    #[test]
+    fn canned_http_error_serves_the_cache_as_stale() {
+        let _env = keyed_env();
+        store_cache("test-key");
+        let fetcher = fetcher(
+            json(502, serde_json::json!({})),
+            json(502, serde_json::json!({})),
+        );
+        let data = collect(&segment(live_options(), &fetcher));
+        assert_eq!(data.secondary, "Week: $21.50/$100");
+        assert_eq!(data.metadata["status"], "stale");
+    }
+
+    #[test]
+    fn canned_timeout_is_reported_as_timeout() {
+        let _env = keyed_env();
+        store_cache("test-key");
+        let fetcher = fetcher(Err(QuotaError::Timeout), Err(QuotaError::Timeout));
+        let data = collect(&segment(live_options(), &fetcher));
+        assert_eq!(data.secondary, "Week: $21.50/$100");
+        assert_eq!(data.metadata["status"], "timeout");
+    }
+
+    #[test]
+    fn canned_malformed_json_is_a_gateway_error() {
+        let _env = keyed_env();
+        let malformed = Ok(HttpResponse {
+            status: 200,
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+            body: "{\"total_balance\": ".to_string(),
+        });
+        let data = collect(&segment(
+            live_options(),
+            &fetcher(daily_usage_ok(), malformed),
+        ));
+        assert_eq!(data.primary, "Gateway error");
+        assert_eq!(data.secondary, "proxy returned a non-JSON body");
+        assert_eq!(data.metadata["status"], "gateway_error");
+    }
+
+    #[test]
+    fn canned_json_with_the_wrong_shape_keeps_daily_usage() {
+        let _env = keyed_env();
+        let wrong_shape = json(200, serde_json::json!({ "total_balance": "lots" }));
+        let data = collect(&segment(
+            live_options(),
+            &fetcher(daily_usage_ok(), wrong_shape),
+        ));
+        assert_eq!(data.primary, "$3.21");
+        assert_eq!(data.secondary, "Week: ?");
+        assert_eq!(data.metadata["status"], "partial");
+    }
+
+    #[test]
+    fn negative_balance_is_rendered_as_due() {
+        let data = render(serde_json::json!({}), &snapshot(-5.0, 21.5, 100.0));
+        assert_eq!(data.primary, "$3.21/\u{2212}$5.00 (due)");
+        assert_eq!(data.metadata["status"], "negative_balance");
+        assert_eq!(data.metadata["text_color"], "red");
+    }
+
+    #[test]
+    fn positive_balance_keeps_the_plain_layout() {
+        let data = render(serde_json::json!({}), &snapshot(5.0, 21.5, 100.0));
+        assert_eq!(data.primary, "$3.21/$5.00");
+        assert!(!data.metadata.contains_key("status"));
+    }
+
+    fn dot_color(weekly_spent: f64) -> String {
+        let data = render(
+            serde_json::json!({ "quota_style": "dot" }),
+            &snapshot(50.0, weekly_spent, 100.0),
+        );
+        assert_eq!(data.primary, STATUS_DOT);
+        assert_eq!(data.secondary, "");
+        data.metadata["text_color"].clone()
+    }
+
+    #[test]
+    fn dot_style_colors_follow_severity() {
+        assert_eq!(dot_color(21.5), "green");
+        assert_eq!(dot_color(80.0), "yellow");
+        assert_eq!(dot_color(95.0), "yellow");
+        assert_eq!(dot_color(120.0), "red");
+    }
+
+    #[test]
+    fn dot_style_keeps_the_full_data_in_metadata() {
+        let data = render(
+            serde_json::json!({ "quota_style": "dot" }),
+            &snapshot(50.0, 21.5, 100.0),
+        );
+        assert_eq!(data.metadata["total_balance"], "50");
+        assert_eq!(data.metadata["weekly_spent"], "21.5");
+    }
+
+    #[test]
+    fn dot_style_is_gray_when_offline() {
+        let env = keyed_env();
+        env.set("YESCODE_OFFLINE", "1");
+        let data = collect(&segment(
+            serde_json::json!({ "quota_style": "dot" }),
+            &MockFetcher::new(),
+        ));
+        assert_eq!(data.primary, STATUS_DOT);
+        assert_eq!(data.metadata["text_color"], "gray");
+    }
+
+    #[test]
+    fn metadata_serializes_with_sorted_keys() {
+        let first = render(serde_json::json!({}), &snapshot(50.0, 80.0, 100.0));
+        let second = render(serde_json::json!({}), &snapshot(50.0, 80.0, 100.0));
+        let json = serde_json::to_string(&first.metadata).unwrap();
+        assert_eq!(json, serde_json::to_string(&second.metadata).unwrap());
+
+        let keys: Vec<&String> = first.metadata.keys().collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+        let positions: Vec<usize> = keys
+            .iter()
+            .map(|key| json.find(&format!("\"{}\":", key)).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn html_error_page_renders_the_gateway_reason() {
+        let _env = keyed_env();
+        let page = Ok(HttpResponse {
+            status: 200,
+            headers: vec![("content-type".to_string(), "text/html".to_string())],
+            body: "<html>Bad Gateway</html>".to_string(),
+        });
+        let data = collect(&segment(live_options(), &fetcher(daily_usage_ok(), page)));
+        assert_eq!(data.primary, "Gateway error");
+        assert_eq!(data.secondary, "proxy returned text/html");
+        assert_eq!(data.metadata["status"], "gateway_error");
+    }
+
+    #[test]
+    fn one_fetch_renders_with_each_segments_options() {
+        let _env = keyed_env();
+        let fetcher = healthy_fetcher();
+        let absolute = segment(live_options(), &fetcher);
+        let percent = segment(
+            serde_json::json!({ "min_refresh_interval_secs": 0, "display_mode": "percent" }),
+            &fetcher,
+        );
+
+        let fetch = absolute.fetch().unwrap();
+        assert_eq!(
+            absolute.render(&fetch, &InputData::default()).secondary,
+            "Week: $21.50/$100"
+        );
+        assert_eq!(
+            percent.render(&fetch, &InputData::default()).secondary,
+            "Week: 22%"
+        );
+        assert_eq!(fetcher.requests().len(), 2);
+    }
+
+    #[test]
+    fn disabled_segments_do_not_fetch() {
+        let _env = keyed_env();
+        let fetcher = healthy_fetcher();
+        assert!(segment(live_options(), &fetcher)
+            .with_enabled(false)
+            .fetch()
+            .is_none());
+        assert!(fetcher.requests().is_empty());
+    }
+
+    #[test]
+    fn spinner_placeholder_advances_until_the_first_fetch() {
+        let env = keyed_env();
+        env.set("YESCODE_OFFLINE", "1");
+        let segment = segment(
+            serde_json::json!({ "placeholder": "spinner" }),
+            &MockFetcher::new(),
+        );
+        let first = collect(&segment);
+        let second = collect(&segment);
+        assert_eq!(first.metadata["status"], "warming");
+        assert_eq!(first.primary, "\u{280b}");
+        assert_eq!(second.primary, "\u{2819}");
+    }
+
+    #[test]
+    fn session_cost_is_appended_to_the_first_block() {
+        let _env = TestEnv::new();
+        let data = segment(serde_json::json!({}), &MockFetcher::new())
+            .with_session_cost(Some(0.43))
+            .build_segment_data(&snapshot(50.0, 21.5, 100.0));
+        assert_eq!(data.primary, "$3.21/$50.00 (sess $0.43)");
+        assert_eq!(data.metadata["session_cost"], "0.43");
+    }
+
+    fn remaining_bar(weekly_spent: f64, weekly_limit: f64) -> (String, String) {
+        let data = render(
+            serde_json::json!({ "quota_style": "remaining_bar" }),
+            &snapshot(50.0, weekly_spent, weekly_limit),
+        );
+        (data.primary, data.metadata["text_color"].clone())
+    }
+
+    fn bar(filled: usize) -> String {
+        format!(
+            "[{}{}]",
+            "\u{2588}".repeat(filled),
+            "\u{2591}".repeat(REMAINING_BAR_WIDTH - filled)
+        )
+    }
+
+    #[test]
+    fn remaining_bar_depletes_as_spend_grows() {
+        assert_eq!(
+            remaining_bar(0.0, 100.0),
+            (format!("{} $100 left", bar(8)), "green".to_string())
+        );
+        assert_eq!(
+            remaining_bar(50.0, 100.0),
+            (format!("{} $50 left", bar(4)), "yellow".to_string())
+        );
+        assert_eq!(
+            remaining_bar(95.0, 100.0),
+            (format!("{} $5 left", bar(0)), "red".to_string())
+        );
+    }
+
+    #[test]
+    fn remaining_bar_without_a_limit_shows_the_balance() {
+        assert_eq!(
+            remaining_bar(10.0, 0.0),
+            ("$50 left".to_string(), "green".to_string())
+        );
+    }
+
+    fn blocks(data: &SegmentData) -> (&str, &str) {
+        (&data.primary, &data.secondary)
+    }
+
+    #[test]
+    fn fixtures_render_the_default_layout() {
+        for (name, expected) in [
+            ("typical.json", ("$3.21/$50.00", "Week: $21.50/$100")),
+            ("extra_fields.json", ("$2.50/$30.00", "Week: $12.00/$60")),
+            (
+                "huge_numbers.json",
+                ("$123456.79/$9876543.21", "Week: $654321.50/$1000000"),
+            ),
+            (
+                "daily_limit.json",
+                ("$3.21/$50.00", "Week: $21.50/$100 Day: $3.50/$20"),
+            ),
+        ] {
+            assert_eq!(
+                blocks(&collect_fixture(name, live_options())),
+                expected,
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn fixture_with_a_negative_balance() {
+        let data = collect_fixture("negative_balance.json", live_options());
+        assert_eq!(
+            blocks(&data),
+            ("$6.40/\u{2212}$5.00 (due)", "Week: $55.00/$50")
+        );
+        assert_eq!(data.metadata["status"], "negative_balance");
+    }
+
+    #[test]
+    fn fixture_with_the_daily_endpoint_down() {
+        let data = collect_fixture("daily_usage_down.json", live_options());
+        assert_eq!(blocks(&data), ("$?.??/$50.00", "Week: $21.50/$100"));
+        assert_eq!(data.metadata["daily_status"], "unavailable");
+    }
+
+    #[test]
+    fn fixture_with_the_balance_endpoint_down() {
+        let data = collect_fixture("balance_down.json", live_options());
+        assert_eq!(blocks(&data), ("$3.21", "Week: ?"));
+        assert_eq!(data.metadata["status"], "partial");
+    }
+
+    #[test]
+    fn fixture_error_pages() {
+        let gateway = collect_fixture("gateway_error.json", live_options());
+        assert_eq!(
+            blocks(&gateway),
+            ("Gateway error", "proxy returned text/html")
+        );
+
+        let maintenance = collect_fixture("maintenance.json", live_options());
+        assert_eq!(
+            blocks(&maintenance),
+            ("Maintenance", "Scheduled maintenance, back at 02:00 UTC")
+        );
+        assert_eq!(maintenance.metadata["status"], "maintenance");
+    }
+
+    fn snapshot_in(currency: &str) -> QuotaSnapshot {
+        let mut snapshot = snapshot(50.0, 21.5, 100.0);
+        snapshot.balance.currency = Some(currency.to_string());
+        snapshot
+    }
+
+    #[test]
+    fn every_amount_uses_the_reported_currency() {
+        let data = render(serde_json::json!({}), &snapshot_in("EUR"));
+        assert_eq!(
+            blocks(&data),
+            (
+                "\u{20ac}3.21/\u{20ac}50.00",
+                "Week: \u{20ac}21.50/\u{20ac}100"
+            )
+        );
+
+        let data = render(serde_json::json!({}), &snapshot_in("CHF"));
+        assert_eq!(
+            blocks(&data),
+            ("CHF 3.21/CHF 50.00", "Week: CHF 21.50/CHF 100")
+        );
+    }
+
+    #[test]
+    fn configured_currency_applies_when_the_api_omits_it() {
+        let data = render(
+            serde_json::json!({ "currency": "GBP" }),
+            &snapshot(50.0, 21.5, 100.0),
+        );
+        assert_eq!(data.primary, "\u{a3}3.21/\u{a3}50.00");
+
+        let data = render(
+            serde_json::json!({ "currency_symbol": "US$" }),
+            &snapshot_in("EUR"),
+        );
+        assert_eq!(data.primary, "US$3.21/US$50.00");
+    }
+
+    fn snapshot_with_daily_limit(limit: f64, spent: Option<f64>) -> QuotaSnapshot {
+        let mut snapshot = snapshot(50.0, 21.5, 100.0);
+        snapshot.balance.daily_limit = Some(limit);
+        snapshot.balance.daily_spent_balance = spent;
+        snapshot
+    }
+
+    #[test]
+    fn daily_limit_is_appended_to_the_week_block() {
+        let data = render(
+            serde_json::json!({}),
+            &snapshot_with_daily_limit(20.0, Some(3.5)),
+        );
+        assert_eq!(data.secondary, "Week: $21.50/$100 Day: $3.50/$20");
+        assert_eq!(data.metadata["daily_limit"], "20");
+        assert_eq!(data.metadata["daily_limit_spent"], "3.5");
+    }
+
+    #[test]
+    fn daily_limit_falls_back_to_todays_usage() {
+        let data = render(
+            serde_json::json!({}),
+            &snapshot_with_daily_limit(20.0, None),
+        );
+        assert_eq!(data.secondary, "Week: $21.50/$100 Day: $3.21/$20");
+    }
+
+    #[test]
+    fn no_daily_limit_renders_as_before() {
+        for snapshot in [
+            snapshot(50.0, 21.5, 100.0),
+            snapshot_with_daily_limit(0.0, Some(3.5)),
+        ] {
+            let data = render(serde_json::json!({}), &snapshot);
+            assert_eq!(data.secondary, "Week: $21.50/$100");
+            assert!(!data.metadata.contains_key("daily_limit"));
+        }
+    }
+
+    #[test]
+    fn money_field_width_keeps_the_segment_width_stable() {
+        let options = serde_json::json!({ "money_field_width": 7 });
+        let widths: Vec<(usize, usize)> = [(1.0, 50.0), (30.0, 999.0)]
+            .iter()
+            .map(|(spent, balance)| {
+                let data = render(options.clone(), &snapshot(*balance, *spent, 100.0));
+                (data.primary.chars().count(), data.secondary.chars().count())
+            })
+            .collect();
+        assert_eq!(widths[0], widths[1]);
+
+        let data = render(options, &snapshot(50.0, 21.5, 100.0));
+        assert_eq!(
+            blocks(&data),
+            ("$   3.21/$  50.00", "Week: $  21.50/$    100")
+        );
+    }
+
+    #[test]
+    fn runway_estimate_is_rendered() {
+        // 最近 7 天只有今天的 $3.21：日均约 $0.46，$50 约够 109 天
+        let data = render(
+            serde_json::json!({ "show_runway": true }),
+            &snapshot(50.0, 21.5, 100.0),
+        );
+        assert_eq!(data.secondary, "Week: $21.50/$100 ~109d left");
+        assert_eq!(
+            data.metadata["runway_days"].parse::<f64>().unwrap().floor(),
+            109.0
+        );
+    }
+
+    #[test]
+    fn runway_without_spend_is_infinite() {
+        let mut snapshot = snapshot(50.0, 21.5, 100.0);
+        snapshot.daily_usage = Some(DailyUsageApiResponse {
+            daily_usage: Vec::new(),
+        });
+        let data = render(serde_json::json!({ "show_runway": true }), &snapshot);
+        assert_eq!(data.secondary, "Week: $21.50/$100 \u{221e} left");
+        assert!(!data.metadata.contains_key("runway_days"));
+    }
+
+    #[test]
+    fn weekly_goal_replaces_the_limit_in_the_ratio() {
+        let data = render(
+            serde_json::json!({ "weekly_goal": 30.0 }),
+            &snapshot(50.0, 27.0, 100.0),
+        );
+        assert_eq!(data.secondary, "Week: $27.00/$30");
+        // 27/30 = 90%，按目标算已是 critical；对照账户限制只有 27%
+        assert_eq!(data.metadata["severity"], "critical");
+        assert_eq!(data.metadata["weekly_limit"], "100");
+        assert_eq!(data.metadata["weekly_goal"], "30");
+
+        let percent = render(
+            serde_json::json!({ "weekly_goal": 30.0, "display_mode": "percent" }),
+            &snapshot(50.0, 21.5, 100.0),
+        );
+        assert_eq!(percent.secondary, "Week: 72%");
+        assert_eq!(percent.metadata["severity"], "ok");
+    }
+
+    #[test]
+    fn non_positive_weekly_goal_falls_back_to_the_limit() {
+        for goal in [0.0, -5.0] {
+            let data = render(
+                serde_json::json!({ "weekly_goal": goal }),
+                &snapshot(50.0, 27.0, 100.0),
+            );
+            assert_eq!(data.secondary, "Week: $27.00/$100");
+            assert_eq!(data.metadata["severity"], "ok");
+            assert!(!data.metadata.contains_key("weekly_goal"));
+        }
+    }
+
+    #[test]
+    fn fixture_under_maintenance() {
+        let data = collect_fixture("maintenance.json", live_options());
+        assert_eq!(
+            blocks(&data),
+            ("Maintenance", "Scheduled maintenance, back at 02:00 UTC")
+        );
+        assert_eq!(data.metadata["status"], "maintenance");
+    }
+
+    #[test]
+    fn long_maintenance_messages_are_truncated() {
+        let data = segment(serde_json::json!({}), &MockFetcher::new())
+            .maintenance_segment_data(Some(&"word ".repeat(20)));
+        assert_eq!(data.secondary.chars().count(), MAINTENANCE_MESSAGE_WIDTH);
+        assert!(data.secondary.ends_with("word\u{2026}"));
+
+        let data =
+            segment(serde_json::json!({}), &MockFetcher::new()).maintenance_segment_data(None);
+        assert_eq!(data.secondary, "Service under maintenance");
+    }
+
+    #[test]
+    fn daily_denominator_selects_the_source() {
+        let mut snapshot = snapshot(50.0, 21.5, 100.0);
+        snapshot.balance.subscription_balance = 40.0;
+
+        for (source, expected) in [
+            ("total_balance", "$3.21/$50.00"),
+            ("weekly_limit", "$3.21/$100.00"),
+            ("subscription_balance", "$3.21/$40.00"),
+        ] {
+            let data = render(
+                serde_json::json!({ "daily_denominator": source }),
+                &snapshot,
+            );
+            assert_eq!(data.primary, expected);
+            assert_eq!(data.metadata["daily_denominator"], source);
+        }
+
+        let data = render(serde_json::json!({}), &snapshot);
+        assert_eq!(data.metadata["daily_denominator"], "total_balance");
+    }
+
+    #[test]
+    fn missing_daily_denominator_falls_back_to_the_total_balance() {
+        let mut snapshot = snapshot(50.0, 21.5, 0.0);
+        snapshot.balance.subscription_balance = 0.0;
+
+        for source in ["weekly_limit", "subscription_balance"] {
+            let data = render(
+                serde_json::json!({ "daily_denominator": source }),
+                &snapshot,
+            );
+            assert_eq!(data.primary, "$3.21/$50.00");
+            assert_eq!(data.metadata["daily_denominator"], "total_balance");
+        }
+    }
+
+    /// 今天花费 today，此前每天花费 previous（从昨天往前）
+    fn snapshot_with_history(today: f64, previous: &[f64]) -> QuotaSnapshot {
+        let now = Local::now().date_naive();
+        let entries: Vec<serde_json::Value> = std::iter::once(today)
+            .chain(previous.iter().copied())
+            .enumerate()
+            .map(|(ago, cost)| {
+                let date = now - chrono::Duration::days(ago as i64);
+                serde_json::json!({ "date": date.format("%Y-%m-%d").to_string(), "total_cost": cost })
+            })
+            .collect();
+        QuotaSnapshot {
+            daily_usage: Some(
+                serde_json::from_value(serde_json::json!({ "daily_usage": entries })).unwrap(),
+            ),
+            ..snapshot(50.0, 21.5, 100.0)
+        }
+    }
+
+    #[test]
+    fn spend_spike_is_flagged() {
+        // 前 7 天共 $7（日均 $1），今天 $4
+        let snapshot = snapshot_with_history(4.0, &[1.0; 7]);
+        let data = render(serde_json::json!({}), &snapshot);
+        assert_eq!(data.primary, "$4.00/$50.00 \u{26a1}");
+        assert_eq!(data.metadata["status"], "spend_spike");
+        assert_eq!(data.metadata["spend_spike_ratio"], "4");
+
+        let data = render(serde_json::json!({ "spike_multiplier": 5.0 }), &snapshot);
+        assert_eq!(data.primary, "$4.00/$50.00");
+        assert!(!data.metadata.contains_key("status"));
+    }
+
+    #[test]
+    fn normal_spend_and_short_history_are_not_flagged() {
+        let data = render(
+            serde_json::json!({}),
+            &snapshot_with_history(2.0, &[1.0; 7]),
+        );
+        assert_eq!(data.primary, "$2.00/$50.00");
+        assert!(!data.metadata.contains_key("status"));
+
+        let data = render(
+            serde_json::json!({}),
+            &snapshot_with_history(40.0, &[1.0; 2]),
+        );
+        assert_eq!(data.primary, "$40.00/$50.00");
+        assert!(!data.metadata.contains_key("spend_spike_ratio"));
+
+        let data = render(
+            serde_json::json!({ "spike_multiplier": 0.0 }),
+            &snapshot_with_history(40.0, &[1.0; 7]),
+        );
+        assert!(!data.primary.contains('\u{26a1}'));
+    }
+
+    #[test]
+    fn data_age_is_live_for_a_fresh_fetch() {
+        let _env = keyed_env();
+        let options = serde_json::json!({
+            "show_data_age": true, "min_refresh_interval_secs": 0, "retries": 0
+        });
+        let data = collect(&segment(options, &healthy_fetcher()));
+        assert_eq!(data.secondary, "Week: $21.50/$100 (live)");
+    }
+
+    #[test]
+    fn data_age_of_cached_data() {
+        let _env = keyed_env();
+        let segment = segment(
+            serde_json::json!({ "show_data_age": true }),
+            &MockFetcher::new(),
+        );
+        let data = |secondary: &str| SegmentData {
+            primary: "$3.21/$50.00".to_string(),
+            secondary: secondary.to_string(),
+            metadata: BTreeMap::new(),
+        };
+
+        let aged = segment.with_data_age(
+            data("Week: $21.50/$100"),
+            Some(chrono::Duration::minutes(2)),
+        );
+        assert_eq!(aged.secondary, "Week: $21.50/$100 (2m ago)");
+        assert_eq!(aged.metadata["data_age_secs"], "120");
+
+        // 没有第二块时追加到第一块
+        let aged = segment.with_data_age(data(""), Some(chrono::Duration::hours(5)));
+        assert_eq!(aged.primary, "$3.21/$50.00 (5h ago)");
+    }
+
+    #[test]
+    fn data_age_is_opt_in() {
+        let env = keyed_env();
+        store_cache("test-key");
+        env.set("YESCODE_OFFLINE", "1");
+        let data = collect(&segment(serde_json::json!({}), &MockFetcher::new()));
+        assert_eq!(data.secondary, "Week: $21.50/$100");
+        assert!(data.metadata.contains_key("data_age_secs"));
+    }
+
+    #[test]
+    fn percent_weekly_style_collapses_the_week_block() {
+        let style = || serde_json::json!({ "weekly_style": "percent" });
+        for (weekly_spent, expected, color) in [
+            (21.5, "22% wk", "green"),
+            (80.0, "80% wk", "yellow"),
+            (130.0, "130% wk", "red"),
+        ] {
+            let data = render(style(), &snapshot(50.0, weekly_spent, 100.0));
+            assert_eq!(data.secondary, expected);
+            assert_eq!(data.metadata["secondary_color"], color);
+        }
+    }
+
+    #[test]
+    fn percent_weekly_style_without_a_limit() {
+        let data = render(
+            serde_json::json!({ "weekly_style": "percent" }),
+            &snapshot(50.0, 21.5, 0.0),
+        );
+        assert_eq!(data.secondary, "no wk limit");
+        assert!(!data.metadata.contains_key("secondary_color"));
+    }
+
+    #[test]
+    fn anthropic_base_url_falls_back_to_settings_json() {
+        let env = TestEnv::new();
+        let settings = env.home().join(".claude");
+        fs::create_dir_all(&settings).unwrap();
+        fs::write(
+            settings.join("settings.json"),
+            r#"{ "env": { "ANTHROPIC_BASE_URL": "https://settings.example" } }"#,
+        )
+        .unwrap();
+        let segment = segment(serde_json::json!({}), &MockFetcher::new());
+        assert_eq!(
+            segment.base_url_sources().anthropic_env.as_deref(),
+            Some("https://settings.example")
+        );
+
+        env.set("ANTHROPIC_BASE_URL", "https://env.example");
+        env.set("YESCODE_BASE_URL", "https://yescode.example");
+        let sources = segment.base_url_sources();
+        assert_eq!(
+            sources.anthropic_env.as_deref(),
+            Some("https://env.example")
+        );
+        assert_eq!(
+            sources.explicit(),
+            Some(("YESCODE_BASE_URL", "https://yescode.example".to_string()))
+        );
+    }
+
+    #[test]
+    fn remaining_and_total_pairs_both_amounts() {
+        let style = || serde_json::json!({ "quota_style": "remaining_and_total" });
+        let data = render(style(), &snapshot(50.0, 21.5, 100.0));
+        assert_eq!(data.primary, "$47 left / $50");
+        assert_eq!(data.secondary, "Week: $21.50/$100");
+
+        // 今天花费 $3.21 已超过 $2 的余额：剩余按 0 显示
+        let data = render(style(), &snapshot(2.0, 21.5, 100.0));
+        assert_eq!(data.primary, "$0 left / $2");
+    }
+
+    #[test]
+    fn remaining_and_total_without_daily_usage() {
+        let snapshot = QuotaSnapshot {
+            daily_usage: None,
+            ..snapshot(50.0, 21.5, 100.0)
+        };
+        let data = render(
+            serde_json::json!({ "quota_style": "remaining_and_total" }),
+            &snapshot,
+        );
+        assert_eq!(data.primary, "$? left / $50");
+    }
+
+    #[test]
+    fn a_short_timeout_gives_up_instead_of_hanging() {
+        let _env = keyed_env();
+        // 接受连接但从不响应的服务器
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let options: HashMap<String, serde_json::Value> =
+            serde_json::from_value(serde_json::json!({
+                "base_url": format!("http://{}", listener.local_addr().unwrap()),
+                "timeout_ms": 200,
+                "retries": 0,
+                "min_refresh_interval_secs": 0,
+            }))
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        let data = collect(&QuotaSegment::new().with_options(&options));
+        assert!(started.elapsed() < std::time::Duration::from_secs(3));
+        assert_eq!(data.metadata["status"], "offline");
+    }
+
+    #[test]
+    fn no_api_key_exits_before_any_file_or_network_access() {
+        let env = TestEnv::new();
+        let ccline_dir = env.home().join(".claude").join("ccline");
+        fs::create_dir_all(&ccline_dir).unwrap();
+        // 读取损坏的缓存会删除它；保留下来说明没有读过
+        fs::write(ccline_dir.join("quota_cache.json"), "{ truncated").unwrap();
+        let fetcher = healthy_fetcher();
+
+        let data = collect(&segment(live_options(), &fetcher));
+        assert_eq!(data.metadata["status"], "no_key");
+        assert!(fetcher.requests().is_empty());
+
+        let files: Vec<_> = fs::read_dir(&ccline_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(files, vec!["quota_cache.json"]);
+    }
+
+    fn snapshot_with_daily(entries: serde_json::Value) -> QuotaSnapshot {
+        QuotaSnapshot {
+            daily_usage: Some(
+                serde_json::from_value(serde_json::json!({ "daily_usage": entries })).unwrap(),
+            ),
+            ..snapshot(50.0, 21.5, 100.0)
+        }
+    }
+
+    #[test]
+    fn today_cost_is_the_newest_entry() {
+        let today = Local::now().date_naive();
+        let day = |offset: i64| {
+            (today + chrono::Duration::days(offset))
+                .format("%Y-%m-%d")
+                .to_string()
+        };
+
+        // 接口把较早的日期排在前面
+        let data = render(
+            serde_json::json!({}),
+            &snapshot_with_daily(serde_json::json!([
+                { "date": day(-1), "total_cost": 9.0 },
+                { "date": day(0), "total_cost": 1.5 },
+            ])),
+        );
+        assert_eq!(data.primary, "$1.50/$50.00");
+        assert_eq!(data.metadata["daily_spent"], "1.5");
+
+        // 接口按 UTC 记日期，本地还是昨天时最新一条已是"明天"
+        let data = render(
+            serde_json::json!({}),
+            &snapshot_with_daily(serde_json::json!([
+                { "date": day(0), "total_cost": 9.0 },
+                { "date": day(1), "total_cost": 0.75 },
+            ])),
+        );
+        assert_eq!(data.primary, "$0.75/$50.00");
+
+        // 本地已过零点而接口还停在前一天
+        let data = render(
+            serde_json::json!({}),
+            &snapshot_with_daily(serde_json::json!([{ "date": day(-1), "total_cost": 9.0 }])),
+        );
+        assert_eq!(data.primary, "$9.00/$50.00");
+    }
+
+    #[test]
+    fn one_day_lookback_matches_today_cost() {
+        let today = Local::now().date_naive();
+        let data = render(
+            serde_json::json!({ "lookback_days": 1 }),
+            &snapshot_with_daily(serde_json::json!([
+                { "date": (today - chrono::Duration::days(1)).format("%Y-%m-%d").to_string(), "total_cost": 9.0 },
+                { "date": today.format("%Y-%m-%d").to_string(), "total_cost": 1.5 },
+            ])),
+        );
+        assert_eq!(
+            data.metadata["lookback_total"],
+            data.metadata["daily_spent"]
+        );
+    }
+
+    #[test]
+    fn same_day_entries_are_summed_for_today() {
+        let data = collect_fixture("bare_daily_array.json", live_options());
+        assert_eq!(blocks(&data), ("$2.00/$5.00", "Week: $2.00/$50"));
+    }
+
+    #[test]
+    fn fixture_happy_path() {
+        let data = collect_fixture("typical.json", live_options());
+        assert_eq!(blocks(&data), ("$3.21/$50.00", "Week: $21.50/$100"));
+        assert_eq!(data.metadata["daily_spent"], "3.21");
+        assert_eq!(data.metadata["total_balance"], "50");
+        assert_eq!(data.metadata["weekly_spent"], "21.5");
+        assert_eq!(data.metadata["weekly_limit_kind"], "limited");
+        assert_eq!(data.metadata["severity"], "ok");
+        assert!(!data.metadata.contains_key("status"));
+    }
+
+    #[test]
+    fn fixture_with_an_unusable_balance_is_offline() {
+        let data = collect_fixture("missing_fields.json", live_options());
+        assert_eq!(blocks(&data), ("Offline", "Offline"));
+        assert_eq!(data.metadata["status"], "offline");
+        assert!(!data.metadata.contains_key("total_balance"));
+    }
+
+    #[test]
+    fn fixture_with_no_daily_usage() {
+        let data = collect_fixture("empty_daily.json", live_options());
+        assert_eq!(blocks(&data), ("$0.00/$100.00", "Week: $0.00/$100"));
+        assert_eq!(data.metadata["daily_spent"], "0");
+        assert_eq!(data.metadata["weekly_spent"], "0");
+        assert_eq!(data.metadata["weekly_projection_confidence"], "low");
+        assert!(!data.metadata.contains_key("runway_days"));
+    }
+
+    #[test]
+    fn fixture_with_a_zero_weekly_limit() {
+        let data = collect_fixture("zero_limit.json", live_options());
+        assert_eq!(blocks(&data), ("$4.00/$20.00", "Week: $4.00"));
+        assert_eq!(data.metadata["weekly_limit"], "0");
+        assert_eq!(data.metadata["weekly_limit_kind"], "unlimited");
+        assert!(!data.metadata.contains_key("weekly_projection_over"));
+    }
+
+    #[test]
+    fn zero_and_negative_weekly_limits_render_as_unlimited() {
+        for name in ["zero_limit.json", "negative_limit.json"] {
+            for mode in ["absolute", "percent", "both"] {
+                let data = collect_fixture(
+                    name,
+                    serde_json::json!({
+                        "min_refresh_interval_secs": 0,
+                        "retries": 0,
+                        "display_mode": mode,
+                    }),
+                );
+                assert_eq!(
+                    blocks(&data),
+                    ("$4.00/$20.00", "Week: $4.00"),
+                    "{} {}",
+                    name,
+                    mode
+                );
+                assert_eq!(data.metadata["weekly_limit_kind"], "unlimited", "{}", name);
+                assert!(
+                    !data.metadata.contains_key("weekly_projection_over"),
+                    "{}",
+                    name
+                );
+                for (key, value) in &data.metadata {
+                    assert!(
+                        !value.contains("NaN") && !value.contains("inf"),
+                        "{} {}: {} = {}",
+                        name,
+                        mode,
+                        key,
+                        value
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn weekly_limit_kind_follows_the_sign_of_the_limit() {
+        let options = serde_json::json!({ "display_mode": "percent" });
+        for (limit, kind, week) in [
+            (100.0, "limited", "Week: 22%"),
+            (0.0, "unlimited", "Week: $21.50"),
+            (-1.0, "unlimited", "Week: $21.50"),
+        ] {
+            let data = render(options.clone(), &snapshot(50.0, 21.5, limit));
+            assert_eq!(data.secondary, week, "{}", limit);
+            assert_eq!(data.metadata["weekly_limit_kind"], kind, "{}", limit);
+        }
+    }
+
+    #[test]
+    fn rate_limit_sets_a_cooldown_that_skips_the_network_until_it_elapses() {
+        let _env = keyed_env();
+        store_cache("test-key");
+        let rate_limited = || {
+            Ok(HttpResponse {
+                status: 429,
+                headers: vec![
+                    ("content-type".to_string(), "application/json".to_string()),
+                    ("retry-after".to_string(), "120".to_string()),
+                ],
+                body: "{}".to_string(),
+            })
+        };
+        let fetcher = MockFetcher::new()
+            .route("/usage/daily", vec![rate_limited(), daily_usage_ok()])
+            .route("/balance", vec![rate_limited(), balance_ok()]);
+        let segment = segment(live_options(), &fetcher);
+
+        let data = collect(&segment);
+        assert_eq!(data.metadata["status"], "rate_limited");
+        assert_eq!(data.metadata["retry_in"], "2m");
+        let requests = fetcher.requests().len();
+        assert!(requests > 0);
+
+        // 冷却期内只用缓存，不再请求
+        let data = collect(&segment);
+        assert_eq!(data.metadata["status"], "rate_limited");
+        assert!(data.metadata.contains_key("retry_in"));
+        assert_eq!(fetcher.requests().len(), requests);
+
+        // 冷却期结束后自动恢复请求
+        let api_key_hash = SmartEndpointDetector::hash_api_key("test-key");
+        cooldown::start(api_key_hash, Some(std::time::Duration::ZERO));
+        let data = collect(&segment);
+        assert!(!data.metadata.contains_key("status"));
+        assert!(!data.metadata.contains_key("retry_in"));
+        assert!(fetcher.requests().len() > requests);
+        assert_eq!(cooldown::remaining(api_key_hash), None);
+    }
+
+    #[test]
+    fn check_reports_options_that_fell_back_to_their_default() {
+        let _env = keyed_env();
+        let report = segment(
+            serde_json::json!({ "warning_percent": "80", "retries": 0 }),
+            &healthy_fetcher(),
+        )
+        .diagnose();
+        let line = report
+            .lines()
+            .find(|line| line.starts_with("Option"))
+            .expect("option line");
+        assert!(line.contains("warning_percent ("), "{}", line);
+        assert!(line.ends_with(", using its default"), "{}", line);
+    }
+
+    #[test]
+    fn explicit_base_url_is_not_written_to_the_endpoint_cache() {
+        let env = keyed_env();
+        let endpoint_cache = env.home().join(".claude/ccline/endpoint_cache.json");
+        let fetcher = healthy_fetcher();
+
+        env.set("YESCODE_BASE_URL", "https://custom.example");
+        collect(&segment(live_options(), &fetcher));
+        assert!(fetcher.requests()[0].starts_with("https://custom.example"));
+        assert!(!endpoint_cache.exists());
+
+        // 去掉覆盖后立即回到默认地址
+        env.remove("YESCODE_BASE_URL");
+        collect(&segment(live_options(), &fetcher));
+        assert!(fetcher
+            .requests()
+            .last()
+            .unwrap()
+            .starts_with(api::DEFAULT_BASE_URL));
+        assert!(endpoint_cache.exists());
+    }
+
+    /// 后台刷新的段：记录启动次数，不真正启动进程
+    fn background_segment(
+        fetcher: &MockFetcher,
+    ) -> (QuotaSegment, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        let spawned = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = spawned.clone();
+        let segment = segment(
+            serde_json::json!({ "refresh": "background", "min_refresh_interval_secs": 0 }),
+            fetcher,
+        )
+        .with_spawner(Box::new(move || {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }));
+        (segment, spawned)
+    }
+
+    #[test]
+    fn background_refresh_serves_the_cache_and_spawns_once() {
+        let env = keyed_env();
+        store_cache("test-key");
+        let fetcher = healthy_fetcher();
+        let (segment, spawned) = background_segment(&fetcher);
+
+        let data = collect(&segment);
+        assert_eq!(data.secondary, "Week: $21.50/$100");
+        assert!(fetcher.requests().is_empty());
+        assert_eq!(spawned.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(env
+            .home()
+            .join(".claude/ccline/quota_refresh.lock")
+            .exists());
+
+        // 标记文件还在（刷新未结束），不再启动第二个
+        collect(&segment);
+        assert!(fetcher.requests().is_empty());
+        assert_eq!(spawned.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // 刷新结束后下一次渲染可以再启动
+        refresh::finish();
+        collect(&segment);
+        assert_eq!(spawned.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn background_refresh_without_a_cache_fetches_once_in_place() {
+        let _env = keyed_env();
+        let fetcher = healthy_fetcher();
+        let (segment, spawned) = background_segment(&fetcher);
+
+        let data = collect(&segment);
+        assert_eq!(blocks(&data), ("$3.21/$50.00", "Week: $21.50/$100"));
+        assert_eq!(spawned.load(std::sync::atomic::Ordering::SeqCst), 0);
+        let balance_requests = fetcher
+            .requests()
+            .iter()
+            .filter(|url| url.contains("/balance"))
+            .count();
+        assert_eq!(balance_requests, 1);
+    }
+
+    #[test]
+    fn failed_spawn_releases_the_flag() {
+        let env = keyed_env();
+        store_cache("test-key");
+        let segment = segment(
+            serde_json::json!({ "refresh": "background", "min_refresh_interval_secs": 0 }),
+            &healthy_fetcher(),
+        )
+        .with_spawner(Box::new(|| Err(std::io::Error::other("no exe"))));
+
+        collect(&segment);
+        assert!(!env
+            .home()
+            .join(".claude/ccline/quota_refresh.lock")
+            .exists());
+    }
+
+    #[test]
+    fn daily_usage_error_reaches_the_segment() {
+        let _env = keyed_env();
+        let data = collect(&segment(
+            live_options(),
+            &fetcher(Err(QuotaError::Timeout), balance_ok()),
+        ));
+        assert_eq!(data.primary, "$?.??/$50.00");
+        assert_eq!(data.metadata["daily_status"], "unavailable");
+        assert_eq!(data.metadata["daily_error"], "timed out");
+
+        let data = collect(&segment(
+            live_options(),
+            &fetcher(json(401, serde_json::json!({})), balance_ok()),
+        ));
+        assert_eq!(data.metadata["daily_error"], "unauthorized");
+    }
+
+    #[test]
+    fn week_end_projection_uses_the_billing_week_not_week_definition() {
+        let today = Local::now().date_naive();
+        let entries: Vec<_> = (0..7)
+            .map(|offset| {
+                let date = today - chrono::Duration::days(offset);
+                serde_json::json!({ "date": date.format("%Y-%m-%d").to_string(), "total_cost": 2.0 })
+            })
+            .collect();
+        let mut snapshot = snapshot_with_daily(serde_json::json!(entries));
+        // 重置在三天后的 00:00：今天之后还剩两天
+        let reset = (today + chrono::Duration::days(3))
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let reset = reset.and_local_timezone(Local).earliest().unwrap();
+        snapshot.balance.weekly_reset_at = Some(reset.to_rfc3339());
+
+        for week in ["rolling", "iso", "us"] {
+            for start in ["monday", "thursday"] {
+                let data = render(
+                    serde_json::json!({ "week_definition": week, "week_start_day": start }),
+                    &snapshot,
+                );
+                // 21.5 + 日均 2.0 × 2
+                assert_eq!(
+                    data.metadata["weekly_projection"], "25.5",
+                    "{} {}",
+                    week, start
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn check_sends_no_requests_when_offline() {
+        let env = keyed_env();
+        env.set("YESCODE_OFFLINE", "1");
+        let fetcher = healthy_fetcher();
+        let report = segment(live_options(), &fetcher).diagnose();
+
+        assert!(fetcher.requests().is_empty(), "{:?}", fetcher.requests());
+        assert!(
+            report
+                .trim_end()
+                .lines()
+                .last()
+                .unwrap()
+                .starts_with("Network"),
+            "{}",
+            report
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use super::options::SymbolPosition;
+
+/// 默认货币
+const DEFAULT_CURRENCY: &str = "USD";
+
+/// 金额的显示格式；默认即 `$X.XX`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MoneyFormat {
+    /// 数字部分右对齐的宽度，0 表示不补齐
+    pub width: usize,
+    pub position: SymbolPosition,
+    /// 整数部分每三位加 `,`
+    pub thousands_separator: bool,
+}
+
+/// 接口返回的货币优先，其次是配置，最后默认美元；返回用于显示的前缀
+pub fn resolve_currency(api_currency: Option<&str>, configured: Option<&str>) -> String {
+    let code = api_currency
+        .or(configured)
+        .map(str::trim)
+        .filter(|code| !code.is_empty())
+        .unwrap_or(DEFAULT_CURRENCY);
+    currency_symbol(code)
+}
+
+/// 常见货币代码对应符号；未知代码以 `CODE ` 作为前缀
+fn currency_symbol(code: &str) -> String {
+    match code.to_ascii_uppercase().as_str() {
+        "USD" => "$".to_string(),
+        "EUR" => "\u{20ac}".to_string(),
+        "GBP" => "\u{a3}".to_string(),
+        "CNY" | "RMB" | "JPY" => "\u{a5}".to_string(),
+        "KRW" => "\u{20a9}".to_string(),
+        "INR" => "\u{20b9}".to_string(),
+        other => format!("{} ", other),
+    }
+}
+
+/// 例：`$3.21`、`€3.21`、`CHF 3.21`
+pub fn format_money(amount: f64, decimals: usize, currency: &str) -> String {
+    format_money_with(amount, decimals, currency, MoneyFormat::default())
+}
+
+/// 按格式显示金额，例：`$  12.34`（补齐）、`$1,234.50`（千位分隔）、`12.34 CHF`（后置）
+///
+/// 后置时，代码类前缀 `CHF ` 的空格移到数字与代码之间。
+pub fn format_money_with(
+    amount: f64,
+    decimals: usize,
+    currency: &str,
+    format: MoneyFormat,
+) -> String {
+    let mut number = format!("{:.*}", decimals, amount);
+    if format.thousands_separator {
+        number = group_thousands(&number);
+    }
+    place_symbol(&number, currency, format)
+}
+
+/// 金额未知时的占位，与同样格式的金额对齐，例：`$?.??`
+pub fn format_unknown_money(decimals: usize, currency: &str, format: MoneyFormat) -> String {
+    let number = if decimals == 0 {
+        "?".to_string()
+    } else {
+        format!("?.{}", "?".repeat(decimals))
+    };
+    place_symbol(&number, currency, format)
+}
+
+/// 窄终端用的取整金额：`$3`、`$1.2k`、`$3.4M`；同一数值总是得到同样的结果
+pub fn format_compact_money(amount: f64, currency: &str, format: MoneyFormat) -> String {
+    let abs = amount.abs();
+    // 取整为 0 的负数不显示负号
+    let sign = if amount < 0.0 && abs.round() > 0.0 {
+        "-"
+    } else {
+        ""
+    };
+    // 按取整后的值选单位，避免出现 `1000k`
+    let number = if abs.round() < 1000.0 {
+        format!("{}{:.0}", sign, abs)
+    } else if (abs / 100.0).round() < 10_000.0 {
+        format!("{}{:.1}k", sign, abs / 1000.0)
+    } else {
+        format!("{}{:.1}M", sign, abs / 1_000_000.0)
+    };
+    let format = MoneyFormat { width: 0, ..format };
+    place_symbol(&number, currency, format)
+}
+
+fn place_symbol(number: &str, currency: &str, format: MoneyFormat) -> String {
+    let number = format!("{:>width$}", number, width = format.width);
+    match format.position {
+        SymbolPosition::Prefix => format!("{}{}", currency, number),
+        SymbolPosition::Suffix => match currency.strip_suffix(' ') {
+            Some(code) => format!("{} {}", number, code),
+            None => format!("{}{}", number, currency),
+        },
+    }
+}
+
+/// `-1234567.89` -> `-1,234,567.89`
+fn group_thousands(number: &str) -> String {
+    let (sign, digits) = match number.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", number),
+    };
+    let (integer, fraction) = match digits.find('.') {
+        Some(dot) => digits.split_at(dot),
+        None => (digits, ""),
+    };
+
+    let mut grouped = String::new();
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{}{}{}", sign, grouped, fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_currency_wins_over_the_configured_one() {
+        assert_eq!(resolve_currency(Some("EUR"), Some("GBP")), "\u{20ac}");
+        assert_eq!(resolve_currency(None, Some("gbp")), "\u{a3}");
+        assert_eq!(resolve_currency(Some(" "), None), "$");
+        assert_eq!(resolve_currency(None, None), "$");
+    }
+
+    #[test]
+    fn known_codes_render_their_symbol() {
+        assert_eq!(
+            format_money(3.21, 2, &resolve_currency(Some("EUR"), None)),
+            "\u{20ac}3.21"
+        );
+        assert_eq!(
+            format_money(1200.0, 0, &resolve_currency(Some("JPY"), None)),
+            "\u{a5}1200"
+        );
+    }
+
+    #[test]
+    fn unknown_codes_prefix_the_number() {
+        assert_eq!(
+            format_money(12.34, 2, &resolve_currency(Some("chf"), None)),
+            "CHF 12.34"
+        );
+    }
+
+    fn padded(width: usize) -> MoneyFormat {
+        MoneyFormat {
+            width,
+            ..MoneyFormat::default()
+        }
+    }
+
+    #[test]
+    fn field_width_right_aligns_the_number() {
+        assert_eq!(format_money_with(12.34, 2, "$", padded(7)), "$  12.34");
+        assert_eq!(format_money_with(500.0, 2, "$", padded(7)), "$ 500.00");
+        // 超出宽度时不截断
+        assert_eq!(format_money_with(123456.0, 2, "$", padded(7)), "$123456.00");
+    }
+
+    #[test]
+    fn varying_amounts_keep_the_same_width() {
+        let widths: Vec<usize> = [0.0, 3.21, 42.5, 999.99]
+            .iter()
+            .map(|amount| {
+                format_money_with(*amount, 2, "$", padded(7))
+                    .chars()
+                    .count()
+            })
+            .collect();
+        assert_eq!(widths, [8, 8, 8, 8]);
+        assert_eq!(format_unknown_money(2, "$", padded(7)), "$   ?.??");
+    }
+
+    #[test]
+    fn suffix_position_moves_codes_after_the_number() {
+        let suffix = MoneyFormat {
+            position: SymbolPosition::Suffix,
+            ..MoneyFormat::default()
+        };
+        assert_eq!(format_money_with(12.34, 2, "CHF ", suffix), "12.34 CHF");
+        assert_eq!(
+            format_money_with(12.34, 2, "\u{20ac}", suffix),
+            "12.34\u{20ac}"
+        );
+    }
+
+    #[test]
+    fn thousands_separator_groups_the_integer_part() {
+        let grouped = MoneyFormat {
+            thousands_separator: true,
+            ..MoneyFormat::default()
+        };
+        assert_eq!(
+            format_money_with(1234567.891, 2, "$", grouped),
+            "$1,234,567.89"
+        );
+        assert_eq!(format_money_with(-1234.5, 2, "$", grouped), "$-1,234.50");
+        assert_eq!(format_money_with(999.0, 0, "$", grouped), "$999");
+    }
+
+    #[test]
+    fn compact_amounts_pick_a_stable_unit() {
+        let format = MoneyFormat::default();
+        assert_eq!(format_compact_money(3.4, "$", format), "$3");
+        assert_eq!(format_compact_money(999.6, "$", format), "$1.0k");
+        assert_eq!(format_compact_money(1234.0, "$", format), "$1.2k");
+        assert_eq!(format_compact_money(3_400_000.0, "$", format), "$3.4M");
+        assert_eq!(format_compact_money(-0.2, "$", format), "$0");
+        assert_eq!(format_compact_money(-12.0, "$", format), "$-12");
+        // 补齐宽度不作用于紧凑金额
+        assert_eq!(format_compact_money(3.4, "$", padded(7)), "$3");
+    }
+}
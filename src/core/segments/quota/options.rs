@@ -0,0 +1,434 @@
+use chrono::Weekday;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+
+/// 今日花费超过前 7 天日均的这个倍数时标记突增
+const DEFAULT_SPIKE_MULTIPLIER: f64 = 3.0;
+
+/// 周额度进度条的默认格数
+const DEFAULT_BAR_WIDTH: usize = 8;
+
+/// 距上次成功获取不到这么多秒时直接使用缓存
+const DEFAULT_MIN_REFRESH_INTERVAL_SECS: u64 = 30;
+
+/// 周已用超过周限制不到这个金额时，视为浮点误差而不是超额
+const DEFAULT_OVER_LIMIT_TOLERANCE: f64 = 0.01;
+
+// 段配置项（来自 SegmentConfig.options）
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct QuotaOptions {
+    /// 在第二块追加本周日均花费
+    pub show_daily_average: bool,
+    /// 在第二块追加按当月日均线性外推的月末总花费
+    pub show_month_projection: bool,
+    /// 在第二块追加按最近 7 天日均估算的余额剩余天数
+    pub show_runway: bool,
+    /// 汇总最近 N 条每日记录的花费，写入 metadata 的 lookback_total
+    pub lookback_days: Option<u32>,
+    /// 在第二块追加 lookback 合计，如 `7d $45.20`
+    pub show_lookback_total: bool,
+    /// 响应缓存写入策略
+    pub cache_write: CacheWriteStrategy,
+    /// 渲染时同步请求，还是先显示缓存、在后台刷新
+    pub refresh: RefreshMode,
+    /// 缓存比这个秒数新时不发请求，0 表示每次都实时获取
+    pub min_refresh_interval_secs: u64,
+    /// 整体显示样式
+    pub quota_style: QuotaStyle,
+    /// 要显示的块及其顺序（也可写作 order），未设置时为今日 + 周额度两块
+    #[serde(alias = "order")]
+    pub fields: Option<Vec<QuotaField>>,
+    /// 设置后各块按此字符串原样连接为一块（如 `" | "`），未设置时分为 primary 和 secondary
+    pub separator: Option<String>,
+    /// 窄终端模式：各块取整后以 `/` 合并为一块，如 `$3/$21w`
+    pub compact: bool,
+    /// 首次成功获取之前的占位显示
+    pub placeholder: PlaceholderStyle,
+    /// 接口未返回货币时使用的货币代码（默认 USD）
+    pub currency: Option<String>,
+    /// 金额数字部分右对齐的宽度，0 表示不补齐
+    pub money_field_width: usize,
+    /// 自定义货币符号，优先于按货币代码推导的符号
+    pub currency_symbol: Option<String>,
+    /// 货币符号放在金额前还是后
+    pub symbol_position: SymbolPosition,
+    /// 金额整数部分加千位分隔符 `1,234.50`
+    pub thousands_separator: bool,
+    /// 超额判定的容差（金额）
+    pub over_limit_tolerance: f64,
+    /// 周已用达到周限制的这个百分比时进入 warning（默认 75）
+    pub warning_percent: f64,
+    /// 周已用达到周限制的这个百分比时进入 critical（默认 90）
+    pub critical_percent: f64,
+    /// 所有终端合计每分钟最多实时获取的次数，0 表示不限
+    pub max_fetches_per_minute: u32,
+    /// 上次成功端点的复用时长（秒），0 表示每次都用默认地址
+    pub endpoint_cache_ttl_secs: u64,
+    /// API 基础地址，优先于 YESCODE_BASE_URL 和 ANTHROPIC_BASE_URL
+    pub base_url: Option<String>,
+    /// 单次请求超时（毫秒），YESCODE_TIMEOUT_MS 可覆盖
+    pub timeout_ms: u64,
+    /// 网络错误或 5xx 时的重试次数，重试共用 timeout_ms 预算
+    pub retries: u32,
+    /// 附加到每个请求的 HTTP 头；不能覆盖 X-API-Key、accept 和 content-type
+    pub extra_headers: BTreeMap<String, String>,
+    /// 个人周花费目标；设置后周比例、进度条和着色以它代替账户周限制
+    pub weekly_goal: Option<f64>,
+    /// 第一块 `$今日/$X` 中 X 的来源
+    pub daily_denominator: DailyDenominator,
+    /// 离线时用文字、离线图标，还是两者都显示
+    pub offline_display: OfflineDisplay,
+    /// 响应缓存按 API key 还是按账户区分
+    pub cache_key: CacheKey,
+    /// 今日花费达到前 7 天日均的多少倍时显示突增标记，0 表示关闭
+    pub spike_multiplier: f64,
+    /// 总余额低于这个金额时第一块改为 `⚠ $4.20 left`，0 表示关闭
+    pub balance_alert_threshold: f64,
+    /// 在末尾追加数据年龄，如 `(2m ago)`，刚获取时为 `(live)`
+    pub show_data_age: bool,
+    /// 本会话首次超出周限制时响一次终端铃（BEL）
+    pub over_limit_bell: bool,
+    /// 第二块中周额度的显示方式
+    pub weekly_style: WeeklyStyle,
+    /// weekly_style 为 amounts 时周额度显示金额、百分比还是两者
+    pub display_mode: DisplayMode,
+    /// 周额度显示为进度条 `Week ▓▓▓▓░░░░ 52%`
+    pub show_bar: bool,
+    /// 进度条格数
+    pub bar_width: usize,
+    /// 进度条字符：方块字符或纯 ASCII（`#`、`-`）
+    pub bar_glyphs: BarGlyphs,
+    /// 本地计算的周统计（周花费、日均）采用的周定义；只影响这些统计，
+    /// 周额度、重置倒计时和周末预测始终按计费周
+    pub week_definition: WeekDefinition,
+    /// 接口没有给出重置时间时，周额度在这一天 00:00 重置（默认周一），
+    /// 周末预测的剩余天数也由这个重置时间得出
+    pub week_start_day: Weekday,
+    /// 推算重置时间时按本地时间还是 UTC 的 00:00
+    pub reset_timezone: ResetTimezone,
+    /// 在最后一块后追加 `resets in 2d`
+    pub show_reset: bool,
+    /// 多个具名账户；YESCODE_ACCOUNT 选择当前账户，默认第一个
+    pub accounts: Vec<Account>,
+    /// 在第一块前加上当前账户名，如 `[work] $3.21/$50.00`
+    pub show_account: bool,
+    /// 解析失败而回退为默认值的配置项及原因，由 `quota --check` 报告
+    #[serde(skip)]
+    pub invalid: Vec<String>,
+}
+
+impl Default for QuotaOptions {
+    fn default() -> Self {
+        Self {
+            show_daily_average: false,
+            show_month_projection: false,
+            show_runway: false,
+            lookback_days: None,
+            show_lookback_total: false,
+            cache_write: CacheWriteStrategy::default(),
+            refresh: RefreshMode::default(),
+            min_refresh_interval_secs: DEFAULT_MIN_REFRESH_INTERVAL_SECS,
+            quota_style: QuotaStyle::default(),
+            fields: None,
+            separator: None,
+            compact: false,
+            placeholder: PlaceholderStyle::default(),
+            currency: None,
+            money_field_width: 0,
+            currency_symbol: None,
+            symbol_position: SymbolPosition::default(),
+            thousands_separator: false,
+            over_limit_tolerance: DEFAULT_OVER_LIMIT_TOLERANCE,
+            warning_percent: super::severity::DEFAULT_WARNING_PERCENT,
+            critical_percent: super::severity::DEFAULT_CRITICAL_PERCENT,
+            max_fetches_per_minute: super::rate_limit::DEFAULT_MAX_FETCHES_PER_MINUTE,
+            endpoint_cache_ttl_secs: super::api::DEFAULT_ENDPOINT_CACHE_TTL_SECS,
+            base_url: None,
+            timeout_ms: super::api::DEFAULT_TIMEOUT_MS,
+            retries: super::api::DEFAULT_RETRIES,
+            extra_headers: BTreeMap::new(),
+            weekly_goal: None,
+            daily_denominator: DailyDenominator::default(),
+            offline_display: OfflineDisplay::default(),
+            cache_key: CacheKey::default(),
+            spike_multiplier: DEFAULT_SPIKE_MULTIPLIER,
+            balance_alert_threshold: 0.0,
+            show_data_age: false,
+            over_limit_bell: false,
+            weekly_style: WeeklyStyle::default(),
+            display_mode: DisplayMode::default(),
+            show_bar: false,
+            bar_width: DEFAULT_BAR_WIDTH,
+            bar_glyphs: BarGlyphs::default(),
+            week_definition: WeekDefinition::default(),
+            week_start_day: Weekday::Mon,
+            reset_timezone: ResetTimezone::default(),
+            show_reset: false,
+            accounts: Vec::new(),
+            show_account: false,
+            invalid: Vec::new(),
+        }
+    }
+}
+
+/// 具名的 API key
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Account {
+    pub name: String,
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaceholderStyle {
+    /// 固定显示 Offline
+    #[default]
+    Static,
+    /// 每次渲染推进一帧的旋转符号
+    Spinner,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaStyle {
+    /// 今日 / 余额 + 周额度两块
+    #[default]
+    Default,
+    /// 只显示一个按状态着色的圆点
+    Dot,
+    /// 随周额度花费逐渐变空的进度条 + 剩余金额
+    RemainingBar,
+    /// 第一块改为剩余与总余额：`$37 left / $50`
+    RemainingAndTotal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaField {
+    /// `$3.21/$50.00`
+    Daily,
+    /// `Week: $21.50/$100`
+    Weekly,
+    /// 只显示总余额 `$50.00`
+    Balance,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolPosition {
+    /// `$3.21`
+    #[default]
+    Prefix,
+    /// `3.21 CHF`
+    Suffix,
+}
+
+/// 推算周额度重置时刻所用的时区
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResetTimezone {
+    /// 本机时区的 00:00
+    #[default]
+    Local,
+    /// UTC 00:00
+    Utc,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeekDefinition {
+    /// 截至今天的最近 7 天
+    #[default]
+    Rolling,
+    /// 周一至周日
+    Iso,
+    /// 周日至周六
+    Us,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeeklyStyle {
+    /// `Week: $21.50/$100`
+    #[default]
+    Amounts,
+    /// `22% wk`，按周额度状态着色
+    Percent,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BarGlyphs {
+    /// `▓▓▓▓░░░░`
+    #[default]
+    Unicode,
+    /// `####----`，用于无法显示方块字符的终端
+    Ascii,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisplayMode {
+    /// `Week: $21.50/$100`
+    #[default]
+    Absolute,
+    /// `Week: 22%`
+    Percent,
+    /// `Week: $21.50 (22%)`
+    Both,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OfflineDisplay {
+    /// 段图标 + `Offline Offline`
+    #[default]
+    Text,
+    /// 只显示离线图标
+    Icon,
+    /// 离线图标 + `Offline`
+    Both,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DailyDenominator {
+    /// 账户总余额
+    #[default]
+    TotalBalance,
+    /// 套餐周限制
+    WeeklyLimit,
+    /// 订阅余额
+    SubscriptionBalance,
+}
+
+impl DailyDenominator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DailyDenominator::TotalBalance => "total_balance",
+            DailyDenominator::WeeklyLimit => "weekly_limit",
+            DailyDenominator::SubscriptionBalance => "subscription_balance",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheKey {
+    /// 按 API key 的哈希，换 key 后缓存失效
+    #[default]
+    ApiKey,
+    /// 按余额接口返回的账户标识，确认属于同一账户的 key 共用缓存；没有账户标识时退回 API key
+    Account,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefreshMode {
+    /// 每次渲染等待实时获取
+    #[default]
+    Blocking,
+    /// 立即返回缓存，由后台进程刷新供下次使用；还没有缓存时同步获取一次
+    Background,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheWriteStrategy {
+    /// 每次成功获取都重写缓存
+    #[default]
+    Always,
+    /// 仅当数据变化时重写缓存
+    OnChange,
+}
+
+impl QuotaOptions {
+    /// 逐项解析：写错的配置项只回退它自己的默认值，其余配置照常生效
+    pub fn from_options(options: &HashMap<String, serde_json::Value>) -> Self {
+        let mut keys: Vec<&String> = options.keys().collect();
+        keys.sort();
+
+        let mut valid = serde_json::Map::new();
+        let mut invalid = Vec::new();
+        for key in keys {
+            let single = serde_json::Map::from_iter([(key.clone(), options[key].clone())]);
+            match serde_json::from_value::<Self>(serde_json::Value::Object(single)) {
+                Ok(_) => {
+                    valid.insert(key.clone(), options[key].clone());
+                }
+                Err(e) => {
+                    if env::var("YESCODE_DEBUG").is_ok() {
+                        eprintln!(
+                            "[DEBUG] Invalid quota option {}, using its default: {}",
+                            key, e
+                        );
+                    }
+                    invalid.push(format!("{} ({})", key, e));
+                }
+            }
+        }
+
+        let mut parsed = match serde_json::from_value::<Self>(serde_json::Value::Object(valid)) {
+            Ok(options) => options,
+            // 单项都合法但组合起来冲突（如同时写了 fields 和 order）
+            Err(e) => {
+                if env::var("YESCODE_DEBUG").is_ok() {
+                    eprintln!("[DEBUG] Invalid quota options, using defaults: {}", e);
+                }
+                invalid.push(e.to_string());
+                Self::default()
+            }
+        };
+        parsed.invalid = invalid;
+        parsed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(options: serde_json::Value) -> QuotaOptions {
+        let options: HashMap<String, serde_json::Value> = serde_json::from_value(options).unwrap();
+        QuotaOptions::from_options(&options)
+    }
+
+    #[test]
+    fn a_mistyped_option_only_resets_itself() {
+        let options = parse(serde_json::json!({
+            "warning_percent": "80",
+            "critical_percent": 95.0,
+            "retries": 3,
+            "base_url": "https://example.test",
+        }));
+        assert_eq!(
+            options.warning_percent,
+            super::super::severity::DEFAULT_WARNING_PERCENT
+        );
+        assert_eq!(options.critical_percent, 95.0);
+        assert_eq!(options.retries, 3);
+        assert_eq!(options.base_url.as_deref(), Some("https://example.test"));
+        assert_eq!(options.invalid.len(), 1);
+        assert!(options.invalid[0].starts_with("warning_percent ("));
+    }
+
+    #[test]
+    fn valid_options_report_nothing() {
+        let options = parse(serde_json::json!({ "order": ["weekly", "daily"], "compact": true }));
+        assert!(options.invalid.is_empty());
+        assert!(options.compact);
+        assert_eq!(
+            options.fields,
+            Some(vec![QuotaField::Weekly, QuotaField::Daily])
+        );
+    }
+
+    #[test]
+    fn unknown_keys_are_ignored() {
+        let options = parse(serde_json::json!({ "not_an_option": 1, "retries": 1 }));
+        assert!(options.invalid.is_empty());
+        assert_eq!(options.retries, 1);
+    }
+}
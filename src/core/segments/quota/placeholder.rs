@@ -0,0 +1,64 @@
+use std::fs;
+use std::path::PathBuf;
+
+/// 旋转占位符的帧
+const SPINNER_FRAMES: [&str; 10] = [
+    "\u{280b}", "\u{2819}", "\u{2839}", "\u{2838}", "\u{283c}", "\u{2834}", "\u{2826}", "\u{2827}",
+    "\u{2807}", "\u{280f}",
+];
+
+// 每次渲染都是新进程，帧序号保存在文件里跨进程推进
+fn get_counter_path() -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    Some(
+        home.join(".claude")
+            .join("ccline")
+            .join("quota_placeholder_frame"),
+    )
+}
+
+/// 返回当前帧并把计数器加一；读写失败时停在第一帧，绝不阻塞渲染
+pub fn next_spinner_frame() -> &'static str {
+    let counter = advance_counter().unwrap_or(0);
+    SPINNER_FRAMES[counter % SPINNER_FRAMES.len()]
+}
+
+fn advance_counter() -> Option<usize> {
+    let path = get_counter_path()?;
+    let counter = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| content.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let _ = fs::write(&path, ((counter + 1) % SPINNER_FRAMES.len()).to_string());
+    Some(counter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestEnv;
+
+    #[test]
+    fn frames_advance_across_renders() {
+        let _env = TestEnv::new();
+        let frames: Vec<&str> = (0..SPINNER_FRAMES.len() + 2)
+            .map(|_| next_spinner_frame())
+            .collect();
+        assert_eq!(frames[..SPINNER_FRAMES.len()], SPINNER_FRAMES);
+        assert_eq!(frames[SPINNER_FRAMES.len()..], SPINNER_FRAMES[..2]);
+    }
+
+    #[test]
+    fn unreadable_counter_starts_at_the_first_frame() {
+        let _env = TestEnv::new();
+        let path = get_counter_path().unwrap();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "not a number").unwrap();
+        assert_eq!(next_spinner_frame(), SPINNER_FRAMES[0]);
+        assert_eq!(next_spinner_frame(), SPINNER_FRAMES[1]);
+    }
+}
@@ -0,0 +1,180 @@
+use std::env;
+
+/// 标准代理环境变量的快照，先检查大写再检查小写
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProxyEnv {
+    pub https_proxy: Option<String>,
+    pub http_proxy: Option<String>,
+    pub all_proxy: Option<String>,
+    pub no_proxy: Option<String>,
+}
+
+impl ProxyEnv {
+    pub fn from_env() -> Self {
+        let read = |name: &str| {
+            env::var(name.to_ascii_uppercase())
+                .or_else(|_| env::var(name))
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        Self {
+            https_proxy: read("https_proxy"),
+            http_proxy: read("http_proxy"),
+            all_proxy: read("all_proxy"),
+            no_proxy: read("no_proxy"),
+        }
+    }
+
+    /// 请求 `url` 时应使用的代理；未配置或命中 NO_PROXY 时为 None（直连）
+    pub fn proxy_for(&self, url: &str) -> Option<&str> {
+        let (scheme, _) = split_url(url)?;
+        if self.bypasses(url) {
+            return None;
+        }
+
+        let specific = if scheme.eq_ignore_ascii_case("https") {
+            self.https_proxy.as_deref()
+        } else {
+            self.http_proxy.as_deref()
+        };
+        specific.or(self.all_proxy.as_deref())
+    }
+
+    /// `url` 的主机是否在 NO_PROXY 中
+    pub fn bypasses(&self, url: &str) -> bool {
+        match (self.no_proxy.as_deref(), split_url(url)) {
+            (Some(no_proxy), Some((_, host))) => no_proxy_matches(no_proxy, host),
+            _ => false,
+        }
+    }
+}
+
+/// `https://user@co.yes.vg:443/api` -> (`https`, `co.yes.vg`)
+fn split_url(url: &str) -> Option<(&str, &str)> {
+    let (scheme, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_port = authority.rsplit('@').next()?;
+    let host = if let Some(bracketed) = host_port.strip_prefix('[') {
+        bracketed.split(']').next()?
+    } else {
+        host_port.split(':').next()?
+    };
+    Some((scheme, host))
+}
+
+/// NO_PROXY 以逗号分隔：`*` 匹配所有主机，`example.com` 与 `.example.com`
+/// 都匹配该域名及其子域名，条目中的端口忽略
+pub fn no_proxy_matches(no_proxy: &str, host: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    no_proxy
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            if entry == "*" {
+                return true;
+            }
+            let entry = entry.trim_start_matches("*.").trim_start_matches('.');
+            let entry = match entry.rsplit_once(':') {
+                Some((name, port))
+                    if port.chars().all(|c| c.is_ascii_digit())
+                        && (name.ends_with(']') || !name.contains(':')) =>
+                {
+                    name
+                }
+                _ => entry,
+            };
+            let entry = entry
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_ascii_lowercase();
+            host == entry || host.ends_with(&format!(".{}", entry))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestEnv;
+
+    const API: &str = "https://co.yes.vg/api/v1/user/balance";
+
+    #[test]
+    fn reads_upper_and_lower_case_variables() {
+        let env = TestEnv::new();
+        assert_eq!(ProxyEnv::from_env(), ProxyEnv::default());
+
+        env.set("https_proxy", "http://lower:3128");
+        env.set("HTTP_PROXY", " http://upper:3128 ");
+        env.set("no_proxy", "localhost");
+        env.set("ALL_PROXY", "");
+        assert_eq!(
+            ProxyEnv::from_env(),
+            ProxyEnv {
+                https_proxy: Some("http://lower:3128".to_string()),
+                http_proxy: Some("http://upper:3128".to_string()),
+                all_proxy: None,
+                no_proxy: Some("localhost".to_string()),
+            }
+        );
+
+        env.set("HTTPS_PROXY", "http://upper:3128");
+        assert_eq!(
+            ProxyEnv::from_env().https_proxy.as_deref(),
+            Some("http://upper:3128")
+        );
+    }
+
+    #[test]
+    fn scheme_specific_proxy_wins_over_all_proxy() {
+        let proxy_env = ProxyEnv {
+            https_proxy: Some("http://secure:3128".to_string()),
+            all_proxy: Some("socks5://all:1080".to_string()),
+            ..ProxyEnv::default()
+        };
+        assert_eq!(proxy_env.proxy_for(API), Some("http://secure:3128"));
+        assert_eq!(
+            proxy_env.proxy_for("http://co.yes.vg/api"),
+            Some("socks5://all:1080")
+        );
+        assert_eq!(ProxyEnv::default().proxy_for(API), None);
+        assert_eq!(proxy_env.proxy_for("not a url"), None);
+    }
+
+    #[test]
+    fn no_proxy_connects_directly() {
+        let proxy_env = ProxyEnv {
+            https_proxy: Some("http://secure:3128".to_string()),
+            no_proxy: Some("localhost, .yes.vg".to_string()),
+            ..ProxyEnv::default()
+        };
+        assert!(proxy_env.bypasses(API));
+        assert_eq!(proxy_env.proxy_for(API), None);
+        assert_eq!(
+            proxy_env.proxy_for("https://example.com"),
+            Some("http://secure:3128")
+        );
+    }
+
+    #[test]
+    fn no_proxy_entry_forms() {
+        assert!(no_proxy_matches("*", "co.yes.vg"));
+        assert!(no_proxy_matches("yes.vg", "co.yes.vg"));
+        assert!(no_proxy_matches("*.yes.vg", "co.yes.vg"));
+        assert!(no_proxy_matches("co.yes.vg:443", "CO.YES.VG."));
+        assert!(no_proxy_matches("[::1]:8080", "::1"));
+        assert!(!no_proxy_matches("yes.vg", "notyes.vg"));
+        assert!(!no_proxy_matches(" , ", "co.yes.vg"));
+    }
+
+    #[test]
+    fn hosts_are_taken_from_the_authority() {
+        assert_eq!(
+            split_url("https://user@co.yes.vg:443/api?x#y"),
+            Some(("https", "co.yes.vg"))
+        );
+        assert_eq!(split_url("http://[::1]:8080/"), Some(("http", "::1")));
+        assert_eq!(split_url("co.yes.vg"), None);
+    }
+}
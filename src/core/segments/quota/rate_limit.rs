@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 默认每分钟最多发起的实时获取次数
+pub const DEFAULT_MAX_FETCHES_PER_MINUTE: u32 = 30;
+
+/// 锁文件超过这个时间仍存在，视为持有者已退出
+const STALE_LOCK: Duration = Duration::from_secs(2);
+const LOCK_RETRIES: u32 = 10;
+const LOCK_RETRY_DELAY: Duration = Duration::from_millis(5);
+
+/// 跨进程共享的令牌桶：容量为每分钟上限，按秒匀速补充
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenBucket {
+    pub tokens: f64,
+    pub updated_at_ms: u64,
+}
+
+impl TokenBucket {
+    pub fn full(capacity: u32, now_ms: u64) -> Self {
+        Self {
+            tokens: capacity as f64,
+            updated_at_ms: now_ms,
+        }
+    }
+
+    /// 补充令牌后尝试取走一个；返回是否允许本次获取
+    pub fn try_take(&mut self, capacity: u32, now_ms: u64) -> bool {
+        let capacity = capacity as f64;
+        let elapsed_ms = now_ms.saturating_sub(self.updated_at_ms) as f64;
+        self.tokens = (self.tokens + elapsed_ms * capacity / 60_000.0).min(capacity);
+        self.updated_at_ms = now_ms;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+fn get_bucket_path() -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    Some(
+        home.join(".claude")
+            .join("ccline")
+            .join("quota_rate_limit.json"),
+    )
+}
+
+/// 申请一次实时获取；0 表示不限流。桶文件读写失败时放行，绝不因此阻塞渲染
+pub fn acquire(max_per_minute: u32) -> bool {
+    if max_per_minute == 0 {
+        return true;
+    }
+    let Some(path) = get_bucket_path() else {
+        return true;
+    };
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+
+    acquire_at(&path, max_per_minute, now_ms)
+}
+
+fn acquire_at(path: &Path, max_per_minute: u32, now_ms: u64) -> bool {
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+
+    let lock_path = path.with_extension("lock");
+    let Some(_lock) = FileLock::acquire(&lock_path) else {
+        // 其他进程正在更新，说明同时有大量渲染，本次按限流处理
+        return false;
+    };
+
+    let mut bucket = fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<TokenBucket>(&content).ok())
+        .unwrap_or_else(|| TokenBucket::full(max_per_minute, now_ms));
+    let allowed = bucket.try_take(max_per_minute, now_ms);
+
+    if let Ok(json) = serde_json::to_string(&bucket) {
+        let _ = super::cache::write_atomic(path, json.as_bytes());
+    }
+
+    if !allowed && env::var("YESCODE_DEBUG").is_ok() {
+        eprintln!(
+            "[DEBUG] Quota fetch rate limit reached ({}/min), using cache",
+            max_per_minute
+        );
+    }
+    allowed
+}
+
+/// 基于 create_new 的简单互斥锁，Drop 时删除
+struct FileLock {
+    path: PathBuf,
+}
+
+impl FileLock {
+    fn acquire(path: &Path) -> Option<Self> {
+        for _ in 0..LOCK_RETRIES {
+            match OpenOptions::new().write(true).create_new(true).open(path) {
+                Ok(_) => {
+                    return Some(Self {
+                        path: path.to_path_buf(),
+                    })
+                }
+                Err(_) => {
+                    let stale = fs::metadata(path)
+                        .and_then(|meta| meta.modified())
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok())
+                        .is_some_and(|age| age > STALE_LOCK);
+                    if stale {
+                        let _ = fs::remove_file(path);
+                    } else {
+                        thread::sleep(LOCK_RETRY_DELAY);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestEnv;
+
+    #[test]
+    fn bucket_refills_at_the_per_minute_rate() {
+        let mut bucket = TokenBucket::full(3, 0);
+        assert!(bucket.try_take(3, 0));
+        assert!(bucket.try_take(3, 0));
+        assert!(bucket.try_take(3, 0));
+        assert!(!bucket.try_take(3, 0));
+
+        // 3/min 即每 20 秒补充一个
+        assert!(!bucket.try_take(3, 19_000));
+        assert!(bucket.try_take(3, 20_000));
+        assert!(!bucket.try_take(3, 20_000));
+
+        // 长时间空闲也不会超过容量
+        bucket.try_take(3, 3_600_000);
+        assert!((bucket.tokens - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn burst_fetches_hit_the_cap_across_calls() {
+        let env = TestEnv::new();
+        let path = env.home().join("bucket.json");
+
+        let allowed = (0..8).filter(|_| acquire_at(&path, 5, 1_000)).count();
+        assert_eq!(allowed, 5);
+
+        // 状态保存在文件中，后续调用共享同一个桶
+        assert!(!acquire_at(&path, 5, 1_000));
+        assert!(acquire_at(&path, 5, 13_000));
+        assert!(!path.with_extension("lock").exists());
+    }
+
+    #[test]
+    fn unreadable_bucket_starts_full() {
+        let env = TestEnv::new();
+        let path = env.home().join("bucket.json");
+        fs::write(&path, "not json").unwrap();
+
+        assert!(acquire_at(&path, 1, 0));
+        assert!(!acquire_at(&path, 1, 0));
+    }
+
+    #[test]
+    fn held_lock_counts_as_rate_limited() {
+        let env = TestEnv::new();
+        let path = env.home().join("bucket.json");
+        fs::write(path.with_extension("lock"), "").unwrap();
+
+        assert!(!acquire_at(&path, 30, 0));
+    }
+
+    #[test]
+    fn zero_disables_the_limit() {
+        let _env = TestEnv::new();
+        assert!((0..100).all(|_| acquire(0)));
+    }
+}
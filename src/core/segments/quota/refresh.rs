@@ -0,0 +1,86 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// 标记文件超过这个时间仍存在，视为刷新进程已异常退出
+const STALE_FLAG: Duration = Duration::from_secs(60);
+
+fn get_flag_path() -> Option<PathBuf> {
+    let home = dirs::home_dir()?;
+    Some(
+        home.join(".claude")
+            .join("ccline")
+            .join("quota_refresh.lock"),
+    )
+}
+
+/// 启动刷新进程的方式；测试时替换为不真正启动进程的实现
+pub type Spawner = Box<dyn Fn() -> io::Result<()> + Send + Sync>;
+
+/// 用 `spawn` 启动刷新去更新缓存，供下次渲染使用
+///
+/// 标记文件保证同一时间只有一个刷新进程；缓存本身由 write_atomic 原子替换。
+pub fn spawn_background(spawn: &dyn Fn() -> io::Result<()>) {
+    let Some(flag) = get_flag_path() else {
+        return;
+    };
+    if let Some(parent) = flag.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+
+    let stale = fs::metadata(&flag)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age > STALE_FLAG);
+    if stale {
+        let _ = fs::remove_file(&flag);
+    }
+    if OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&flag)
+        .is_err()
+    {
+        // 已有刷新在进行
+        return;
+    }
+
+    match spawn() {
+        Ok(_) => {
+            if env::var("YESCODE_DEBUG").is_ok() {
+                eprintln!("[DEBUG] Refreshing quota cache in the background");
+            }
+        }
+        Err(e) => {
+            if env::var("YESCODE_DEBUG").is_ok() {
+                eprintln!("[DEBUG] Failed to start background quota refresh: {}", e);
+            }
+            finish();
+        }
+    }
+}
+
+/// 默认方式：启动一个脱离当前进程的 `quota --refresh`
+pub fn spawn_refresh_process() -> io::Result<()> {
+    let mut command = Command::new(env::current_exe()?);
+    command
+        .args(["quota", "--refresh"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // 独立进程组，Claude Code 结束本次渲染时不会一并杀掉刷新进程
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    command.spawn().map(|_| ())
+}
+
+/// 刷新进程结束时删除标记文件
+pub fn finish() {
+    if let Some(flag) = get_flag_path() {
+        let _ = fs::remove_file(flag);
+    }
+}
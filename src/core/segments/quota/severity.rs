@@ -0,0 +1,174 @@
+use std::env;
+
+/// 周额度使用程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Ok,
+    Warning,
+    Critical,
+    OverLimit,
+}
+
+/// 默认阈值（周限制的百分比）
+pub const DEFAULT_WARNING_PERCENT: f64 = 75.0;
+pub const DEFAULT_CRITICAL_PERCENT: f64 = 90.0;
+const TOLERANCE_EPSILON: f64 = 1e-9;
+
+/// Warning / Critical 的起点，以周已用占周限制的比例表示
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    warning: f64,
+    critical: f64,
+}
+
+impl Thresholds {
+    /// 由百分比构造；不满足 0 < warning <= critical 时回退到默认阈值
+    pub fn from_percent(warning: f64, critical: f64) -> Self {
+        if warning > 0.0 && warning <= critical && critical.is_finite() {
+            return Self {
+                warning: warning / 100.0,
+                critical: critical / 100.0,
+            };
+        }
+
+        if env::var("YESCODE_DEBUG").is_ok() {
+            eprintln!(
+                "[DEBUG] Invalid quota thresholds {}/{}, using defaults",
+                warning, critical
+            );
+        }
+        Self::default()
+    }
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            warning: DEFAULT_WARNING_PERCENT / 100.0,
+            critical: DEFAULT_CRITICAL_PERCENT / 100.0,
+        }
+    }
+}
+
+impl Severity {
+    /// 由周已用 / 周限制计算；没有限制（<= 0）时视为正常。
+    /// 超出周限制不超过 tolerance 时按 Critical 处理，避免浮点误差误报超额
+    pub fn from_weekly(
+        weekly_spent: f64,
+        weekly_limit: f64,
+        tolerance: f64,
+        thresholds: Thresholds,
+    ) -> Self {
+        if weekly_limit <= 0.0 {
+            return Severity::Ok;
+        }
+
+        let ratio = weekly_spent / weekly_limit;
+        // 比较本身也有浮点误差：恰好等于容差时不算超额
+        if weekly_spent - weekly_limit - tolerance.max(0.0) > TOLERANCE_EPSILON {
+            Severity::OverLimit
+        } else if ratio >= thresholds.critical {
+            Severity::Critical
+        } else if ratio >= thresholds.warning {
+            Severity::Warning
+        } else {
+            Severity::Ok
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Ok => "ok",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+            Severity::OverLimit => "over_limit",
+        }
+    }
+
+    /// 周额度文字的颜色：正常时不覆盖配置的颜色，接近上限变黄，临界或超额变红
+    pub fn week_color(&self) -> Option<&'static str> {
+        match self {
+            Severity::Ok => None,
+            Severity::Warning => Some("yellow"),
+            Severity::Critical | Severity::OverLimit => Some("red"),
+        }
+    }
+
+    /// 单字符模式下圆点的颜色
+    pub fn dot_color(&self) -> &'static str {
+        match self {
+            Severity::Ok => "green",
+            Severity::Warning | Severity::Critical => "yellow",
+            Severity::OverLimit => "red",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn severity(weekly_spent: f64, tolerance: f64) -> Severity {
+        Severity::from_weekly(weekly_spent, 100.0, tolerance, Thresholds::default())
+    }
+
+    #[test]
+    fn default_thresholds() {
+        assert_eq!(severity(74.99, 0.01), Severity::Ok);
+        assert_eq!(severity(75.0, 0.01), Severity::Warning);
+        assert_eq!(severity(90.0, 0.01), Severity::Critical);
+        assert_eq!(severity(100.0, 0.01), Severity::Critical);
+    }
+
+    #[test]
+    fn rounding_within_the_tolerance_is_not_over_limit() {
+        assert_eq!(severity(100.001, 0.01), Severity::Critical);
+        assert_eq!(severity(100.01, 0.01), Severity::Critical);
+    }
+
+    #[test]
+    fn spend_beyond_the_tolerance_is_over_limit() {
+        assert_eq!(severity(100.02, 0.01), Severity::OverLimit);
+        assert_eq!(severity(100.001, 0.0), Severity::OverLimit);
+        // 负的容差按 0 处理
+        assert_eq!(severity(100.001, -1.0), Severity::OverLimit);
+        assert_eq!(severity(120.0, 5.0), Severity::OverLimit);
+    }
+
+    #[test]
+    fn no_limit_is_always_ok() {
+        assert_eq!(
+            Severity::from_weekly(500.0, 0.0, 0.01, Thresholds::default()),
+            Severity::Ok
+        );
+    }
+
+    #[test]
+    fn custom_thresholds_and_invalid_fallback() {
+        let custom = Thresholds::from_percent(50.0, 80.0);
+        assert_eq!(
+            Severity::from_weekly(50.0, 100.0, 0.0, custom),
+            Severity::Warning
+        );
+        assert_eq!(
+            Severity::from_weekly(80.0, 100.0, 0.0, custom),
+            Severity::Critical
+        );
+
+        assert_eq!(Thresholds::from_percent(90.0, 75.0), Thresholds::default());
+        assert_eq!(Thresholds::from_percent(0.0, 75.0), Thresholds::default());
+        assert_eq!(
+            Thresholds::from_percent(50.0, f64::INFINITY),
+            Thresholds::default()
+        );
+    }
+
+    #[test]
+    fn colors_per_severity() {
+        assert_eq!(Severity::Ok.week_color(), None);
+        assert_eq!(Severity::Warning.week_color(), Some("yellow"));
+        assert_eq!(Severity::OverLimit.week_color(), Some("red"));
+        assert_eq!(Severity::Critical.dot_color(), "yellow");
+        assert_eq!(Severity::OverLimit.dot_color(), "red");
+    }
+}
@@ -0,0 +1,220 @@
+use super::cache::QuotaSnapshot;
+use super::history;
+use super::money::format_money;
+use super::options::WeekDefinition;
+use chrono::{DateTime, Local, Utc};
+
+/// 周额度进度条宽度（字符数）
+const BAR_WIDTH: usize = 20;
+
+/// `ccline quota --summary` 的单屏报告
+pub fn render_summary(
+    snapshot: &QuotaSnapshot,
+    currency: &str,
+    week: WeekDefinition,
+    now: DateTime<Local>,
+    reset: Option<DateTime<Utc>>,
+) -> String {
+    let balance = &snapshot.balance;
+    let today = now.date_naive();
+    let days = snapshot
+        .daily_usage
+        .as_ref()
+        .map(history::coalesce_daily_usage);
+
+    let today_spent = days
+        .as_ref()
+        .map(|days| days.get(&today).copied().unwrap_or(0.0));
+    let week_to_date = days
+        .as_ref()
+        .map(|days| history::week_to_date_spend(days, today, week));
+    let month_to_date = days
+        .as_ref()
+        .map(|days| history::month_to_date_spend(days, today));
+    let projection = month_to_date.and_then(|spent| history::project_month_end(spent, today));
+    let average = days
+        .as_ref()
+        .map(|days| history::average_daily_spend_this_week(days, today, week));
+
+    let mut lines = vec![
+        format!("Today        {}", money_or_na(today_spent, currency)),
+        format!(
+            "Balance      {}",
+            format_balance(balance.total_balance, currency)
+        ),
+        if balance.weekly_limit > 0.0 {
+            format!(
+                "Week         {} / {}  {}",
+                format_money(balance.weekly_spent_balance, 2, currency),
+                format_money(balance.weekly_limit, 0, currency),
+                limit_bar(balance.weekly_spent_balance, balance.weekly_limit)
+            )
+        } else {
+            format!(
+                "Week         {}  (unlimited)",
+                format_money(balance.weekly_spent_balance, 2, currency)
+            )
+        },
+        match week {
+            WeekDefinition::Rolling => {
+                format!("Last 7 days  {}", money_or_na(week_to_date, currency))
+            }
+            WeekDefinition::Iso | WeekDefinition::Us => {
+                format!("This week    {}", money_or_na(week_to_date, currency))
+            }
+        },
+    ];
+
+    if let Some(limit) = balance.daily_limit.filter(|limit| *limit > 0.0) {
+        let spent = balance.daily_spent_balance.or(today_spent).unwrap_or(0.0);
+        lines.insert(
+            2,
+            format!(
+                "Day          {} / {}  {}",
+                format_money(spent, 2, currency),
+                format_money(limit, 0, currency),
+                limit_bar(spent, limit)
+            ),
+        );
+    }
+
+    let mut month_line = format!("Month        {}", money_or_na(month_to_date, currency));
+    if let Some(projection) = projection {
+        month_line.push_str(&format!(
+            " (proj {})",
+            format_money(projection, 0, currency)
+        ));
+    }
+    lines.push(month_line);
+
+    lines.push(match average {
+        Some(average) => format!("Average      {}/day", format_money(average, 2, currency)),
+        None => "Average      n/a".to_string(),
+    });
+
+    if let Some(reset) = reset {
+        lines.push(format!(
+            "Resets in    {}",
+            format_countdown(reset.signed_duration_since(now))
+        ));
+    }
+
+    lines.join("\n")
+}
+
+fn money_or_na(amount: Option<f64>, currency: &str) -> String {
+    match amount {
+        Some(amount) => format_money(amount, 2, currency),
+        None => "n/a".to_string(),
+    }
+}
+
+fn format_balance(total: f64, currency: &str) -> String {
+    if total < 0.0 {
+        format!("\u{2212}{} (due)", format_money(-total, 2, currency))
+    } else {
+        format_money(total, 2, currency)
+    }
+}
+
+/// 例：`[█████░░░░░░░░░░░░░░░] 25%`；没有限额时只显示 `no limit`
+fn limit_bar(spent: f64, limit: f64) -> String {
+    if limit <= 0.0 {
+        return "no limit".to_string();
+    }
+    let ratio = spent / limit;
+    let filled = ((ratio.clamp(0.0, 1.0) * BAR_WIDTH as f64).round() as usize).min(BAR_WIDTH);
+    format!(
+        "[{}{}] {:.0}%",
+        "\u{2588}".repeat(filled),
+        "\u{2591}".repeat(BAR_WIDTH - filled),
+        ratio * 100.0
+    )
+}
+
+fn format_countdown(remaining: chrono::Duration) -> String {
+    let minutes = remaining.num_minutes().max(0);
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn sample_snapshot() -> QuotaSnapshot {
+        QuotaSnapshot {
+            daily_usage: Some(
+                serde_json::from_value(serde_json::json!({ "daily_usage": [
+                    { "date": "2026-09-30", "total_cost": 10.0 },
+                    { "date": "2026-10-01", "total_cost": 4.0 },
+                    { "date": "2026-10-12", "total_cost": 2.0 },
+                    { "date": "2026-10-13", "total_cost": 3.0 },
+                    { "date": "2026-10-14", "total_cost": 1.5 },
+                ] }))
+                .unwrap(),
+            ),
+            balance: serde_json::from_value(serde_json::json!({
+                "balance": 42.5,
+                "pay_as_you_go_balance": 0.0,
+                "subscription_balance": 42.5,
+                "total_balance": 42.5,
+                "weekly_limit": 100.0,
+                "weekly_spent_balance": 25.0,
+            }))
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn renders_the_sample_dataset() {
+        // 2026-10-14 是周三
+        let now = Local.with_ymd_and_hms(2026, 10, 14, 12, 0, 0).unwrap();
+        let reset = now.with_timezone(&Utc) + chrono::Duration::hours(108);
+        let summary = render_summary(
+            &sample_snapshot(),
+            "$",
+            WeekDefinition::Iso,
+            now,
+            Some(reset),
+        );
+        assert_eq!(
+            summary,
+            [
+                "Today        $1.50".to_string(),
+                "Balance      $42.50".to_string(),
+                format!(
+                    "Week         $25.00 / $100  [{}{}] 25%",
+                    "\u{2588}".repeat(5),
+                    "\u{2591}".repeat(15)
+                ),
+                "This week    $6.50".to_string(),
+                "Month        $10.50 (proj $23)".to_string(),
+                "Average      $2.17/day".to_string(),
+                "Resets in    4d 12h".to_string(),
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn missing_history_is_shown_as_na() {
+        let snapshot = QuotaSnapshot {
+            daily_usage: None,
+            ..sample_snapshot()
+        };
+        let now = Local.with_ymd_and_hms(2026, 10, 14, 12, 0, 0).unwrap();
+        let summary = render_summary(&snapshot, "$", WeekDefinition::Rolling, now, None);
+        assert!(summary.contains("Today        n/a"));
+        assert!(summary.contains("Last 7 days  n/a"));
+        assert!(summary.contains("Average      n/a"));
+        assert!(!summary.contains("Resets in"));
+    }
+}
@@ -0,0 +1,128 @@
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, SignatureScheme};
+use std::sync::Arc;
+
+/// `YESCODE_INSECURE_TLS=1` 时跳过证书校验（仅用于自签名证书的企业代理）
+fn insecure_tls_requested() -> bool {
+    matches!(
+        std::env::var("YESCODE_INSECURE_TLS").as_deref(),
+        Ok("1") | Ok("true") | Ok("yes")
+    )
+}
+
+/// agent 要替换的 TLS 配置：只有显式设置 YESCODE_INSECURE_TLS 时才有，否则保持默认的完整校验
+pub fn tls_config_override() -> Option<Arc<ClientConfig>> {
+    if !insecure_tls_requested() {
+        return None;
+    }
+    if std::env::var("YESCODE_DEBUG").is_ok() {
+        eprintln!(
+            "[DEBUG] WARNING: YESCODE_INSECURE_TLS is set, TLS certificates are NOT verified"
+        );
+    }
+    Some(insecure_client_config())
+}
+
+/// 不校验服务器证书的 TLS 配置；握手签名仍按正常算法校验
+fn insecure_client_config() -> Arc<ClientConfig> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .expect("ring provider supports the default protocol versions")
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(NoCertificateVerification(provider)))
+        .with_no_client_auth();
+    Arc::new(config)
+}
+
+#[derive(Debug)]
+struct NoCertificateVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for NoCertificateVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestEnv;
+
+    #[test]
+    fn certificates_are_verified_by_default() {
+        let _env = TestEnv::new();
+        assert!(tls_config_override().is_none());
+    }
+
+    #[test]
+    fn insecure_tls_needs_an_explicit_opt_in() {
+        let env = TestEnv::new();
+        for value in ["1", "true", "yes"] {
+            env.set("YESCODE_INSECURE_TLS", value);
+            assert!(tls_config_override().is_some(), "{}", value);
+        }
+        for value in ["0", "false", ""] {
+            env.set("YESCODE_INSECURE_TLS", value);
+            assert!(tls_config_override().is_none(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn insecure_config_skips_certificate_checks() {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let verifier = NoCertificateVerification(provider);
+        let server_name = ServerName::try_from("co.yes.vg").unwrap();
+        assert!(verifier
+            .verify_server_cert(
+                &CertificateDer::from(vec![0u8; 4]),
+                &[],
+                &server_name,
+                &[],
+                UnixTime::now(),
+            )
+            .is_ok());
+        assert!(!verifier.supported_verify_schemes().is_empty());
+    }
+}
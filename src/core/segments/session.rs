@@ -1,6 +1,6 @@
 use super::{Segment, SegmentData};
 use crate::config::{InputData, SegmentId};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 #[derive(Default)]
 pub struct SessionSegment;
@@ -61,7 +61,7 @@ impl Segment for SessionSegment {
             _ => String::new(),
         };
 
-        let mut metadata = HashMap::new();
+        let mut metadata = BTreeMap::new();
         if let Some(duration) = cost_data.total_duration_ms {
             metadata.insert("duration_ms".to_string(), duration.to_string());
         }
@@ -0,0 +1,188 @@
+use super::{Segment, SegmentData};
+use crate::config::{InputData, SegmentId, TranscriptEntry};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, BufReader};
+
+/// The session's total cost and where the figure came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionCost {
+    pub total_usd: f64,
+    pub source: CostSource,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostSource {
+    /// `cost.total_cost_usd` in Claude Code's input
+    Input,
+    /// Sum of per-message `costUSD` in the transcript
+    Transcript,
+}
+
+impl CostSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CostSource::Input => "input",
+            CostSource::Transcript => "transcript",
+        }
+    }
+}
+
+impl SessionCost {
+    /// Prefer the authoritative session total from Claude Code's input,
+    /// fall back to summing per-message costs from the transcript
+    pub fn from_input(input: &InputData) -> Option<Self> {
+        if let Some(total_usd) = input.cost.as_ref().and_then(|c| c.total_cost_usd) {
+            return Some(Self {
+                total_usd,
+                source: CostSource::Input,
+            });
+        }
+        sum_transcript_cost(&input.transcript_path).map(|total_usd| Self {
+            total_usd,
+            source: CostSource::Transcript,
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct SessionCostSegment {
+    /// Cost already computed for this render; `None` computes it in `collect`
+    cost: Option<Option<SessionCost>>,
+}
+
+impl SessionCostSegment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reuse a cost computed earlier in the render instead of scanning the transcript again
+    pub fn with_cost(mut self, cost: Option<SessionCost>) -> Self {
+        self.cost = Some(cost);
+        self
+    }
+}
+
+impl Segment for SessionCostSegment {
+    fn collect(&self, input: &InputData) -> Option<SegmentData> {
+        let cost = match self.cost {
+            Some(cost) => cost,
+            None => SessionCost::from_input(input),
+        }?;
+
+        let primary = if cost.total_usd < 0.01 {
+            "$0".to_string()
+        } else {
+            format!("${:.2}", cost.total_usd)
+        };
+
+        let mut metadata = BTreeMap::new();
+        metadata.insert("cost".to_string(), cost.total_usd.to_string());
+        metadata.insert("source".to_string(), cost.source.as_str().to_string());
+
+        Some(SegmentData {
+            primary,
+            secondary: String::new(),
+            metadata,
+        })
+    }
+
+    fn id(&self) -> SegmentId {
+        SegmentId::SessionCost
+    }
+}
+
+/// Sum `costUSD` over all transcript entries; None if the transcript has no cost data
+fn sum_transcript_cost(transcript_path: &str) -> Option<f64> {
+    let file = fs::File::open(transcript_path).ok()?;
+    let reader = BufReader::new(file);
+
+    let costs: Vec<f64> = reader
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<TranscriptEntry>(line.trim()).ok())
+        .filter_map(|entry| entry.cost_usd)
+        .collect();
+
+    if costs.is_empty() {
+        None
+    } else {
+        Some(costs.iter().sum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestEnv;
+
+    fn input(value: serde_json::Value) -> InputData {
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// A transcript whose entries record 0.25 + 0.5 in `costUSD`
+    fn transcript(env: &TestEnv) -> String {
+        let path = env.home().join("transcript.jsonl");
+        fs::write(
+            &path,
+            [
+                r#"{"type":"user","uuid":"a"}"#,
+                r#"{"type":"assistant","uuid":"b","costUSD":0.25}"#,
+                "not json",
+                r#"{"type":"assistant","uuid":"c","costUSD":0.5}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn input_cost_wins_over_the_transcript() {
+        let env = TestEnv::new();
+        let input = input(serde_json::json!({
+            "transcript_path": transcript(&env),
+            "cost": { "total_cost_usd": 1.234 },
+        }));
+        let data = SessionCostSegment::new().collect(&input).unwrap();
+        assert_eq!(data.primary, "$1.23");
+        assert_eq!(data.metadata["cost"], "1.234");
+        assert_eq!(data.metadata["source"], "input");
+    }
+
+    #[test]
+    fn falls_back_to_the_transcript_without_input_cost() {
+        let env = TestEnv::new();
+        let input = input(serde_json::json!({ "transcript_path": transcript(&env) }));
+        let data = SessionCostSegment::new().collect(&input).unwrap();
+        assert_eq!(data.primary, "$0.75");
+        assert_eq!(data.metadata["source"], "transcript");
+    }
+
+    #[test]
+    fn hidden_without_any_cost_data() {
+        let _env = TestEnv::new();
+        let input =
+            input(serde_json::json!({ "transcript_path": "/nonexistent/transcript.jsonl" }));
+        assert!(SessionCostSegment::new().collect(&input).is_none());
+    }
+
+    #[test]
+    fn a_shared_cost_skips_the_transcript() {
+        let env = TestEnv::new();
+        let input = input(serde_json::json!({ "transcript_path": transcript(&env) }));
+        let shared = SessionCost {
+            total_usd: 2.0,
+            source: CostSource::Transcript,
+        };
+        let data = SessionCostSegment::new()
+            .with_cost(Some(shared))
+            .collect(&input)
+            .unwrap();
+        assert_eq!(data.primary, "$2.00");
+        assert!(SessionCostSegment::new()
+            .with_cost(None)
+            .collect(&input)
+            .is_none());
+    }
+}
@@ -0,0 +1,169 @@
+use super::{Segment, SegmentData};
+use crate::config::{InputData, SegmentId};
+use std::collections::BTreeMap;
+use std::env;
+
+/// Renders a user-defined template such as `{model.display_name} @ {workspace.project_dir}`
+///
+/// Placeholders name `InputData` fields; unknown or missing fields render empty.
+/// Use `{{` and `}}` for literal braces.
+#[derive(Default)]
+pub struct StaticSegment {
+    template: String,
+}
+
+impl StaticSegment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_template(mut self, template: impl Into<String>) -> Self {
+        self.template = template.into();
+        self
+    }
+}
+
+impl Segment for StaticSegment {
+    fn collect(&self, input: &InputData) -> Option<SegmentData> {
+        let primary = render_template(&self.template, input);
+        if primary.trim().is_empty() {
+            return None;
+        }
+
+        let mut metadata = BTreeMap::new();
+        metadata.insert("template".to_string(), self.template.clone());
+
+        Some(SegmentData {
+            primary,
+            secondary: String::new(),
+            metadata,
+        })
+    }
+
+    fn id(&self) -> SegmentId {
+        SegmentId::Static
+    }
+}
+
+/// Substitute `{field}` placeholders from the input payload
+pub fn render_template(template: &str, input: &InputData) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(index) = rest.find(['{', '}']) {
+        output.push_str(&rest[..index]);
+        let tail = &rest[index..];
+
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            output.push_str(&tail[..1]);
+            rest = &tail[2..];
+        } else if let (Some(body), Some(end)) = (tail.strip_prefix('{'), tail.find('}')) {
+            output.push_str(&field_value(body[..end - 1].trim(), input));
+            rest = &tail[end + 1..];
+        } else {
+            // Unmatched brace, keep it as written
+            output.push_str(&tail[..1]);
+            rest = &tail[1..];
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+fn field_value(field: &str, input: &InputData) -> String {
+    let cost = input.cost.as_ref();
+    let value = match field {
+        "model.id" => Some(input.model.id.clone()),
+        "model.display_name" => Some(input.model.display_name.clone()),
+        "workspace.current_dir" => Some(input.workspace.current_dir.clone()),
+        "workspace.project_dir" => Some(input.workspace.project_dir.clone()),
+        "transcript_path" => Some(input.transcript_path.clone()),
+        "output_style.name" => input.output_style.as_ref().map(|s| s.name.clone()),
+        "cost.total_cost_usd" => cost
+            .and_then(|c| c.total_cost_usd)
+            .map(|v| format!("{:.2}", v)),
+        "cost.total_duration_ms" => cost
+            .and_then(|c| c.total_duration_ms)
+            .map(|v| v.to_string()),
+        "cost.total_api_duration_ms" => cost
+            .and_then(|c| c.total_api_duration_ms)
+            .map(|v| v.to_string()),
+        "cost.total_lines_added" => cost
+            .and_then(|c| c.total_lines_added)
+            .map(|v| v.to_string()),
+        "cost.total_lines_removed" => cost
+            .and_then(|c| c.total_lines_removed)
+            .map(|v| v.to_string()),
+        _ => {
+            if env::var("YESCODE_DEBUG").is_ok() {
+                eprintln!("[DEBUG] Unknown static template field: {}", field);
+            }
+            None
+        }
+    };
+
+    value.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input() -> InputData {
+        serde_json::from_value(serde_json::json!({
+            "model": { "id": "claude-sonnet-4", "display_name": "Sonnet 4" },
+            "workspace": { "current_dir": "/work/app/src", "project_dir": "/work/app" },
+            "transcript_path": "/tmp/session.jsonl",
+            "cost": { "total_cost_usd": 1.5, "total_lines_added": 12 },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn substitutes_input_fields() {
+        let input = input();
+        assert_eq!(
+            render_template("{model.display_name} @ {workspace.project_dir}", &input),
+            "Sonnet 4 @ /work/app"
+        );
+        assert_eq!(
+            render_template("${ cost.total_cost_usd } +{cost.total_lines_added}", &input),
+            "$1.50 +12"
+        );
+    }
+
+    #[test]
+    fn missing_and_unknown_fields_render_empty() {
+        let input = input();
+        assert_eq!(render_template("[{output_style.name}]", &input), "[]");
+        assert_eq!(render_template("[{cost.total_duration_ms}]", &input), "[]");
+        assert_eq!(render_template("a{no.such.field}b", &input), "ab");
+    }
+
+    #[test]
+    fn escaped_and_unmatched_braces_are_literal() {
+        let input = input();
+        assert_eq!(render_template("{{model.id}}", &input), "{model.id}");
+        assert_eq!(
+            render_template("{{{model.id}}}", &input),
+            "{claude-sonnet-4}"
+        );
+        assert_eq!(render_template("open { end", &input), "open { end");
+        assert_eq!(render_template("close } end", &input), "close } end");
+    }
+
+    #[test]
+    fn segment_hides_when_the_template_renders_blank() {
+        let input = input();
+        let data = StaticSegment::new()
+            .with_template("{model.id}")
+            .collect(&input)
+            .unwrap();
+        assert_eq!(data.primary, "claude-sonnet-4");
+        assert_eq!(data.metadata["template"], "{model.id}");
+
+        let blank = StaticSegment::new().with_template(" {output_style.name} ");
+        assert!(blank.collect(&input).is_none());
+    }
+}
@@ -3,7 +3,7 @@ use crate::config::{InputData, SegmentId};
 use crate::utils::credentials;
 use chrono::{DateTime, Datelike, Duration, Local, Timelike, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 #[derive(Debug, Deserialize)]
 struct ApiUsageResponse {
@@ -52,7 +52,7 @@ impl UsageSegment {
             if let Ok(dt) = DateTime::parse_from_rfc3339(time_str) {
                 let mut local_dt = dt.with_timezone(&Local);
                 if local_dt.minute() > 45 {
-                    local_dt = local_dt + Duration::hours(1);
+                    local_dt += Duration::hours(1);
                 }
                 return format!(
                     "{}-{}-{}",
@@ -209,49 +209,51 @@ impl Segment for UsageSegment {
             .map(|cache| self.is_cache_valid(cache, cache_duration))
             .unwrap_or(false);
 
-        let (five_hour_util, seven_day_util, resets_at) = if use_cached {
-            let cache = cached_data.unwrap();
-            (
-                cache.five_hour_utilization,
-                cache.seven_day_utilization,
-                cache.resets_at,
-            )
-        } else {
-            match self.fetch_api_usage(api_base_url, &token, timeout) {
-                Some(response) => {
-                    let cache = ApiUsageCache {
-                        five_hour_utilization: response.five_hour.utilization,
-                        seven_day_utilization: response.seven_day.utilization,
-                        resets_at: response.seven_day.resets_at.clone(),
-                        cached_at: Utc::now().to_rfc3339(),
-                    };
-                    self.save_cache(&cache);
-                    (
-                        response.five_hour.utilization,
-                        response.seven_day.utilization,
-                        response.seven_day.resets_at,
-                    )
-                }
-                None => {
-                    if let Some(cache) = cached_data {
+        // Offline mode serves whatever is cached, however old
+        let (five_hour_util, seven_day_util, resets_at) =
+            if use_cached || crate::utils::network::is_offline() {
+                let cache = cached_data?;
+                (
+                    cache.five_hour_utilization,
+                    cache.seven_day_utilization,
+                    cache.resets_at,
+                )
+            } else {
+                match self.fetch_api_usage(api_base_url, &token, timeout) {
+                    Some(response) => {
+                        let cache = ApiUsageCache {
+                            five_hour_utilization: response.five_hour.utilization,
+                            seven_day_utilization: response.seven_day.utilization,
+                            resets_at: response.seven_day.resets_at.clone(),
+                            cached_at: Utc::now().to_rfc3339(),
+                        };
+                        self.save_cache(&cache);
                         (
-                            cache.five_hour_utilization,
-                            cache.seven_day_utilization,
-                            cache.resets_at,
+                            response.five_hour.utilization,
+                            response.seven_day.utilization,
+                            response.seven_day.resets_at,
                         )
-                    } else {
-                        return None;
+                    }
+                    None => {
+                        if let Some(cache) = cached_data {
+                            (
+                                cache.five_hour_utilization,
+                                cache.seven_day_utilization,
+                                cache.resets_at,
+                            )
+                        } else {
+                            return None;
+                        }
                     }
                 }
-            }
-        };
+            };
 
         let dynamic_icon = Self::get_circle_icon(seven_day_util / 100.0);
         let five_hour_percent = five_hour_util.round() as u8;
         let primary = format!("{}%", five_hour_percent);
         let secondary = format!("· {}", Self::format_reset_time(resets_at.as_deref()));
 
-        let mut metadata = HashMap::new();
+        let mut metadata = BTreeMap::new();
         metadata.insert("dynamic_icon".to_string(), dynamic_icon);
         metadata.insert(
             "five_hour_utilization".to_string(),
@@ -1,5 +1,6 @@
-use crate::config::{AnsiColor, Config, SegmentConfig, StyleMode};
+use crate::config::{AnsiColor, Config, LineSeparator, SegmentConfig, SegmentId, StyleMode};
 use crate::core::segments::SegmentData;
+use std::collections::HashMap;
 
 /// Strip ANSI escape sequences and return visible text length
 fn visible_width(text: &str) -> usize {
@@ -8,7 +9,15 @@ fn visible_width(text: &str) -> usize {
     let mut chars = text.chars().peekable();
 
     while let Some(ch) = chars.next() {
-        if ch == '\x1b' {
+        if ch == '\x1b' && chars.peek() == Some(&']') {
+            // OSC sequence (e.g. hyperlinks): skip until ST (ESC \) or BEL
+            chars.next();
+            while let Some(osc) = chars.next() {
+                if osc == '\x07' || (osc == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                    break;
+                }
+            }
+        } else if ch == '\x1b' {
             // Start of ANSI escape sequence
             in_escape = true;
             // Skip the [ character
@@ -29,6 +38,155 @@ fn visible_width(text: &str) -> usize {
     visible.chars().count()
 }
 
+/// Remove color and style escape sequences (CSI) and OSC 8 hyperlinks, keeping the text
+fn strip_escapes(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' && chars.next_if_eq(&'[').is_some() {
+            // Parameters and intermediates run until the final byte (@ to ~)
+            for code in chars.by_ref() {
+                if ('@'..='~').contains(&code) {
+                    break;
+                }
+            }
+        } else if ch == '\x1b' && chars.next_if_eq(&']').is_some() {
+            // OSC runs until BEL or the string terminator ESC \
+            while let Some(code) = chars.next() {
+                if code == '\x07' || (code == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                    break;
+                }
+            }
+        } else {
+            plain.push(ch);
+        }
+    }
+    plain
+}
+
+/// Remove trailing whitespace while keeping trailing escape sequences (CSI and OSC).
+/// Spaces painted with a background color (Powerline padding) are visible
+/// and therefore kept.
+fn trim_trailing_whitespace(line: &str) -> String {
+    enum Token<'a> {
+        Text(char, bool),
+        Escape(&'a str),
+    }
+
+    let mut tokens = Vec::new();
+    let mut background = false;
+    let mut rest = line;
+    while let Some(ch) = rest.chars().next() {
+        if let Some(sequence) = rest.strip_prefix("\x1b[") {
+            let len = sequence
+                .find(|c: char| c.is_ascii_alphabetic())
+                .map(|i| i + 3)
+                .unwrap_or(rest.len());
+            let escape = &rest[..len];
+            if escape.ends_with('m') {
+                let codes: Vec<u32> = escape[2..len - 1]
+                    .split(';')
+                    .map(|code| code.parse().unwrap_or(0))
+                    .collect();
+                let mut i = 0;
+                while i < codes.len() {
+                    match codes[i] {
+                        0 | 49 => background = false,
+                        40..=47 | 100..=107 => background = true,
+                        code @ (38 | 48) => {
+                            background |= code == 48;
+                            // Skip the `5;n` or `2;r;g;b` color arguments
+                            i += if codes.get(i + 1) == Some(&2) { 4 } else { 2 };
+                        }
+                        _ => {}
+                    }
+                    i += 1;
+                }
+            }
+            tokens.push(Token::Escape(escape));
+            rest = &rest[len..];
+        } else if let Some(sequence) = rest.strip_prefix("\x1b]") {
+            // OSC (e.g. OSC 8 hyperlinks) runs until ST (`ESC \`) or BEL and is zero-width
+            let len = match (sequence.find("\x1b\\"), sequence.find('\x07')) {
+                (Some(st), Some(bel)) if bel < st => bel + 3,
+                (Some(st), _) => st + 4,
+                (None, Some(bel)) => bel + 3,
+                (None, None) => rest.len(),
+            };
+            tokens.push(Token::Escape(&rest[..len]));
+            rest = &rest[len..];
+        } else {
+            tokens.push(Token::Text(ch, background));
+            rest = &rest[ch.len_utf8()..];
+        }
+    }
+
+    let mut trailing_escapes = Vec::new();
+    while let Some(token) = tokens.last() {
+        match token {
+            Token::Escape(escape) => trailing_escapes.push(*escape),
+            Token::Text(ch, false) if ch.is_whitespace() => {}
+            Token::Text(..) => break,
+        }
+        tokens.pop();
+    }
+
+    let mut trimmed = String::with_capacity(line.len());
+    for token in &tokens {
+        match token {
+            Token::Text(ch, _) => trimmed.push(*ch),
+            Token::Escape(escape) => trimmed.push_str(escape),
+        }
+    }
+    for escape in trailing_escapes.iter().rev() {
+        trimmed.push_str(escape);
+    }
+    trimmed
+}
+
+/// Describe what width truncation cut, for `YESCODE_DEBUG`
+fn drop_log(max_width: usize, dropped: &[SegmentId]) -> String {
+    let names: Vec<String> = dropped.iter().map(|id| format!("{:?}", id)).collect();
+    format!(
+        "Width budget {}: dropped {} segment(s): {}",
+        max_width,
+        dropped.len(),
+        names.join(", ")
+    )
+}
+
+/// The two lines produced when `info_line` is enabled
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatusLines {
+    pub primary: String,
+    pub info: String,
+}
+
+/// Join the primary and info lines; the separator only appears when both have content
+fn join_lines(lines: &StatusLines, separator: LineSeparator) -> String {
+    if lines.info.is_empty() {
+        return lines.primary.clone();
+    }
+    if lines.primary.is_empty() {
+        return lines.info.clone();
+    }
+
+    let rule_width = visible_width(&lines.primary).max(visible_width(&lines.info));
+    let divider = match separator {
+        LineSeparator::None => return format!("{}\n{}", lines.primary, lines.info),
+        LineSeparator::Blank => String::new(),
+        LineSeparator::Thin => "\u{2500}".repeat(rule_width),
+        LineSeparator::Thick => "\u{2501}".repeat(rule_width),
+    };
+    format!("{}\n{}\n{}", lines.primary, divider, lines.info)
+}
+
+/// Whether a segment option targets the info line (`"info"`)
+fn is_info_line(config: &SegmentConfig, option: &str) -> bool {
+    config.options.get(option).and_then(|v| v.as_str()) == Some("info")
+}
+
 pub struct StatusLineGenerator {
     config: Config,
 }
@@ -40,6 +198,7 @@ impl StatusLineGenerator {
 
     pub fn generate(&self, segments: Vec<(SegmentConfig, SegmentData)>) -> String {
         let mut output = Vec::new();
+        let mut output_ids = Vec::new();
         let enabled_segments: Vec<_> = segments
             .into_iter()
             .filter(|(config, _)| config.enabled)
@@ -49,6 +208,7 @@ impl StatusLineGenerator {
             let rendered = self.render_segment(config, data);
             if !rendered.is_empty() {
                 output.push(rendered);
+                output_ids.push(config.id);
             }
         }
 
@@ -56,12 +216,124 @@ impl StatusLineGenerator {
             return String::new();
         }
 
+        if self.config.style.truncate {
+            let max_width = crate::utils::terminal::resolve_width(self.config.style.max_width);
+            let dropped =
+                self.truncate_to_width(&mut output, &output_ids, &enabled_segments, max_width);
+            if !dropped.is_empty() && std::env::var("YESCODE_DEBUG").is_ok() {
+                eprintln!("[DEBUG] {}", drop_log(max_width, &dropped));
+            }
+        }
+
+        let mut line = self.join_segments(&output, &enabled_segments);
+        if self.config.style.trim_trailing_whitespace {
+            line = trim_trailing_whitespace(&line);
+        }
+        self.apply_trailing_reset(line)
+    }
+
+    /// Render the final output: one line, or the primary and info lines when `info_line` is set
+    /// Colors are stripped when `should_colorize` says the reader can't or doesn't want them
+    pub fn generate_output(&self, segments: Vec<(SegmentConfig, SegmentData)>) -> String {
+        let output = if self.config.style.info_line {
+            let lines = self.generate_lines(segments);
+            join_lines(&lines, self.config.style.line_separator)
+        } else {
+            self.generate(segments)
+        };
+
+        if crate::utils::terminal::should_colorize() {
+            output
+        } else {
+            strip_escapes(&output)
+        }
+    }
+
+    /// Split segments between the primary and info lines and render each.
+    /// `line = "info"` moves a whole segment; `secondary_line = "info"` moves only
+    /// its secondary text (e.g. quota's weekly block) while the primary text stays.
+    pub fn generate_lines(&self, segments: Vec<(SegmentConfig, SegmentData)>) -> StatusLines {
+        let mut primary = Vec::new();
+        let mut info = Vec::new();
+
+        for (config, data) in segments {
+            if is_info_line(&config, "line") {
+                info.push((config, data));
+            } else if is_info_line(&config, "secondary_line") && !data.secondary.is_empty() {
+                let mut info_data = SegmentData {
+                    primary: data.secondary.clone(),
+                    secondary: String::new(),
+                    metadata: data.metadata.clone(),
+                };
+                if let Some(color) = data.metadata.get("secondary_color") {
+                    info_data
+                        .metadata
+                        .insert("text_color".to_string(), color.clone());
+                }
+                let primary_data = SegmentData {
+                    secondary: String::new(),
+                    ..data
+                };
+                info.push((config.clone(), info_data));
+                primary.push((config, primary_data));
+            } else {
+                primary.push((config, data));
+            }
+        }
+
+        StatusLines {
+            primary: self.generate(primary),
+            info: self.generate(info),
+        }
+    }
+
+    /// Drop segments from the right until the line fits; returns the dropped ids, rightmost first
+    fn truncate_to_width(
+        &self,
+        output: &mut Vec<String>,
+        output_ids: &[SegmentId],
+        enabled_segments: &[(SegmentConfig, SegmentData)],
+        max_width: usize,
+    ) -> Vec<SegmentId> {
+        let mut dropped = Vec::new();
+        while output.len() > 1
+            && visible_width(&self.join_segments(output, enabled_segments)) > max_width
+        {
+            output.pop();
+            if let Some(id) = output_ids.get(output.len()) {
+                dropped.push(*id);
+            }
+        }
+        dropped
+    }
+
+    /// Make the line end with exactly one reset when colored, or none when disabled
+    fn apply_trailing_reset(&self, mut line: String) -> String {
+        const RESET: &str = "\x1b[0m";
+
+        if self.config.style.emit_trailing_reset {
+            if line.contains('\x1b') && !line.ends_with(RESET) {
+                line.push_str(RESET);
+            }
+        } else {
+            while line.ends_with(RESET) {
+                line.truncate(line.len() - RESET.len());
+            }
+        }
+        line
+    }
+
+    fn join_segments(
+        &self,
+        output: &[String],
+        enabled_segments: &[(SegmentConfig, SegmentData)],
+    ) -> String {
         // Handle Powerline arrow separators with color transition
         if self.config.style.separator == "\u{e0b0}" {
-            self.join_with_powerline_arrows(&output, &enabled_segments)
+            self.join_with_powerline_arrows(output, enabled_segments)
         } else {
             // For all other separators, use white color and simple join
-            self.join_with_white_separators(&output)
+            self.join_with_white_separators(output)
         }
     }
 
@@ -129,10 +401,10 @@ impl StatusLineGenerator {
                 // Powerline arrows with color transition
                 let prev_bg = segment_configs
                     .get(i)
-                    .and_then(|config| config.colors.background.as_ref());
+                    .and_then(|config| self.segment_background(config));
                 let curr_bg = segment_configs
                     .get(i + 1)
-                    .and_then(|config| config.colors.background.as_ref());
+                    .and_then(|config| self.segment_background(config));
                 self.create_powerline_arrow(prev_bg, curr_bg)
             } else {
                 // Regular separators with white color
@@ -214,15 +486,65 @@ impl StatusLineGenerator {
         Text::from(tui_lines)
     }
 
+    /// Powerline styles (the Powerline mode or `\u{e0b0}` arrows) fill segments with
+    /// their background color; other styles ignore it so plain lines stay unpainted
+    fn uses_backgrounds(&self) -> bool {
+        self.config.style.mode == StyleMode::Powerline || self.config.style.separator == "\u{e0b0}"
+    }
+
+    fn segment_background<'a>(&self, config: &'a SegmentConfig) -> Option<&'a AnsiColor> {
+        config
+            .colors
+            .background
+            .as_ref()
+            .filter(|_| self.uses_backgrounds())
+    }
+
     fn render_segment(&self, config: &SegmentConfig, data: &SegmentData) -> String {
+        let rendered = self.render_segment_content(config, data);
+
+        // Optional OSC 8 link around the whole segment (`hyperlink` option), only
+        // alongside colors: plain output carries no escape sequences at all
+        match config.options.get("hyperlink").and_then(|v| v.as_str()) {
+            Some(url)
+                if !url.is_empty()
+                    && crate::utils::terminal::should_colorize()
+                    && crate::utils::terminal::supports_hyperlinks() =>
+            {
+                crate::utils::terminal::hyperlink(&rendered, url)
+            }
+            _ => rendered,
+        }
+    }
+
+    fn render_segment_content(&self, config: &SegmentConfig, data: &SegmentData) -> String {
+        let state_icon = data
+            .metadata
+            .get("icon_state")
+            .and_then(|state| crate::core::icons::state_icon(state, self.config.style.mode));
         let icon = if let Some(dynamic_icon) = data.metadata.get("dynamic_icon") {
             dynamic_icon.clone()
+        } else if let Some(state_icon) = state_icon {
+            state_icon.to_string()
         } else {
             self.get_icon(config)
         };
 
+        // Segments may override the configured text color, e.g. to flag an alert state
+        let text_color = data
+            .metadata
+            .get("text_color")
+            .and_then(|name| AnsiColor::from_name(name))
+            .or_else(|| config.colors.text.clone());
+        // The secondary text may carry its own color, e.g. a severity-colored block
+        let secondary_color = data
+            .metadata
+            .get("secondary_color")
+            .and_then(|name| AnsiColor::from_name(name))
+            .or_else(|| text_color.clone());
+
         // Apply background color to the entire segment if set
-        if let Some(bg_color) = &config.colors.background {
+        if let Some(bg_color) = self.segment_background(config) {
             let bg_code = self.apply_background_color(bg_color);
 
             // Build the entire segment content first
@@ -234,47 +556,53 @@ impl StatusLineGenerator {
             };
 
             let text_styled = self
-                .apply_style(
-                    &data.primary,
-                    config.colors.text.as_ref(),
-                    config.styles.text_bold,
-                )
+                .apply_style(&data.primary, text_color.as_ref(), config.styles.text_bold)
                 .replace("\x1b[0m", "");
 
-            let mut segment_content = format!(" {} {} ", icon_colored, text_styled);
+            let mut segment_content = if icon.is_empty() {
+                format!(" {}", text_styled)
+            } else if data.primary.is_empty() {
+                format!(" {}", icon_colored)
+            } else {
+                format!(" {} {}", icon_colored, text_styled)
+            };
 
             if !data.secondary.is_empty() {
                 let secondary_styled = self
                     .apply_style(
                         &data.secondary,
-                        config.colors.text.as_ref(),
+                        secondary_color.as_ref(),
                         config.styles.text_bold,
                     )
                     .replace("\x1b[0m", "");
-                segment_content.push_str(&format!("{} ", secondary_styled));
+                segment_content.push_str(&self.render_inner_separator(config));
+                segment_content.push_str(&secondary_styled);
             }
+            segment_content.push(' ');
 
             // Apply background to the entire content and reset at the end
             format!("{}{}\x1b[49m", bg_code, segment_content)
         } else {
             // No background color, use original logic
             let icon_colored = self.apply_color(&icon, config.colors.icon.as_ref());
-            let text_styled = self.apply_style(
-                &data.primary,
-                config.colors.text.as_ref(),
-                config.styles.text_bold,
-            );
-
-            let mut segment = format!("{} {}", icon_colored, text_styled);
+            let text_styled =
+                self.apply_style(&data.primary, text_color.as_ref(), config.styles.text_bold);
+
+            // An empty icon (e.g. a segment rendering a bare status glyph) takes no space
+            let mut segment = if icon.is_empty() {
+                text_styled
+            } else if data.primary.is_empty() {
+                icon_colored
+            } else {
+                format!("{} {}", icon_colored, text_styled)
+            };
 
             if !data.secondary.is_empty() {
-                segment.push_str(&format!(
-                    " {}",
-                    self.apply_style(
-                        &data.secondary,
-                        config.colors.text.as_ref(),
-                        config.styles.text_bold
-                    )
+                segment.push_str(&self.render_inner_separator(config));
+                segment.push_str(&self.apply_style(
+                    &data.secondary,
+                    secondary_color.as_ref(),
+                    config.styles.text_bold,
                 ));
             }
 
@@ -282,6 +610,34 @@ impl StatusLineGenerator {
         }
     }
 
+    /// Glyph joining a segment's primary and secondary text.
+    /// Configured via the `inner_separator` option (default a single space) and
+    /// `inner_separator_color`, either `"faint"` or a color table. Only the glyph
+    /// itself is wrapped, and it resets just the attribute it set so a segment
+    /// background keeps running.
+    fn render_inner_separator(&self, config: &SegmentConfig) -> String {
+        let separator = config
+            .options
+            .get("inner_separator")
+            .and_then(|v| v.as_str())
+            .unwrap_or(" ");
+
+        match config.options.get("inner_separator_color") {
+            Some(serde_json::Value::String(style)) if style == "faint" || style == "dim" => {
+                format!("\x1b[2m{}\x1b[22m", separator)
+            }
+            Some(value) => match serde_json::from_value::<AnsiColor>(value.clone()) {
+                Ok(color) => format!(
+                    "{}{}\x1b[39m",
+                    self.color_to_foreground_code(&color),
+                    separator
+                ),
+                Err(_) => separator.to_string(),
+            },
+            None => separator.to_string(),
+        }
+    }
+
     fn get_icon(&self, config: &SegmentConfig) -> String {
         match self.config.style.mode {
             StyleMode::Plain => config.icon.plain.clone(),
@@ -387,10 +743,10 @@ impl StatusLineGenerator {
         for (i, _) in rendered_segments.iter().enumerate().skip(1) {
             let prev_bg = segment_configs
                 .get(i - 1)
-                .and_then(|(config, _)| config.colors.background.as_ref());
+                .and_then(|(config, _)| self.segment_background(config));
             let curr_bg = segment_configs
                 .get(i)
-                .and_then(|(config, _)| config.colors.background.as_ref());
+                .and_then(|(config, _)| self.segment_background(config));
 
             // Create Powerline arrow with color transition
             let arrow = self.create_powerline_arrow(prev_bg, curr_bg);
@@ -461,11 +817,67 @@ pub fn collect_all_segments(
     use crate::core::segments::*;
 
     let mut results = Vec::new();
+    // Session cost can be folded into the quota segment instead of its own segment
+    let combine_session_cost = config.segments.iter().any(|segment| {
+        segment.id == crate::config::SegmentId::Quota
+            && segment.enabled
+            && segment
+                .options
+                .get("combine_session_cost")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false)
+    });
+    // The session cost is computed at most once per render: the session cost
+    // segment and quota's combine_session_cost both read it
+    let mut session_cost: Option<Option<SessionCost>> = None;
+    // Network segments listed more than once are fetched once. Quota entries share
+    // the fetch result and each renders it with its own options; usage and update
+    // have no options, so their data is reused as is
+    let mut quota_fetch: Option<QuotaFetch> = None;
+    let mut network_data: HashMap<crate::config::SegmentId, SegmentData> = HashMap::new();
 
     for segment_config in &config.segments {
+        if !crate::core::condition::should_show(segment_config.options.get("show_if"), input) {
+            continue;
+        }
+
+        if let Some(data) = network_data.get(&segment_config.id) {
+            if std::env::var("YESCODE_DEBUG").is_ok() {
+                eprintln!(
+                    "[DEBUG] Duplicate {:?} segment, reusing data from first entry",
+                    segment_config.id
+                );
+            }
+            results.push((
+                segment_config.clone(),
+                transform(segment_config, data.clone()),
+            ));
+            continue;
+        }
+
         let segment_data = match segment_config.id {
             crate::config::SegmentId::Model => {
-                let segment = ModelSegment::new();
+                let display_names = segment_config
+                    .options
+                    .get("display_names")
+                    .and_then(|v| v.as_object())
+                    .map(|names| {
+                        names
+                            .iter()
+                            .filter_map(|(pattern, name)| {
+                                name.as_str().map(|n| (pattern.clone(), n.to_string()))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let mut segment = ModelSegment::new().with_display_names(display_names);
+                if let Some(max_length) = segment_config
+                    .options
+                    .get("max_length")
+                    .and_then(|v| v.as_u64())
+                {
+                    segment = segment.with_max_length(max_length as usize);
+                }
                 segment.collect(input)
             }
             crate::config::SegmentId::Directory => {
@@ -490,6 +902,9 @@ pub fn collect_all_segments(
                 segment.collect(input)
             }
             crate::config::SegmentId::Cost => {
+                if combine_session_cost {
+                    continue;
+                }
                 let segment = CostSegment::new();
                 segment.collect(input)
             }
@@ -497,6 +912,14 @@ pub fn collect_all_segments(
                 let segment = SessionSegment::new();
                 segment.collect(input)
             }
+            crate::config::SegmentId::SessionCost => {
+                if combine_session_cost {
+                    continue;
+                }
+                let cost = *session_cost.get_or_insert_with(|| SessionCost::from_input(input));
+                let segment = SessionCostSegment::new().with_cost(cost);
+                segment.collect(input)
+            }
             crate::config::SegmentId::OutputStyle => {
                 let segment = OutputStyleSegment::new();
                 segment.collect(input)
@@ -506,15 +929,553 @@ pub fn collect_all_segments(
                 segment.collect(input)
             }
             crate::config::SegmentId::Quota => {
-                let segment = QuotaSegment::new();
+                let mut segment = QuotaSegment::new()
+                    .with_options(&segment_config.options)
+                    .with_enabled(segment_config.enabled);
+                if combine_session_cost {
+                    let cost = *session_cost.get_or_insert_with(|| SessionCost::from_input(input));
+                    segment = segment.with_session_cost(cost.map(|cost| cost.total_usd));
+                }
+                if segment.is_enabled() {
+                    if quota_fetch.is_none() {
+                        quota_fetch = segment.fetch();
+                    } else if std::env::var("YESCODE_DEBUG").is_ok() {
+                        eprintln!("[DEBUG] Duplicate Quota segment, reusing the first fetch");
+                    }
+                    quota_fetch
+                        .as_ref()
+                        .map(|fetch| segment.render(fetch, input))
+                } else {
+                    None
+                }
+            }
+            crate::config::SegmentId::Static => {
+                let template = segment_config
+                    .options
+                    .get("template")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default();
+                let segment = StaticSegment::new().with_template(template);
                 segment.collect(input)
             }
         };
 
-        if let Some(data) = segment_data {
+        // Only data from an enabled entry that produced something is reused
+        if segment_config.id.is_network()
+            && segment_config.id != crate::config::SegmentId::Quota
+            && segment_config.enabled
+        {
+            if let Some(data) = &segment_data {
+                network_data.insert(segment_config.id, data.clone());
+            }
+        }
+
+        if let Some(data) = segment_data.map(|data| transform(segment_config, data)) {
             results.push((segment_config.clone(), data));
         }
     }
 
     results
 }
+
+/// Run the segment's `transform_command` hook, if configured
+fn transform(segment_config: &SegmentConfig, data: SegmentData) -> SegmentData {
+    let Some(command) = segment_config
+        .options
+        .get("transform_command")
+        .and_then(|v| v.as_str())
+        .filter(|command| !command.trim().is_empty())
+    else {
+        return data;
+    };
+
+    let timeout_ms = segment_config
+        .options
+        .get("transform_timeout_ms")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(crate::core::transform::DEFAULT_TIMEOUT_MS);
+    crate::core::transform::apply(command, data, std::time::Duration::from_millis(timeout_ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ColorConfig, IconConfig, InputData, StyleConfig, TextStyleConfig};
+    use crate::test_support::TestEnv;
+    use std::collections::BTreeMap;
+
+    fn segment_config(id: SegmentId, options: serde_json::Value) -> SegmentConfig {
+        SegmentConfig {
+            id,
+            enabled: true,
+            icon: IconConfig {
+                plain: String::new(),
+                nerd_font: String::new(),
+            },
+            colors: ColorConfig {
+                icon: None,
+                text: None,
+                background: None,
+            },
+            styles: TextStyleConfig::default(),
+            options: serde_json::from_value(options).unwrap(),
+        }
+    }
+
+    fn data(primary: &str, secondary: &str) -> SegmentData {
+        SegmentData {
+            primary: primary.to_string(),
+            secondary: secondary.to_string(),
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    fn config(segments: Vec<SegmentConfig>) -> Config {
+        Config {
+            style: StyleConfig::default(),
+            segments,
+            theme: "test".to_string(),
+        }
+    }
+
+    fn generator(style: StyleConfig) -> StatusLineGenerator {
+        StatusLineGenerator::new(Config {
+            style,
+            ..config(Vec::new())
+        })
+    }
+
+    #[test]
+    fn truncation_logs_the_dropped_segments() {
+        let _env = TestEnv::new();
+        let segments: Vec<(SegmentConfig, SegmentData)> = [
+            (SegmentId::Model, "model"),
+            (SegmentId::Directory, "directory"),
+            (SegmentId::Git, "git"),
+            (SegmentId::Usage, "usage"),
+        ]
+        .into_iter()
+        .map(|(id, text)| (segment_config(id, serde_json::json!({})), data(text, "")))
+        .collect();
+        let generator = generator(StyleConfig {
+            separator: " ".to_string(),
+            ..StyleConfig::default()
+        });
+        let mut output: Vec<String> = segments
+            .iter()
+            .map(|(config, data)| generator.render_segment(config, data))
+            .collect();
+        let ids: Vec<SegmentId> = segments.iter().map(|(config, _)| config.id).collect();
+
+        // "model directory" is exactly 15 columns
+        let dropped = generator.truncate_to_width(&mut output, &ids, &segments, 15);
+        assert_eq!(dropped, vec![SegmentId::Usage, SegmentId::Git]);
+        assert_eq!(output.len(), 2);
+        assert_eq!(
+            drop_log(15, &dropped),
+            "Width budget 15: dropped 2 segment(s): Usage, Git"
+        );
+    }
+
+    #[test]
+    fn info_line_partitions_whole_segments_and_secondary_blocks() {
+        let _env = TestEnv::new();
+        let generator = generator(StyleConfig {
+            separator: " ".to_string(),
+            emit_trailing_reset: false,
+            info_line: true,
+            ..StyleConfig::default()
+        });
+        let segments = vec![
+            (
+                segment_config(SegmentId::Model, serde_json::json!({})),
+                data("model", ""),
+            ),
+            (
+                segment_config(SegmentId::Git, serde_json::json!({ "line": "info" })),
+                data("git", ""),
+            ),
+            (
+                segment_config(
+                    SegmentId::Quota,
+                    serde_json::json!({ "secondary_line": "info" }),
+                ),
+                data("daily", "week"),
+            ),
+        ];
+
+        let lines = generator.generate_lines(segments.clone());
+        assert_eq!(strip_escapes(&lines.primary), "model daily");
+        assert_eq!(strip_escapes(&lines.info), "git week");
+        assert_eq!(generator.generate_output(segments), "model daily\ngit week");
+    }
+
+    #[test]
+    fn line_separator_styles() {
+        let both = StatusLines {
+            primary: "model".to_string(),
+            info: "git log".to_string(),
+        };
+        for (separator, expected) in [
+            (LineSeparator::None, "model\ngit log"),
+            (LineSeparator::Blank, "model\n\ngit log"),
+            (
+                LineSeparator::Thin,
+                "model\n\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\ngit log",
+            ),
+            (
+                LineSeparator::Thick,
+                "model\n\u{2501}\u{2501}\u{2501}\u{2501}\u{2501}\u{2501}\u{2501}\ngit log",
+            ),
+        ] {
+            assert_eq!(join_lines(&both, separator), expected);
+        }
+    }
+
+    #[test]
+    fn line_separator_needs_two_non_empty_lines() {
+        let primary_only = StatusLines {
+            primary: "model".to_string(),
+            info: String::new(),
+        };
+        let info_only = StatusLines {
+            primary: String::new(),
+            info: "git".to_string(),
+        };
+        for separator in [
+            LineSeparator::None,
+            LineSeparator::Blank,
+            LineSeparator::Thin,
+            LineSeparator::Thick,
+        ] {
+            assert_eq!(join_lines(&primary_only, separator), "model");
+            assert_eq!(join_lines(&info_only, separator), "git");
+            assert_eq!(join_lines(&StatusLines::default(), separator), "");
+        }
+    }
+
+    fn with_background(id: SegmentId, background: AnsiColor) -> SegmentConfig {
+        let mut config = segment_config(id, serde_json::json!({}));
+        config.colors.background = Some(background);
+        config
+    }
+
+    #[test]
+    fn powerline_paints_segment_backgrounds() {
+        let _env = TestEnv::new();
+        let config = with_background(SegmentId::Model, AnsiColor::Color16 { c16: 4 });
+        let powerline = generator(StyleConfig {
+            mode: StyleMode::Powerline,
+            ..StyleConfig::default()
+        });
+        let rendered = powerline.render_segment(&config, &data("model", ""));
+        assert!(rendered.starts_with("\x1b[44m"), "{:?}", rendered);
+        assert_eq!(strip_escapes(&rendered), " model ");
+
+        let plain = generator(StyleConfig::default()).render_segment(&config, &data("model", ""));
+        assert_eq!(plain, "model");
+    }
+
+    #[cfg(feature = "tui")]
+    #[test]
+    fn tui_preview_paints_backgrounds_only_where_the_statusline_does() {
+        let _env = TestEnv::new();
+        crate::utils::terminal::set_ansi_host(true);
+        let segments = || {
+            vec![
+                (
+                    with_background(SegmentId::Model, AnsiColor::Color16 { c16: 4 }),
+                    data("model", ""),
+                ),
+                (
+                    with_background(SegmentId::Directory, AnsiColor::Color16 { c16: 2 }),
+                    data("dir", ""),
+                ),
+            ]
+        };
+        let painted = |generator: &StatusLineGenerator| {
+            generator
+                .generate_for_tui_preview(segments(), 80)
+                .lines
+                .iter()
+                .flat_map(|line| line.spans.iter())
+                .any(|span| !matches!(span.style.bg, None | Some(ratatui::style::Color::Reset)))
+        };
+
+        assert!(!painted(&generator(StyleConfig::default())));
+        assert!(painted(&generator(StyleConfig {
+            mode: StyleMode::Powerline,
+            ..StyleConfig::default()
+        })));
+    }
+
+    #[test]
+    fn powerline_arrow_blends_adjacent_backgrounds() {
+        let generator = generator(StyleConfig::default());
+        let blue = AnsiColor::Color16 { c16: 4 };
+        let nord = AnsiColor::from_hex("#3b4252").unwrap();
+
+        assert_eq!(
+            generator.create_powerline_arrow(Some(&blue), Some(&nord)),
+            "\x1b[48;2;59;66;82m\x1b[34m\u{e0b0}\x1b[0m"
+        );
+        assert_eq!(
+            generator.create_powerline_arrow(Some(&nord), None),
+            "\x1b[38;2;59;66;82m\u{e0b0}\x1b[0m"
+        );
+        assert_eq!(
+            generator.create_powerline_arrow(None, Some(&blue)),
+            "\x1b[44m\u{e0b0}\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn powerline_separator_joins_with_the_transition() {
+        let _env = TestEnv::new();
+        let generator = generator(StyleConfig {
+            separator: "\u{e0b0}".to_string(),
+            emit_trailing_reset: false,
+            ..StyleConfig::default()
+        });
+        let segments = vec![
+            (
+                with_background(SegmentId::Model, AnsiColor::Color16 { c16: 4 }),
+                data("model", ""),
+            ),
+            (
+                with_background(SegmentId::Git, AnsiColor::Color256 { c256: 236 }),
+                data("git", ""),
+            ),
+        ];
+        let line = generator.generate(segments);
+        assert!(
+            line.contains("\x1b[48;5;236m\x1b[34m\u{e0b0}"),
+            "{:?}",
+            line
+        );
+        assert_eq!(strip_escapes(&line), " model \u{e0b0} git ");
+    }
+
+    #[test]
+    fn faint_inner_separator_wraps_only_the_glyph() {
+        let config = segment_config(
+            SegmentId::Quota,
+            serde_json::json!({ "inner_separator": " | ", "inner_separator_color": "faint" }),
+        );
+        let rendered = generator(StyleConfig::default())
+            .render_segment(&config, &data("$3.21/$50.00", "Week: $21.50/$100"));
+        assert_eq!(rendered, "$3.21/$50.00\x1b[2m | \x1b[22mWeek: $21.50/$100");
+    }
+
+    #[test]
+    fn colored_inner_separator_resets_only_the_foreground() {
+        let config = segment_config(
+            SegmentId::Quota,
+            serde_json::json!({ "inner_separator": "/", "inner_separator_color": { "c16": 8 } }),
+        );
+        let rendered = generator(StyleConfig::default()).render_segment(&config, &data("a", "b"));
+        assert_eq!(rendered, "a\x1b[90m/\x1b[39mb");
+    }
+
+    #[test]
+    fn inner_separator_defaults_to_a_plain_space() {
+        let config = segment_config(SegmentId::Quota, serde_json::json!({}));
+        let rendered = generator(StyleConfig::default()).render_segment(&config, &data("a", "b"));
+        assert_eq!(rendered, "a b");
+    }
+
+    #[test]
+    fn faint_inner_separator_respects_the_color_gate() {
+        let env = TestEnv::new();
+        let config = segment_config(
+            SegmentId::Quota,
+            serde_json::json!({ "inner_separator": " | ", "inner_separator_color": "faint" }),
+        );
+        let generator = generator(StyleConfig::default());
+
+        env.set("CLICOLOR_FORCE", "1");
+        let colored = generator.generate_output(vec![(config.clone(), data("a", "b"))]);
+        assert_eq!(colored, "a\x1b[2m | \x1b[22mb\x1b[0m");
+
+        env.set("NO_COLOR", "1");
+        let plain = generator.generate_output(vec![(config, data("a", "b"))]);
+        assert_eq!(plain, "a | b");
+    }
+
+    #[test]
+    fn hyperlink_option_wraps_the_segment() {
+        let env = TestEnv::new();
+        env.set("CLICOLOR_FORCE", "1");
+        let config = segment_config(
+            SegmentId::Quota,
+            serde_json::json!({ "hyperlink": "https://co.yes.vg/dashboard" }),
+        );
+        let rendered = generator(StyleConfig::default()).render_segment(&config, &data("a", "b"));
+        assert_eq!(
+            rendered,
+            "\x1b]8;;https://co.yes.vg/dashboard\x1b\\a b\x1b]8;;\x1b\\"
+        );
+    }
+
+    #[test]
+    fn plain_output_has_no_hyperlinks() {
+        let env = TestEnv::new();
+        let config = segment_config(
+            SegmentId::Quota,
+            serde_json::json!({ "hyperlink": "https://co.yes.vg/dashboard" }),
+        );
+        let generator = generator(StyleConfig::default());
+
+        // Not a terminal, and NO_COLOR with links forced on
+        let plain = generator.generate_output(vec![(config.clone(), data("a", "b"))]);
+        assert!(!plain.contains("\x1b]8;"), "{:?}", plain);
+        env.set("NO_COLOR", "1");
+        env.set("FORCE_HYPERLINK", "1");
+        let plain = generator.generate_output(vec![(config, data("a", "b"))]);
+        assert!(!plain.contains("\x1b]8;"), "{:?}", plain);
+        assert_eq!(plain, "a b");
+    }
+
+    #[test]
+    fn strip_escapes_drops_csi_and_osc8() {
+        assert_eq!(
+            strip_escapes("\x1b[32m\x1b]8;;https://co.yes.vg\x1b\\quota\x1b]8;;\x1b\\\x1b[0m $5"),
+            "quota $5"
+        );
+        assert_eq!(
+            strip_escapes("\x1b]8;;https://co.yes.vg\x07a\x1b]8;;\x07 b"),
+            "a b"
+        );
+    }
+
+    #[test]
+    fn trailing_padding_inside_a_hyperlink_is_trimmed() {
+        let line = "\x1b[32mquota\x1b]8;;https://co.yes.vg\x1b\\ $5  \x1b]8;;\x1b\\  \x1b[0m";
+        assert_eq!(
+            trim_trailing_whitespace(line),
+            "\x1b[32mquota\x1b]8;;https://co.yes.vg\x1b\\ $5\x1b]8;;\x1b\\\x1b[0m"
+        );
+
+        let bel = "\x1b]8;;https://co.yes.vg\x07a \x1b]8;;\x07 ";
+        assert_eq!(
+            trim_trailing_whitespace(bel),
+            "\x1b]8;;https://co.yes.vg\x07a\x1b]8;;\x07"
+        );
+    }
+
+    #[test]
+    fn hyperlink_falls_back_to_plain_text() {
+        let env = TestEnv::new();
+        env.set("NO_COLOR", "1");
+        let config = segment_config(
+            SegmentId::Quota,
+            serde_json::json!({ "hyperlink": "https://co.yes.vg/dashboard" }),
+        );
+        let generator = generator(StyleConfig::default());
+        assert_eq!(generator.render_segment(&config, &data("a", "b")), "a b");
+
+        env.remove("NO_COLOR");
+        let config = segment_config(SegmentId::Quota, serde_json::json!({ "hyperlink": "" }));
+        assert_eq!(generator.render_segment(&config, &data("a", "b")), "a b");
+    }
+
+    #[test]
+    fn repeated_quota_entries_render_with_their_own_options() {
+        let env = TestEnv::new();
+        env.set(
+            "YESCODE_QUOTA_FIXTURE",
+            concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/quota/typical.json"),
+        );
+        let mut disabled = segment_config(SegmentId::Quota, serde_json::json!({}));
+        disabled.enabled = false;
+        let config = config(vec![
+            disabled,
+            segment_config(
+                SegmentId::Quota,
+                serde_json::json!({ "display_mode": "percent" }),
+            ),
+            segment_config(SegmentId::Quota, serde_json::json!({})),
+        ]);
+
+        let secondaries: Vec<String> = collect_all_segments(&config, &InputData::default())
+            .into_iter()
+            .map(|(_, data)| data.secondary)
+            .collect();
+        assert_eq!(secondaries, ["Week: 22%", "Week: $21.50/$100"]);
+    }
+
+    #[test]
+    fn combined_session_cost_moves_into_the_quota_segment() {
+        let env = TestEnv::new();
+        env.set(
+            "YESCODE_QUOTA_FIXTURE",
+            concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/quota/typical.json"),
+        );
+        let config = config(vec![
+            segment_config(SegmentId::Cost, serde_json::json!({})),
+            segment_config(SegmentId::SessionCost, serde_json::json!({})),
+            segment_config(
+                SegmentId::Quota,
+                serde_json::json!({ "combine_session_cost": true }),
+            ),
+        ]);
+        let input: InputData =
+            serde_json::from_value(serde_json::json!({ "cost": { "total_cost_usd": 0.43 } }))
+                .unwrap();
+
+        let segments = collect_all_segments(&config, &input);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].0.id, SegmentId::Quota);
+        assert!(segments[0].1.primary.ends_with("/$50.00 (sess $0.43)"));
+    }
+
+    fn faint_segment() -> SegmentConfig {
+        segment_config(
+            SegmentId::Quota,
+            serde_json::json!({ "inner_separator": " | ", "inner_separator_color": "faint" }),
+        )
+    }
+
+    fn render_with_reset(emit_trailing_reset: bool) -> String {
+        let generator = generator(StyleConfig {
+            emit_trailing_reset,
+            ..StyleConfig::default()
+        });
+        generator.generate_output(vec![(faint_segment(), data("a", "b"))])
+    }
+
+    #[test]
+    fn trailing_reset_with_color() {
+        let env = TestEnv::new();
+        env.set("CLICOLOR_FORCE", "1");
+        assert_eq!(render_with_reset(true), "a\x1b[2m | \x1b[22mb\x1b[0m");
+        assert_eq!(render_with_reset(false), "a\x1b[2m | \x1b[22mb");
+    }
+
+    #[test]
+    fn no_trailing_reset_without_color() {
+        let env = TestEnv::new();
+        env.set("NO_COLOR", "1");
+        assert_eq!(render_with_reset(true), "a | b");
+        assert_eq!(render_with_reset(false), "a | b");
+    }
+
+    #[test]
+    fn trailing_reset_is_never_doubled() {
+        let with_reset = generator(StyleConfig::default());
+        assert_eq!(
+            with_reset.apply_trailing_reset("\x1b[31ma\x1b[0m".to_string()),
+            "\x1b[31ma\x1b[0m"
+        );
+        assert_eq!(with_reset.apply_trailing_reset("a".to_string()), "a");
+
+        let without_reset = generator(StyleConfig {
+            emit_trailing_reset: false,
+            ..StyleConfig::default()
+        });
+        assert_eq!(
+            without_reset.apply_trailing_reset("\x1b[31ma\x1b[0m\x1b[0m".to_string()),
+            "\x1b[31ma"
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use crate::core::segments::SegmentData;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Default limit for a `transform_command` run
+pub const DEFAULT_TIMEOUT_MS: u64 = 500;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// JSON a transform command may print instead of plain text; missing fields keep their value
+#[derive(Deserialize)]
+struct TransformOutput {
+    primary: Option<String>,
+    secondary: Option<String>,
+    #[serde(default)]
+    metadata: BTreeMap<String, String>,
+}
+
+/// Pipe the segment as JSON to `command` and replace it with the command's stdout
+///
+/// Plain stdout replaces `primary` and clears `secondary`; a JSON object with
+/// `primary`/`secondary`/`metadata` updates just those fields. On a spawn failure,
+/// non-zero exit, timeout or empty stdout the original data is kept.
+pub fn apply(command: &str, data: SegmentData, timeout: Duration) -> SegmentData {
+    let result = run(command, &data, timeout).and_then(|stdout| {
+        let stdout = stdout.trim_end_matches(['\r', '\n']);
+        if stdout.trim().is_empty() {
+            Err("printed nothing".to_string())
+        } else {
+            Ok(stdout.to_string())
+        }
+    });
+
+    match result {
+        Ok(stdout) => merge(data, &stdout),
+        Err(e) => {
+            if env::var("YESCODE_DEBUG").is_ok() {
+                eprintln!("[DEBUG] transform_command `{}` failed: {}", command, e);
+            }
+            data
+        }
+    }
+}
+
+fn merge(mut data: SegmentData, stdout: &str) -> SegmentData {
+    match serde_json::from_str::<TransformOutput>(stdout) {
+        Ok(output) => {
+            if let Some(primary) = output.primary {
+                data.primary = primary;
+            }
+            if let Some(secondary) = output.secondary {
+                data.secondary = secondary;
+            }
+            data.metadata.extend(output.metadata);
+        }
+        Err(_) => {
+            data.primary = stdout.to_string();
+            data.secondary = String::new();
+        }
+    }
+    data
+}
+
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
+
+fn run(command: &str, data: &SegmentData, timeout: Duration) -> Result<String, String> {
+    let input = serde_json::to_string(data).map_err(|e| e.to_string())?;
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    // Feed stdin and drain stdout on their own threads so a chatty command can't block us
+    if let Some(mut stdin) = child.stdin.take() {
+        thread::spawn(move || {
+            let _ = stdin.write_all(input.as_bytes());
+        });
+    }
+    // A background process can inherit stdout and keep it open after the child exits,
+    // so the output is collected through a channel with the same deadline
+    let (sender, output) = mpsc::channel();
+    if let Some(mut stdout) = child.stdout.take() {
+        thread::spawn(move || {
+            let mut buffer = String::new();
+            let _ = stdout.read_to_string(&mut buffer);
+            let _ = sender.send(buffer);
+        });
+    }
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {}ms", timeout.as_millis()));
+            }
+            None => thread::sleep(POLL_INTERVAL),
+        }
+    };
+
+    if !status.success() {
+        return Err(format!("exited with {}", status));
+    }
+    output
+        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        .map_err(|_| format!("stdout still open after {}ms", timeout.as_millis()))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn data() -> SegmentData {
+        let mut metadata = BTreeMap::new();
+        metadata.insert("severity".to_string(), "ok".to_string());
+        SegmentData {
+            primary: "$3.21/$50.00".to_string(),
+            secondary: "Week: $21.50/$100".to_string(),
+            metadata,
+        }
+    }
+
+    #[test]
+    fn plain_stdout_replaces_the_text() {
+        let output = apply("echo custom", data(), TIMEOUT);
+        assert_eq!(output.primary, "custom");
+        assert_eq!(output.secondary, "");
+        assert_eq!(output.metadata["severity"], "ok");
+    }
+
+    #[test]
+    fn json_stdout_updates_the_given_fields() {
+        let stub = r#"echo '{"secondary": "wk", "metadata": {"tag": "x"}}'"#;
+        let output = apply(stub, data(), TIMEOUT);
+        assert_eq!(output.primary, "$3.21/$50.00");
+        assert_eq!(output.secondary, "wk");
+        assert_eq!(output.metadata["tag"], "x");
+    }
+
+    #[test]
+    fn the_segment_is_piped_as_json() {
+        let output = apply(r#"grep -o '"severity":"[a-z]*"'"#, data(), TIMEOUT);
+        assert_eq!(output.primary, r#""severity":"ok""#);
+    }
+
+    #[test]
+    fn failures_keep_the_original() {
+        for command in [
+            "echo partial; exit 3",
+            "",
+            "sleep 5",
+            "true",
+            "printf '\\n\\n'",
+        ] {
+            let output = apply(command, data(), Duration::from_millis(200));
+            assert_eq!(output.primary, "$3.21/$50.00", "{}", command);
+            assert_eq!(output.secondary, "Week: $21.50/$100", "{}", command);
+        }
+    }
+
+    #[test]
+    fn a_background_process_holding_stdout_does_not_block() {
+        let started = Instant::now();
+        let output = apply("sleep 5 & echo early", data(), Duration::from_millis(200));
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(output.primary, "$3.21/$50.00");
+    }
+}
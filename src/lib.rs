@@ -1,8 +1,12 @@
 pub mod cli;
+pub mod completions;
 pub mod config;
 pub mod core;
 pub mod ui;
 pub mod utils;
 
+#[cfg(test)]
+mod test_support;
+
 #[cfg(feature = "self-update")]
 pub mod updater;
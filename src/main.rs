@@ -1,11 +1,38 @@
-use ccometixline_yescc::cli::Cli;
+use ccometixline_yescc::cli::{Cli, Command, OutputFormat, QuotaArgs, StarshipArgs};
 use ccometixline_yescc::config::{Config, InputData};
 use ccometixline_yescc::core::{collect_all_segments, StatusLineGenerator};
-use std::io::{self, IsTerminal};
+use std::io::{self, IsTerminal, Read, Write};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse_args();
 
+    if cli.no_network {
+        ccometixline_yescc::utils::network::set_no_network(true);
+    }
+    // Pango output is converted from the ANSI line, so the colors must be rendered
+    if cli.format == OutputFormat::Pango {
+        ccometixline_yescc::utils::terminal::set_ansi_host(true);
+    }
+
+    match &cli.command {
+        Some(Command::Quota(args)) => return run_quota_command(args),
+        Some(Command::Starship(args)) => {
+            run_starship_command(args, cli.theme.as_deref());
+            return Ok(());
+        }
+        Some(Command::Completions(args)) => {
+            print!(
+                "{}",
+                ccometixline_yescc::completions::generate(args.shell, env!("CARGO_BIN_NAME"))
+            );
+            return Ok(());
+        }
+        None => {}
+    }
+
     // Handle configuration commands
     if cli.init {
         Config::init()?;
@@ -133,25 +160,251 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
         #[cfg(not(feature = "tui"))]
         {
-            eprintln!("No input data provided and TUI feature is not enabled.");
-            eprintln!("Usage: echo '{{...}}' | ccline");
+            print_missing_input_hint();
             eprintln!("   or: ccline --help");
+            render_without_input(config, cli.format);
         }
         return Ok(());
     }
 
+    // Claude Code reads the statusline through a pipe and renders its colors; any other
+    // pipe (a log file, `| less`) gets plain text unless CLICOLOR_FORCE asks otherwise
+    if ccometixline_yescc::utils::terminal::is_claude_code() {
+        ccometixline_yescc::utils::terminal::set_ansi_host(true);
+    }
+
     // Read Claude Code data from stdin
-    let stdin = io::stdin();
-    let input: InputData = serde_json::from_reader(stdin.lock())?;
+    let mut raw_input = String::new();
+    io::stdin().read_to_string(&mut raw_input)?;
 
-    // Collect segment data
-    let segments_data = collect_all_segments(&config, &input);
+    if raw_input.trim().is_empty() {
+        if needs_input_hint(&raw_input, io::stderr().is_terminal()) {
+            print_missing_input_hint();
+        }
+        render_without_input(config, cli.format);
+        return Ok(());
+    }
 
-    // Render statusline
-    let generator = StatusLineGenerator::new(config);
-    let statusline = generator.generate(segments_data);
+    // Unparseable input still renders the segments that don't need it
+    print_statusline(&render_line(config, Some(&raw_input)), cli.format);
 
-    println!("{}", statusline);
+    Ok(())
+}
 
+fn print_statusline(statusline: &str, format: OutputFormat) {
+    match format {
+        OutputFormat::Ansi => println!("{}", statusline),
+        OutputFormat::Pango => println!(
+            "{}",
+            ccometixline_yescc::core::pango::ansi_to_pango(statusline)
+        ),
+    }
+}
+
+/// Handle `ccline quota ...`
+#[cfg(feature = "quota")]
+fn run_quota_command(args: &QuotaArgs) -> Result<(), Box<dyn std::error::Error>> {
+    use ccometixline_yescc::core::segments::{QuotaSegment, Segment};
+
+    let config = Config::load().unwrap_or_else(|_| Config::default());
+    let options = config
+        .segments
+        .iter()
+        .find(|segment| segment.id == ccometixline_yescc::config::SegmentId::Quota)
+        .map(|segment| segment.options.clone())
+        .unwrap_or_default();
+    let segment = QuotaSegment::new().with_options(&options);
+
+    if args.refresh {
+        segment.refresh_cache();
+    } else if args.check {
+        println!("{}", segment.diagnose());
+    } else if args.summary {
+        println!("{}", segment.summary()?);
+    } else if args.json {
+        println!("{}", segment.to_json()?);
+    } else if let Some(data) = segment.collect(&InputData::default()) {
+        println!("{} {}", data.primary, data.secondary);
+    }
     Ok(())
 }
+
+#[cfg(not(feature = "quota"))]
+fn run_quota_command(_args: &QuotaArgs) -> Result<(), Box<dyn std::error::Error>> {
+    eprintln!("Quota feature is not enabled. Please install with --features quota");
+    std::process::exit(1);
+}
+
+/// Handle `ccline starship`: one line on stdout within the deadline, never blocking on stdin
+fn run_starship_command(args: &StarshipArgs, theme: Option<&str>) {
+    let deadline = Instant::now() + Duration::from_millis(args.timeout_ms);
+    // Starship captures the module's output and renders its colors
+    ccometixline_yescc::utils::terminal::set_ansi_host(true);
+
+    let mut config = Config::load().unwrap_or_else(|_| Config::default());
+    if let Some(theme) = theme {
+        config = ccometixline_yescc::ui::themes::ThemePresets::get_theme(theme);
+    }
+
+    // Starship leaves stdin attached to the shell; only read it when something is piped in
+    let raw_input = if io::stdin().is_terminal() {
+        None
+    } else {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut raw_input = String::new();
+            if io::stdin().read_to_string(&mut raw_input).is_ok() {
+                let _ = sender.send(raw_input);
+            }
+        });
+        receiver
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            .ok()
+    };
+    let raw_input = raw_input.filter(|raw_input| !raw_input.trim().is_empty());
+
+    let line = starship_line(config, raw_input, deadline);
+    let mut stdout = io::stdout();
+    let _ = stdout.write_all(line.as_bytes());
+    let _ = stdout.flush();
+}
+
+/// Render within the deadline, falling back to a cache-only render, as a single line
+fn starship_line(config: Config, raw_input: Option<String>, deadline: Instant) -> String {
+    // Render on a worker thread; past the deadline, render again from cache only
+    let (sender, receiver) = mpsc::channel();
+    {
+        let config = config.clone();
+        let raw_input = raw_input.clone();
+        thread::spawn(move || {
+            let _ = sender.send(render_line(config, raw_input.as_deref()));
+        });
+    }
+    let line = receiver
+        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        .unwrap_or_else(|_| {
+            if std::env::var("YESCODE_DEBUG").is_ok() {
+                eprintln!("[DEBUG] Starship deadline reached, rendering from cache");
+            }
+            ccometixline_yescc::utils::network::set_no_network(true);
+            render_line(config, raw_input.as_deref())
+        });
+
+    // A single line without a trailing newline; Starship adds its own spacing
+    line.replace(['\r', '\n'], " ")
+}
+
+/// Render the statusline from raw stdin JSON, or only input-independent segments without it
+fn render_line(mut config: Config, raw_input: Option<&str>) -> String {
+    let input = match raw_input.map(serde_json::from_str::<InputData>) {
+        Some(Ok(input)) => input,
+        other => {
+            if let Some(Err(e)) = other {
+                if std::env::var("YESCODE_DEBUG").is_ok() {
+                    eprintln!("[DEBUG] Ignoring invalid status input: {}", e);
+                }
+            }
+            config
+                .segments
+                .retain(|segment| segment.id.is_input_independent());
+            InputData::default()
+        }
+    };
+
+    let segments_data = collect_all_segments(&config, &input);
+    StatusLineGenerator::new(config).generate_output(segments_data)
+}
+
+/// Only hint at someone running ccline by hand; Claude Code never reads stderr
+fn needs_input_hint(raw_input: &str, stderr_is_terminal: bool) -> bool {
+    stderr_is_terminal && raw_input.trim().is_empty()
+}
+
+const MISSING_INPUT_HINT: &str = "\
+No status input on stdin: ccline expects Claude Code's statusline JSON.
+Usage: echo '{...}' | ccline
+Showing segments that don't need input (e.g. quota) only.";
+
+/// Explain the expected stdin payload when ccline is run by hand
+fn print_missing_input_hint() {
+    eprintln!("{}", MISSING_INPUT_HINT);
+}
+
+/// Render only the segments that work without Claude Code's input
+fn render_without_input(config: Config, format: OutputFormat) {
+    print_statusline(&render_line(config, None), format);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ccometixline_yescc::config::SegmentId;
+
+    fn model_only_config() -> Config {
+        let mut config = Config::default();
+        config
+            .segments
+            .retain(|segment| segment.id == SegmentId::Model);
+        config.segments[0].enabled = true;
+        config
+    }
+
+    #[test]
+    fn hint_only_for_empty_input_on_a_terminal() {
+        assert!(needs_input_hint("", true));
+        assert!(needs_input_hint("  \n", true));
+        assert!(!needs_input_hint("", false));
+        assert!(!needs_input_hint("{}", true));
+    }
+
+    #[test]
+    fn hint_describes_the_expected_input() {
+        assert!(MISSING_INPUT_HINT.contains("statusline JSON"));
+        assert!(MISSING_INPUT_HINT.contains("| ccline"));
+    }
+
+    #[test]
+    fn missing_input_drops_segments_that_need_it() {
+        let input = r#"{ "model": { "id": "claude-opus", "display_name": "Opus" } }"#;
+        assert!(render_line(model_only_config(), Some(input)).contains("Opus"));
+        assert!(!render_line(model_only_config(), None).contains("Opus"));
+        assert!(!render_line(model_only_config(), Some("not json")).contains("Opus"));
+    }
+
+    #[test]
+    fn starship_prints_one_line_without_a_trailing_newline() {
+        let input = r#"{
+            "model": { "id": "claude-opus", "display_name": "Opus" },
+            "workspace": { "current_dir": "/work/app", "project_dir": "/work/app" }
+        }"#;
+        let mut config = Config::default();
+        config
+            .segments
+            .retain(|segment| matches!(segment.id, SegmentId::Model | SegmentId::Directory));
+        for segment in &mut config.segments {
+            segment.enabled = true;
+        }
+        config.segments[1].options.insert(
+            "line".to_string(),
+            serde_json::Value::String("info".to_string()),
+        );
+        config.style.info_line = true;
+        assert!(render_line(config.clone(), Some(input)).contains('\n'));
+
+        let line = starship_line(
+            config,
+            Some(input.to_string()),
+            Instant::now() + Duration::from_secs(5),
+        );
+        assert!(line.contains("Opus") && line.contains("app"));
+        assert!(!line.contains(['\r', '\n']));
+    }
+
+    #[test]
+    fn starship_returns_promptly_past_the_deadline() {
+        let started = Instant::now();
+        let line = starship_line(model_only_config(), None, started);
+        assert!(!line.contains('\n'));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
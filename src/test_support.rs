@@ -0,0 +1,122 @@
+//! Helpers for tests that touch process-wide state (environment variables, `HOME`)
+
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+static NEXT_HOME: AtomicUsize = AtomicUsize::new(0);
+
+/// Variables read by ccline that would otherwise leak in from the developer's shell
+const ISOLATED_VARS: &[&str] = &[
+    "HOME",
+    "YESCODE_API_KEY",
+    "ANTHROPIC_API_KEY",
+    "ANTHROPIC_AUTH_TOKEN",
+    "YESCODE_BASE_URL",
+    "ANTHROPIC_BASE_URL",
+    "YESCODE_ACCOUNT",
+    "YESCODE_OFFLINE",
+    "YESCODE_QUOTA_DISABLED",
+    "YESCODE_QUOTA_FIXTURE",
+    "YESCODE_TIMEOUT_MS",
+    "YESCODE_INSECURE_TLS",
+    "YESCODE_DEBUG",
+    "CLAUDECODE",
+    "NO_COLOR",
+    "CLICOLOR",
+    "CLICOLOR_FORCE",
+    "FORCE_HYPERLINK",
+    "TERM",
+    "COLUMNS",
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+    "NO_PROXY",
+    "no_proxy",
+];
+
+/// Serializes tests that read or write the environment and gives each one an empty
+/// `HOME`, so caches and config files never touch the real `~/.claude`.
+/// Everything is restored when the value is dropped.
+pub struct TestEnv {
+    home: PathBuf,
+    saved: Vec<(&'static str, Option<OsString>)>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TestEnv {
+    pub fn new() -> Self {
+        let lock = ENV_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let saved = ISOLATED_VARS
+            .iter()
+            .map(|name| (*name, env::var_os(name)))
+            .collect();
+        for name in ISOLATED_VARS {
+            env::remove_var(name);
+        }
+
+        let home = env::temp_dir().join(format!(
+            "ccline-test-{}-{}",
+            std::process::id(),
+            NEXT_HOME.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(&home).expect("create test HOME");
+        env::set_var("HOME", &home);
+
+        crate::utils::network::set_no_network(false);
+        crate::utils::terminal::set_ansi_host(false);
+
+        Self {
+            home,
+            saved,
+            _lock: lock,
+        }
+    }
+
+    /// The temporary `HOME`, for files a test needs on disk
+    pub fn home(&self) -> &Path {
+        &self.home
+    }
+
+    pub fn set(&self, name: &str, value: &str) {
+        Self::check_isolated(name);
+        env::set_var(name, value);
+    }
+
+    pub fn remove(&self, name: &str) {
+        Self::check_isolated(name);
+        env::remove_var(name);
+    }
+
+    fn check_isolated(name: &str) {
+        assert!(
+            ISOLATED_VARS.contains(&name),
+            "{} is not restored after the test; add it to ISOLATED_VARS",
+            name
+        );
+    }
+}
+
+impl Drop for TestEnv {
+    fn drop(&mut self) {
+        for (name, value) in &self.saved {
+            match value {
+                Some(value) => env::set_var(name, value),
+                None => env::remove_var(name),
+            }
+        }
+        crate::utils::network::set_no_network(false);
+        crate::utils::terminal::set_ansi_host(false);
+        let _ = fs::remove_dir_all(&self.home);
+    }
+}
@@ -501,9 +501,11 @@ impl App {
                         SegmentId::Usage => "Usage",
                         SegmentId::Cost => "Cost",
                         SegmentId::Session => "Session",
+                        SegmentId::SessionCost => "Session Cost",
                         SegmentId::OutputStyle => "Output Style",
                         SegmentId::Update => "Update",
                         SegmentId::Quota => "Quota",
+                        SegmentId::Static => "Static",
                     };
                     let is_enabled = segment.enabled;
                     self.status_message = Some(format!(
@@ -529,9 +531,11 @@ impl App {
                                 SegmentId::Usage => "Usage",
                                 SegmentId::Cost => "Cost",
                                 SegmentId::Session => "Session",
+                                SegmentId::SessionCost => "Session Cost",
                                 SegmentId::OutputStyle => "Output Style",
                                 SegmentId::Update => "Update",
                                 SegmentId::Quota => "Quota",
+                                SegmentId::Static => "Static",
                             };
                             let is_enabled = segment.enabled;
                             self.status_message = Some(format!(
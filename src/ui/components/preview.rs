@@ -7,7 +7,7 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph},
     Frame,
 };
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 pub struct PreviewComponent {
     preview_cache: String,
@@ -99,7 +99,7 @@ impl PreviewComponent {
                     primary: "Sonnet 4".to_string(),
                     secondary: "".to_string(),
                     metadata: {
-                        let mut map = HashMap::new();
+                        let mut map = BTreeMap::new();
                         map.insert("model".to_string(), "claude-4-sonnet-20250512".to_string());
                         map
                     },
@@ -108,7 +108,7 @@ impl PreviewComponent {
                     primary: "CCometixLine".to_string(),
                     secondary: "".to_string(),
                     metadata: {
-                        let mut map = HashMap::new();
+                        let mut map = BTreeMap::new();
                         map.insert("current_dir".to_string(), "~/CCometixLine".to_string());
                         map
                     },
@@ -117,7 +117,7 @@ impl PreviewComponent {
                     primary: "master".to_string(),
                     secondary: "✓".to_string(),
                     metadata: {
-                        let mut map = HashMap::new();
+                        let mut map = BTreeMap::new();
                         map.insert("branch".to_string(), "master".to_string());
                         map.insert("status".to_string(), "Clean".to_string());
                         map.insert("ahead".to_string(), "0".to_string());
@@ -129,7 +129,7 @@ impl PreviewComponent {
                     primary: "78.2%".to_string(),
                     secondary: "· 156.4k".to_string(),
                     metadata: {
-                        let mut map = HashMap::new();
+                        let mut map = BTreeMap::new();
                         map.insert("total_tokens".to_string(), "156400".to_string());
                         map.insert("percentage".to_string(), "78.2".to_string());
                         map.insert("session_tokens".to_string(), "48200".to_string());
@@ -139,13 +139,13 @@ impl PreviewComponent {
                 SegmentId::Usage => SegmentData {
                     primary: "24%".to_string(),
                     secondary: "· 10-7-2".to_string(),
-                    metadata: HashMap::new(),
+                    metadata: BTreeMap::new(),
                 },
                 SegmentId::Cost => SegmentData {
                     primary: "$0.02".to_string(),
                     secondary: "".to_string(),
                     metadata: {
-                        let mut map = HashMap::new();
+                        let mut map = BTreeMap::new();
                         map.insert("cost".to_string(), "0.01234".to_string());
                         map
                     },
@@ -154,18 +154,28 @@ impl PreviewComponent {
                     primary: "3m45s".to_string(),
                     secondary: "+156 -23".to_string(),
                     metadata: {
-                        let mut map = HashMap::new();
+                        let mut map = BTreeMap::new();
                         map.insert("duration_ms".to_string(), "225000".to_string());
                         map.insert("lines_added".to_string(), "156".to_string());
                         map.insert("lines_removed".to_string(), "23".to_string());
                         map
                     },
                 },
+                SegmentId::SessionCost => SegmentData {
+                    primary: "$0.43".to_string(),
+                    secondary: "".to_string(),
+                    metadata: {
+                        let mut map = BTreeMap::new();
+                        map.insert("cost".to_string(), "0.4321".to_string());
+                        map.insert("source".to_string(), "input".to_string());
+                        map
+                    },
+                },
                 SegmentId::OutputStyle => SegmentData {
                     primary: "default".to_string(),
                     secondary: "".to_string(),
                     metadata: {
-                        let mut map = HashMap::new();
+                        let mut map = BTreeMap::new();
                         map.insert("style_name".to_string(), "default".to_string());
                         map
                     },
@@ -174,7 +184,7 @@ impl PreviewComponent {
                     primary: format!("v{}", env!("CARGO_PKG_VERSION")),
                     secondary: "".to_string(),
                     metadata: {
-                        let mut map = HashMap::new();
+                        let mut map = BTreeMap::new();
                         map.insert(
                             "current_version".to_string(),
                             env!("CARGO_PKG_VERSION").to_string(),
@@ -187,7 +197,7 @@ impl PreviewComponent {
                     primary: "$88.48".to_string(),
                     secondary: "".to_string(),
                     metadata: {
-                        let mut map = HashMap::new();
+                        let mut map = BTreeMap::new();
                         map.insert("raw_spent".to_string(), "88.4846".to_string());
                         map.insert(
                             "endpoint_used".to_string(),
@@ -196,6 +206,11 @@ impl PreviewComponent {
                         map
                     },
                 },
+                SegmentId::Static => SegmentData {
+                    primary: "Sonnet 4 @ CCometixLine".to_string(),
+                    secondary: "".to_string(),
+                    metadata: BTreeMap::new(),
+                },
             };
 
             segments_data.push((segment_config.clone(), mock_data));
@@ -55,9 +55,11 @@ impl SegmentListComponent {
                     SegmentId::Usage => "Usage",
                     SegmentId::Cost => "Cost",
                     SegmentId::Session => "Session",
+                    SegmentId::SessionCost => "Session Cost",
                     SegmentId::OutputStyle => "Output Style",
                     SegmentId::Update => "Update",
                     SegmentId::Quota => "Quota",
+                    SegmentId::Static => "Static",
                 };
 
                 if is_selected {
@@ -34,9 +34,11 @@ impl SettingsComponent {
                 SegmentId::Usage => "Usage",
                 SegmentId::Cost => "Cost",
                 SegmentId::Session => "Session",
+                SegmentId::SessionCost => "Session Cost",
                 SegmentId::OutputStyle => "Output Style",
                 SegmentId::Update => "Update",
                 SegmentId::Quota => "Quota",
+                SegmentId::Static => "Static",
             };
             let current_icon = match config.style.mode {
                 StyleMode::Plain => &segment.icon.plain,
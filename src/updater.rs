@@ -101,7 +101,7 @@ impl UpdateState {
             };
 
             // Trigger background update check if needed
-            if state.should_check_update() {
+            if !crate::utils::network::is_offline() && state.should_check_update() {
                 // Check if another update process is running
                 let should_start_check = if let Some(pid) = state.update_pid {
                     !Self::is_process_running(pid)
@@ -1,4 +1,6 @@
 pub mod claude_code_patcher;
 pub mod credentials;
+pub mod network;
+pub mod terminal;
 
 pub use claude_code_patcher::{ClaudeCodePatcher, LocationResult};
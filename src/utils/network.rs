@@ -0,0 +1,48 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static NO_NETWORK: AtomicBool = AtomicBool::new(false);
+
+/// Forbid all outbound requests for the rest of the process (`--no-network`)
+pub fn set_no_network(no_network: bool) {
+    NO_NETWORK.store(no_network, Ordering::Relaxed);
+}
+
+/// Whether network segments must stay on their cached/offline path.
+/// True when `--no-network` was passed or `YESCODE_OFFLINE` is set to a truthy value.
+pub fn is_offline() -> bool {
+    if NO_NETWORK.load(Ordering::Relaxed) {
+        return true;
+    }
+
+    matches!(
+        std::env::var("YESCODE_OFFLINE").as_deref(),
+        Ok("1") | Ok("true") | Ok("yes")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestEnv;
+
+    #[test]
+    fn flag_forces_offline() {
+        let _env = TestEnv::new();
+        assert!(!is_offline());
+        set_no_network(true);
+        assert!(is_offline());
+    }
+
+    #[test]
+    fn yescode_offline_accepts_truthy_values() {
+        let env = TestEnv::new();
+        for value in ["1", "true", "yes"] {
+            env.set("YESCODE_OFFLINE", value);
+            assert!(is_offline(), "YESCODE_OFFLINE={}", value);
+        }
+        for value in ["0", "false", ""] {
+            env.set("YESCODE_OFFLINE", value);
+            assert!(!is_offline(), "YESCODE_OFFLINE={}", value);
+        }
+    }
+}
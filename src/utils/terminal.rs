@@ -0,0 +1,227 @@
+use std::env;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ANSI_HOST: AtomicBool = AtomicBool::new(false);
+
+/// Declare that stdout is read by a program that renders ANSI itself (Claude Code,
+/// Starship, the Pango converter), so colors are kept although stdout is a pipe
+pub fn set_ansi_host(host: bool) {
+    ANSI_HOST.store(host, Ordering::Relaxed);
+}
+
+/// Whether ccline was started by Claude Code, which exports `CLAUDECODE` to the
+/// statusline command and renders the ANSI it reads from the pipe
+pub fn is_claude_code() -> bool {
+    env::var_os("CLAUDECODE").is_some_and(|value| !value.is_empty())
+}
+
+/// Whether ANSI colors may be emitted.
+/// A non-empty `NO_COLOR` turns them off, then `CLICOLOR_FORCE` (other than `0`) turns
+/// them on; `CLICOLOR=0` and `TERM=dumb` turn them off. Otherwise colors are used when
+/// stdout is a terminal or an ANSI host (see `set_ansi_host`).
+pub fn should_colorize() -> bool {
+    if env::var("NO_COLOR").is_ok_and(|value| !value.is_empty()) {
+        return false;
+    }
+    if env::var("CLICOLOR_FORCE").is_ok_and(|value| !value.is_empty() && value != "0") {
+        return true;
+    }
+    if matches!(env::var("CLICOLOR").as_deref(), Ok("0"))
+        || matches!(env::var("TERM").as_deref(), Ok("dumb"))
+    {
+        return false;
+    }
+
+    ANSI_HOST.load(Ordering::Relaxed) || io::stdout().is_terminal()
+}
+
+/// Whether OSC 8 hyperlinks may be emitted.
+/// `FORCE_HYPERLINK` overrides detection either way; otherwise links are
+/// suppressed for dumb terminals and when a non-empty `NO_COLOR` asks for plain
+/// output. Links are still only written alongside colors (see `should_colorize`).
+pub fn supports_hyperlinks() -> bool {
+    if let Ok(force) = env::var("FORCE_HYPERLINK") {
+        return force != "0";
+    }
+
+    if env::var("NO_COLOR").is_ok_and(|value| !value.is_empty()) {
+        return false;
+    }
+
+    !matches!(env::var("TERM").as_deref(), Ok("dumb"))
+}
+
+/// Wrap text in an OSC 8 hyperlink escape sequence
+pub fn hyperlink(text: &str, url: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+/// Width used when no other source is available
+pub const DEFAULT_WIDTH: usize = 80;
+
+/// Resolve the statusline width budget: `max_width` config, then `COLUMNS`,
+/// then the terminal size, falling back to 80 columns.
+pub fn resolve_width(max_width: Option<usize>) -> usize {
+    #[cfg(feature = "tui")]
+    let terminal_columns = crossterm::terminal::size().ok().map(|(columns, _)| columns);
+    #[cfg(not(feature = "tui"))]
+    let terminal_columns = None;
+
+    width_from_sources(
+        max_width,
+        env::var("COLUMNS").ok().as_deref(),
+        terminal_columns,
+    )
+}
+
+fn width_from_sources(
+    max_width: Option<usize>,
+    columns_env: Option<&str>,
+    terminal_columns: Option<u16>,
+) -> usize {
+    if let Some(width) = max_width.filter(|w| *w > 0) {
+        return width;
+    }
+
+    if let Some(width) = columns_env
+        .and_then(|columns| columns.trim().parse::<usize>().ok())
+        .filter(|w| *w > 0)
+    {
+        return width;
+    }
+
+    if let Some(columns) = terminal_columns.filter(|columns| *columns > 0) {
+        return columns as usize;
+    }
+
+    if env::var("YESCODE_DEBUG").is_ok() {
+        eprintln!(
+            "[DEBUG] Terminal width unavailable, using {} columns",
+            DEFAULT_WIDTH
+        );
+    }
+    DEFAULT_WIDTH
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestEnv;
+
+    #[test]
+    fn no_color_wins_over_every_other_setting() {
+        let env = TestEnv::new();
+        set_ansi_host(true);
+        assert!(should_colorize());
+
+        env.set("NO_COLOR", "1");
+        env.set("CLICOLOR_FORCE", "1");
+        assert!(!should_colorize());
+
+        // An empty NO_COLOR does not count
+        env.set("NO_COLOR", "");
+        assert!(should_colorize());
+    }
+
+    #[test]
+    fn clicolor_force_keeps_colors_on_a_pipe() {
+        let env = TestEnv::new();
+        env.set("CLICOLOR_FORCE", "1");
+        env.set("CLICOLOR", "0");
+        env.set("TERM", "dumb");
+        assert!(should_colorize());
+
+        env.set("CLICOLOR_FORCE", "0");
+        assert!(!should_colorize());
+    }
+
+    #[test]
+    fn clicolor_zero_and_dumb_terminals_turn_colors_off() {
+        let env = TestEnv::new();
+        set_ansi_host(true);
+        env.set("CLICOLOR", "0");
+        assert!(!should_colorize());
+
+        env.set("CLICOLOR", "1");
+        assert!(should_colorize());
+
+        env.set("TERM", "dumb");
+        assert!(!should_colorize());
+    }
+
+    #[test]
+    fn piped_output_is_plain_unless_an_ansi_host_reads_it() {
+        let _env = TestEnv::new();
+        // cargo test captures stdout, so it is never a terminal here
+        assert!(!should_colorize());
+
+        set_ansi_host(true);
+        assert!(should_colorize());
+    }
+
+    #[test]
+    fn claude_code_is_detected_from_its_environment_marker() {
+        let env = TestEnv::new();
+        assert!(!is_claude_code());
+
+        env.set("CLAUDECODE", "");
+        assert!(!is_claude_code());
+
+        env.set("CLAUDECODE", "1");
+        assert!(is_claude_code());
+    }
+
+    #[test]
+    fn hyperlink_wraps_text_in_osc8() {
+        assert_eq!(
+            hyperlink("quota", "https://co.yes.vg"),
+            "\x1b]8;;https://co.yes.vg\x1b\\quota\x1b]8;;\x1b\\"
+        );
+    }
+
+    #[test]
+    fn hyperlinks_are_suppressed_for_plain_output() {
+        let env = TestEnv::new();
+        assert!(supports_hyperlinks());
+
+        env.set("TERM", "dumb");
+        assert!(!supports_hyperlinks());
+
+        env.set("TERM", "xterm-256color");
+        env.set("NO_COLOR", "1");
+        assert!(!supports_hyperlinks());
+
+        // An empty NO_COLOR is ignored, as in should_colorize
+        env.set("NO_COLOR", "");
+        assert!(supports_hyperlinks());
+    }
+
+    #[test]
+    fn force_hyperlink_overrides_detection() {
+        let env = TestEnv::new();
+        env.set("NO_COLOR", "1");
+        env.set("FORCE_HYPERLINK", "1");
+        assert!(supports_hyperlinks());
+
+        env.remove("NO_COLOR");
+        env.set("FORCE_HYPERLINK", "0");
+        assert!(!supports_hyperlinks());
+    }
+
+    #[test]
+    fn width_defaults_to_80_without_any_source() {
+        assert_eq!(width_from_sources(None, None, None), DEFAULT_WIDTH);
+        assert_eq!(
+            width_from_sources(Some(0), Some("junk"), Some(0)),
+            DEFAULT_WIDTH
+        );
+    }
+
+    #[test]
+    fn width_sources_in_priority_order() {
+        assert_eq!(width_from_sources(Some(60), Some("100"), Some(120)), 60);
+        assert_eq!(width_from_sources(None, Some(" 100 "), Some(120)), 100);
+        assert_eq!(width_from_sources(None, None, Some(120)), 120);
+    }
+}